@@ -1,11 +1,31 @@
 use crate::auth::license_validator::LicenseValidator;
-use crate::logger::logger::{log_error, log_info};
+use crate::config::settings::Settings;
+use crate::logger::logger::{log_error, log_info, log_warn};
+use rand::Rng;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::net::UdpSocket;
+use tokio::task::JoinSet;
 use tokio::time;
 
+// Allowed gap between the system clock and NTP time before it counts as manipulation.
+const TIME_MANIPULATION_THRESHOLD_SECS: u64 = 60;
+// Network time needs this many NTP answers (or one from each server, if fewer are configured),
+// and drops any answer this far from their median.
+const NTP_MIN_SAMPLES: usize = 2;
+const NTP_OUTLIER_SECS: u64 = 5;
+// Anything tighter would mostly be hammering the NTP servers.
+const MIN_LICENSE_CHECK_INTERVAL_SECS: u64 = 30;
+const MAX_LICENSE_CHECK_JITTER: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeCheck {
+    Verified,
+    Unverified,
+    Manipulated(u64),
+}
+
 pub struct LicenseChecker {
     validator: Arc<LicenseValidator>,
     is_running: Arc<AtomicBool>
@@ -19,98 +39,247 @@ impl LicenseChecker {
         }
     }
 
-    pub async fn detect_time_manipulation() -> bool {
-        use std::cmp::{max, min};
-
+    pub async fn detect_time_manipulation() -> TimeCheck {
         let system_time = SystemTime::now()
             .duration_since(UNIX_EPOCH)
-            .unwrap()
+            .unwrap_or_default()
             .as_secs();
 
         let network_time = Self::fetch_network_time().await;
-        let difference = max(system_time, network_time) - min(system_time, network_time);
+        let result = Self::classify_time(system_time, network_time, TIME_MANIPULATION_THRESHOLD_SECS);
 
-        if difference >= 60 {
+        if let TimeCheck::Manipulated(difference) = result {
             log_error(&format!("Time manipulation detected: {}s difference", difference), "LicenseChecker::detect_time_manipulation");
-            return false;
         }
-        true
+        result
     }
 
-    pub async fn fetch_network_time() -> u64 {
-        let ntp_servers = [
-            "pool.ntp.org",
-            "time.google.com",
-            "time.windows.com",
-            "time.apple.com"
-        ];
-
-        for server in ntp_servers {
-            if let Ok(time) = Self::fetch_time_from_server(server).await {
-                return time;
+    fn classify_time(system_time: u64, network_time: Option<u64>, threshold_secs: u64) -> TimeCheck {
+        match network_time {
+            None => TimeCheck::Unverified,
+            Some(network_time) => {
+                let difference = system_time.abs_diff(network_time);
+                if difference >= threshold_secs {
+                    TimeCheck::Manipulated(difference)
+                } else {
+                    TimeCheck::Verified
+                }
             }
         }
+    }
 
-        let fallback_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
+    // Asks every server at once and takes the median, so one misconfigured server can't decide the result
+    // on its own. Servers used to be tried in order with the first answer winning; a single configured
+    // server still verifies the time on its own answer.
+    pub async fn fetch_network_time() -> Option<u64> {
+        let context = "LicenseChecker::fetch_network_time";
+        let settings = Settings::load().unwrap_or_default();
+        let timeout = Duration::from_millis(settings.ntp_timeout_ms.max(1));
+        let port = settings.ntp_port;
+
+        let servers: Vec<String> = settings.ntp_servers.iter()
+            .map(|server| server.trim().to_string())
+            .filter(|server| !server.is_empty())
+            .collect();
+        let required_samples = Self::required_samples(servers.len());
+
+        let mut queries = JoinSet::new();
+        for server in servers {
+            queries.spawn(async move {
+                let result = Self::fetch_time_from_server(&server, port, timeout).await;
+                (server, result)
+            });
+        }
+
+        let mut samples = Vec::new();
+        while let Some(joined) = queries.join_next().await {
+            match joined {
+                Ok((server, Ok(network_time))) => {
+                    log_info(&format!("Network time received from {}:{}", server, port), context);
+                    samples.push(network_time);
+                }
+                Ok((server, Err(e))) => {
+                    log_warn(&format!("NTP server {}:{} failed: {}", server, port, e), context);
+                }
+                Err(e) => {
+                    log_warn(&format!("NTP query task failed: {}", e), context);
+                }
+            }
+        }
+
+        if samples.len() < required_samples {
+            log_error(&format!("Only {} NTP server(s) answered, need at least {} to verify the time",
+                               samples.len(), required_samples), context);
+            return None;
+        }
+
+        let network_time = Self::consensus_time(samples, NTP_OUTLIER_SECS);
+        if network_time.is_none() {
+            log_error("NTP servers disagree with each other, could not settle on a network time", context);
+        }
+        network_time
+    }
+
+    fn required_samples(server_count: usize) -> usize {
+        NTP_MIN_SAMPLES.min(server_count).max(1)
+    }
+
+    // Median of the samples within outlier_secs of the overall median; None if no sample is.
+    fn consensus_time(mut samples: Vec<u64>, outlier_secs: u64) -> Option<u64> {
+        let median = Self::median(&mut samples)?;
+        let total = samples.len();
+        let mut agreeing: Vec<u64> = samples.into_iter()
+            .filter(|sample| sample.abs_diff(median) <= outlier_secs)
+            .collect();
+
+        let dropped = total - agreeing.len();
+        if dropped > 0 {
+            log_warn(&format!("Ignored {} NTP answer(s) more than {}s from the median", dropped, outlier_secs),
+                     "LicenseChecker::consensus_time");
+        }
+        Self::median(&mut agreeing)
+    }
+
+    fn median(samples: &mut [u64]) -> Option<u64> {
+        if samples.is_empty() {
+            return None;
+        }
 
-        log_error("Failed to fetch network time from all servers", "LicenseChecker::fetch_network_time");
-        fallback_time
+        samples.sort_unstable();
+        let middle = samples.len() / 2;
+        if samples.len() % 2 == 0 {
+            Some(samples[middle - 1] + (samples[middle] - samples[middle - 1]) / 2)
+        } else {
+            Some(samples[middle])
+        }
     }
 
-    async fn fetch_time_from_server(server: &str) -> Result<u64, Box<dyn std::error::Error>> {
-        let socket = UdpSocket::bind("0.0.0.0:0").await?;
-        socket.connect(format!("{}:123", server)).await?;
+    // The timeout covers the whole exchange, including DNS resolution, so one dead server can't stall the rest.
+    async fn fetch_time_from_server(server: &str, port: u16, timeout: Duration) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        let exchange = async {
+            let socket = UdpSocket::bind("0.0.0.0:0").await?;
+            socket.connect((server, port)).await?;
 
-        let ntp_msg = [0x1B; 48];
-        socket.send(&ntp_msg).await?;
+            let ntp_msg = [0x1B; 48];
+            socket.send(&ntp_msg).await?;
 
-        let mut buf = [0; 48];
-        let timeout = time::timeout(Duration::from_secs(5), socket.recv(&mut buf)).await??;
+            let mut buf = [0; 48];
+            let received = socket.recv(&mut buf).await?;
+            Ok::<_, std::io::Error>((received, buf))
+        };
 
-        if timeout < 48 {
+        let (received, buf) = time::timeout(timeout, exchange).await
+            .map_err(|_| format!("no response within {}ms", timeout.as_millis()))??;
+
+        if received < 48 {
             return Err("Incomplete NTP response".into());
         }
 
         let ntp_seconds = u32::from_be_bytes([buf[40], buf[41], buf[42], buf[43]]);
+        if ntp_seconds == 0 {
+            return Err("NTP response carried no transmit timestamp".into());
+        }
         Ok((ntp_seconds as u64).saturating_sub(2208988800))
     }
 
+    fn next_check_interval() -> Duration {
+        let settings = Settings::load().unwrap_or_default();
+        let base = settings.license_check_interval_secs.max(MIN_LICENSE_CHECK_INTERVAL_SECS) as f64;
+        let jitter = if settings.license_check_jitter.is_nan() {
+            0.0
+        } else {
+            settings.license_check_jitter.clamp(0.0, MAX_LICENSE_CHECK_JITTER)
+        };
+        let factor = rand::rng().random_range((1.0 - jitter)..=(1.0 + jitter));
+        Duration::from_secs_f64(base * factor)
+    }
+
+    // Waits in one-second slices so clearing is_running ends the loop without sitting out the interval.
+    // Returns false once it has been cleared.
+    async fn wait_for_next_check(is_running: &AtomicBool) -> bool {
+        let deadline = time::Instant::now() + Self::next_check_interval();
+        while is_running.load(Ordering::SeqCst) {
+            let now = time::Instant::now();
+            if now >= deadline {
+                return true;
+            }
+            time::sleep((deadline - now).min(Duration::from_secs(1))).await;
+        }
+        false
+    }
+
     pub async fn start_checking(&self) {
         let validator = Arc::clone(&self.validator);
         let is_running = Arc::clone(&self.is_running);
 
         tokio::spawn(async move {
-            let mut interval = time::interval(Duration::from_secs(150));
-
+            // The first check runs right away; later ones wait a jittered interval, re-read each time.
             loop {
-                interval.tick().await;
-
                 if !is_running.load(Ordering::SeqCst) {
                     break;
                 }
 
-                if !Self::detect_time_manipulation().await {
-                    log_error("DTM detected - exiting", "LicenseChecker::start_checking");
-                    std::process::exit(1);
+                match Self::detect_time_manipulation().await {
+                    TimeCheck::Verified => {}
+                    TimeCheck::Unverified => {
+                        log_warn("Could not verify system time against NTP, skipping this check", "LicenseChecker::start_checking");
+                    }
+                    TimeCheck::Manipulated(_) => {
+                        log_error("DTM detected - exiting", "LicenseChecker::start_checking");
+                        std::process::exit(1);
+                    }
                 }
 
                 match validator.validate_license() {
-                    Ok(true) => {
+                    Ok(()) => {
                         log_info("License check passed", "LicenseChecker::start_checking");
                     }
-                    Ok(false) => {
-                        log_error("License has expired or is invalid", "LicenseChecker::start_checking");
+                    Err(e) if e.is_fatal() => {
+                        log_error(&format!("License validation failed: {} - exiting", e), "LicenseChecker::start_checking");
                         std::process::exit(1);
                     }
                     Err(e) => {
-                        log_error(&format!("License validation error: {}", e), "LicenseChecker::start_checking");
-                        std::process::exit(1);
+                        log_warn(&format!("License validation error, retrying next check: {}", e), "LicenseChecker::start_checking");
                     }
                 }
+
+                if !Self::wait_for_next_check(&is_running).await {
+                    break;
+                }
             }
         });
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SYSTEM_TIME: u64 = 1_767_225_600;
+
+    #[test]
+    fn no_network_time_is_unverified() {
+        assert_eq!(LicenseChecker::classify_time(SYSTEM_TIME, None, TIME_MANIPULATION_THRESHOLD_SECS), TimeCheck::Unverified);
+    }
+
+    #[test]
+    fn a_clock_within_the_threshold_is_verified() {
+        for network_time in [SYSTEM_TIME, SYSTEM_TIME - 59, SYSTEM_TIME + 59] {
+            assert_eq!(LicenseChecker::classify_time(SYSTEM_TIME, Some(network_time), TIME_MANIPULATION_THRESHOLD_SECS), TimeCheck::Verified);
+        }
+    }
+
+    #[test]
+    fn a_clock_off_by_the_threshold_or_more_is_manipulated() {
+        assert_eq!(LicenseChecker::classify_time(SYSTEM_TIME, Some(SYSTEM_TIME - 60), TIME_MANIPULATION_THRESHOLD_SECS), TimeCheck::Manipulated(60));
+        assert_eq!(LicenseChecker::classify_time(SYSTEM_TIME, Some(SYSTEM_TIME + 3_600), TIME_MANIPULATION_THRESHOLD_SECS), TimeCheck::Manipulated(3_600));
+    }
+
+    #[test]
+    fn fewer_servers_than_the_minimum_need_an_answer_from_each() {
+        assert_eq!(LicenseChecker::required_samples(0), 1);
+        assert_eq!(LicenseChecker::required_samples(1), 1);
+        assert_eq!(LicenseChecker::required_samples(2), NTP_MIN_SAMPLES);
+        assert_eq!(LicenseChecker::required_samples(5), NTP_MIN_SAMPLES);
+    }
 }
\ No newline at end of file
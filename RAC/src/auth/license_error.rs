@@ -0,0 +1,61 @@
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum LicenseError {
+    NotFound,
+    MachineMismatch,
+    Expired,
+    BadSignature,
+    Malformed(String),
+    Decrypt(String),
+    Io(io::Error),
+}
+
+impl LicenseError {
+    // Io covers transient read failures (file locked by an AV scan, etc.); everything else means the license is unusable.
+    pub fn is_fatal(&self) -> bool {
+        !matches!(self, LicenseError::Io(_))
+    }
+}
+
+impl fmt::Display for LicenseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LicenseError::NotFound => write!(f, "License file not found. Please contact your administrator."),
+            LicenseError::MachineMismatch => write!(f, "License was issued for a different machine"),
+            LicenseError::Expired => write!(f, "License has expired"),
+            LicenseError::BadSignature => write!(f, "Invalid license signature"),
+            LicenseError::Malformed(message) => write!(f, "Malformed license: {}", message),
+            LicenseError::Decrypt(message) => write!(f, "License decryption failed: {}", message),
+            LicenseError::Io(e) => write!(f, "License I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for LicenseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LicenseError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for LicenseError {
+    fn from(e: io::Error) -> Self {
+        LicenseError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for LicenseError {
+    fn from(e: serde_json::Error) -> Self {
+        LicenseError::Malformed(e.to_string())
+    }
+}
+
+impl From<base64::DecodeError> for LicenseError {
+    fn from(e: base64::DecodeError) -> Self {
+        LicenseError::Malformed(e.to_string())
+    }
+}
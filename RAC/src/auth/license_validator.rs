@@ -7,14 +7,16 @@ use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::path::PathBuf;
 use std::process::Command;
-use std::{env, fs};
+use std::fs;
 use time::OffsetDateTime;
 
+use crate::auth::license_error::LicenseError;
+use crate::config::data_dir;
 use crate::logger::logger::{log_error, log_info, log_warn};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LicenseInfo {
-    machine_id: String,
+    pub(crate) machine_id: String,
     pub(crate) expires_at: i64,
 }
 
@@ -39,13 +41,7 @@ impl LicenseValidator {
         protected_encryption: Vec<u8>,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         let machine_id = Self::get_machine_id()?;
-        let local_appdata = env::var("LOCALAPPDATA")?;
-        let license_dir = PathBuf::from(local_appdata).join("RAC");
-
-        if !license_dir.exists() {
-            fs::create_dir_all(&license_dir)?;
-            log_info("Created license directory", "LicenseValidator::new");
-        }
+        let license_dir = data_dir::data_dir()?;
 
         log_info(
             &format!("Initialized LicenseValidator with machine ID: {}", machine_id),
@@ -69,37 +65,113 @@ impl LicenseValidator {
         self.license_dir.to_string_lossy().replace("\\\\", "\\")
     }
 
-    pub fn get_license_info(&self) -> Result<LicenseInfo, Box<dyn std::error::Error>> {
+    pub fn get_license_info(&self) -> Result<LicenseInfo, LicenseError> {
         let license_path = self
             .license_dir
             .join(self.machine_id.to_string() + ".license");
+        if !license_path.exists() {
+            return Err(LicenseError::NotFound);
+        }
+
         let encrypted_data = fs::read(&license_path)?;
         let license_data = self.decrypt_license_data(&encrypted_data)?;
         let license: License = serde_json::from_str(&license_data)?;
         Ok(license.info)
     }
 
+    // Licenses are issued against the SMBIOS UUID (what `wmic csproduct get UUID` printed), so the
+    // WMI query comes first. MachineGuid is a different value: a license tied to it must be reissued.
     fn get_machine_id() -> Result<String, Box<dyn std::error::Error>> {
-        #[cfg(target_os = "windows")]
-        {
-            let output = Command::new("wmic")
-                .args(["csproduct", "get", "UUID"])
-                .output()?;
-            let stdout = String::from_utf8(output.stdout)?;
-            let uuid = stdout
-                .lines()
-                .nth(1)
-                .ok_or("Failed to get UUID")?
-                .trim()
-                .to_string();
-            Ok(uuid)
+        let context = "LicenseValidator::get_machine_id";
+
+        match Self::query_wmi_uuid() {
+            Ok(uuid) => return Ok(uuid),
+            Err(e) => log_warn(&format!("WMI UUID lookup failed: {}", e), context),
+        }
+
+        match Self::read_machine_guid() {
+            Ok(guid) => {
+                log_warn("Using registry MachineGuid as machine ID; existing licenses for this machine need to be reissued", context);
+                return Ok(guid);
+            }
+            Err(e) => log_warn(&format!("Registry MachineGuid lookup failed: {}", e), context),
+        }
+
+        Self::query_wmic_uuid()
+    }
+
+    fn query_wmi_uuid() -> Result<String, Box<dyn std::error::Error>> {
+        #[derive(Deserialize)]
+        #[serde(rename = "Win32_ComputerSystemProduct")]
+        struct ComputerSystemProduct {
+            #[serde(rename = "UUID")]
+            uuid: String,
         }
+
+        let com_library = wmi::COMLibrary::new()
+            .or_else(|_| -> Result<_, wmi::WMIError> { Ok(unsafe { wmi::COMLibrary::assume_initialized() }) })?;
+        let connection = wmi::WMIConnection::new(com_library)?;
+        let products: Vec<ComputerSystemProduct> = connection.query()?;
+
+        let uuid = products
+            .into_iter()
+            .map(|product| product.uuid.trim().to_string())
+            .find(|uuid| !uuid.is_empty())
+            .ok_or("Win32_ComputerSystemProduct returned no UUID")?;
+        Ok(uuid)
     }
 
-    fn decrypt_license_data(&self, encrypted_data: &[u8]) -> Result<String, Box<dyn std::error::Error>> {
+    fn read_machine_guid() -> Result<String, Box<dyn std::error::Error>> {
+        use windows::core::w;
+        use windows::Win32::System::Registry::{RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ, RRF_SUBKEY_WOW6464KEY};
+
+        let mut buffer = [0u16; 128];
+        let mut size = (buffer.len() * std::mem::size_of::<u16>()) as u32;
+
+        let status = unsafe {
+            RegGetValueW(
+                HKEY_LOCAL_MACHINE,
+                w!("SOFTWARE\\Microsoft\\Cryptography"),
+                w!("MachineGuid"),
+                RRF_RT_REG_SZ | RRF_SUBKEY_WOW6464KEY,
+                None,
+                Some(buffer.as_mut_ptr() as *mut _),
+                Some(&mut size),
+            )
+        };
+        if status.is_err() {
+            return Err(format!("RegGetValueW failed with error {}", status.0).into());
+        }
+
+        let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+        let guid = String::from_utf16(&buffer[..len])?.trim().to_uppercase();
+        if guid.is_empty() {
+            return Err("MachineGuid is empty".into());
+        }
+        Ok(guid)
+    }
+
+    fn query_wmic_uuid() -> Result<String, Box<dyn std::error::Error>> {
+        let output = Command::new("wmic")
+            .args(["csproduct", "get", "UUID"])
+            .output()?;
+        let stdout = String::from_utf8(output.stdout)?;
+        let uuid = stdout
+            .lines()
+            .nth(1)
+            .ok_or("Failed to get UUID")?
+            .trim()
+            .to_string();
+        if uuid.is_empty() {
+            return Err("wmic returned an empty UUID".into());
+        }
+        Ok(uuid)
+    }
+
+    fn decrypt_license_data(&self, encrypted_data: &[u8]) -> Result<String, LicenseError> {
         if encrypted_data.len() < 12 {
             log_error("Invalid encrypted data length", "decrypt_license_data");
-            return Err("Invalid encrypted data length".into());
+            return Err(LicenseError::Decrypt("invalid encrypted data length".to_string()));
         }
 
         match self.decrypt_license_data_internal(encrypted_data) {
@@ -117,14 +189,16 @@ impl LicenseValidator {
     fn decrypt_license_data_internal(
         &self,
         encrypted_data: &[u8],
-    ) -> Result<String, Box<dyn std::error::Error>> {
+    ) -> Result<String, LicenseError> {
         let xored_encryption_key: Vec<u8> = self
             .protected_encryption
             .iter()
             .enumerate()
             .map(|(i, &byte)| byte ^ self.xor_key[i % self.xor_key.len()])
             .collect();
-        let decoded_key = general_purpose::STANDARD.decode(&xored_encryption_key)?;
+        let decoded_key = general_purpose::STANDARD
+            .decode(&xored_encryption_key)
+            .map_err(|e| LicenseError::Decrypt(format!("invalid encryption key: {}", e)))?;
         let key = Key::<Aes256Gcm>::from_slice(&decoded_key);
         let cipher = Aes256Gcm::new(key);
 
@@ -133,19 +207,27 @@ impl LicenseValidator {
 
         let decrypted = cipher
             .decrypt(nonce, ciphertext)
-            .map_err(|e| format!("Decryption failed: {}", e))?;
+            .map_err(|e| LicenseError::Decrypt(e.to_string()))?;
 
-        String::from_utf8(decrypted).map_err(|e| format!("Invalid UTF-8: {}", e).into())
+        String::from_utf8(decrypted).map_err(|e| LicenseError::Decrypt(format!("invalid UTF-8: {}", e)))
     }
 
-    pub fn validate_license(&self) -> Result<bool, Box<dyn std::error::Error>> {
+    pub fn is_expired(expires_at: i64, now: i64) -> bool {
+        now > expires_at
+    }
+
+    pub fn validate_license(&self) -> Result<(), LicenseError> {
+        self.validate_license_at(OffsetDateTime::now_utc().unix_timestamp())
+    }
+
+    pub fn validate_license_at(&self, now: i64) -> Result<(), LicenseError> {
         let license_path = self
             .license_dir
             .join(self.machine_id.to_string() + ".license");
 
         if !license_path.exists() {
             log_error("License file not found", "validate_license");
-            return Err("License file not found. Please contact your administrator.".into());
+            return Err(LicenseError::NotFound);
         }
 
         log_info("Starting license validation", "validate_license");
@@ -156,32 +238,27 @@ impl LicenseValidator {
 
         if license.info.machine_id != self.machine_id {
             log_warn("Machine ID mismatch detected", "validate_license");
-            return Ok(false);
+            return Err(LicenseError::MachineMismatch);
         }
 
-        let now = OffsetDateTime::now_utc().unix_timestamp();
-        if now > license.info.expires_at {
+        if Self::is_expired(license.info.expires_at, now) {
             log_warn("License has expired", "validate_license");
-            return Ok(false);
+            return Err(LicenseError::Expired);
         }
 
         match self.verify_signature(&license) {
-            Ok(true) => {
+            Ok(()) => {
                 log_info("License validation successful", "validate_license");
-                Ok(true)
-            }
-            Ok(false) => {
-                log_warn("Invalid license signature", "validate_license");
-                Ok(false)
+                Ok(())
             }
             Err(e) => {
-                log_error(&format!("Signature verification error: {}", e), "validate_license");
+                log_warn(&format!("Signature verification failed: {}", e), "validate_license");
                 Err(e)
             }
         }
     }
 
-    fn verify_signature(&self, license: &License) -> Result<bool, Box<dyn std::error::Error>> {
+    fn verify_signature(&self, license: &License) -> Result<(), LicenseError> {
         let public_key_bytes = &self.protected_public;
 
         let xored_public_key: Vec<u8> = public_key_bytes
@@ -191,7 +268,8 @@ impl LicenseValidator {
             .collect();
         let public_key_str = String::from_utf8_lossy(&xored_public_key);
 
-        let public_key = RsaPublicKey::from_public_key_pem(public_key_str.as_ref())?;
+        let public_key = RsaPublicKey::from_public_key_pem(public_key_str.as_ref())
+            .map_err(|e| LicenseError::Malformed(format!("invalid public key: {}", e)))?;
         let info_bytes = serde_json::to_vec(&license.info)?;
 
         let mut hasher = Sha256::new();
@@ -200,12 +278,37 @@ impl LicenseValidator {
 
         let signature_bytes = general_purpose::STANDARD.decode(&license.signature)?;
 
-        Ok(public_key
+        public_key
             .verify(
                 rsa::Pkcs1v15Sign::new::<Sha256>(),
                 &hash,
                 &signature_bytes,
             )
-            .is_ok())
+            .map_err(|_| LicenseError::BadSignature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPIRES_AT: i64 = 1_767_225_600;
+
+    #[test]
+    fn license_is_valid_through_its_expiry_second() {
+        assert!(!LicenseValidator::is_expired(EXPIRES_AT, EXPIRES_AT - 1));
+        assert!(!LicenseValidator::is_expired(EXPIRES_AT, EXPIRES_AT));
+    }
+
+    #[test]
+    fn license_is_expired_one_second_after_expiry() {
+        assert!(LicenseValidator::is_expired(EXPIRES_AT, EXPIRES_AT + 1));
+    }
+
+    #[test]
+    fn far_future_license_is_valid() {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        assert!(!LicenseValidator::is_expired(i64::MAX, now));
+        assert!(LicenseValidator::is_expired(EXPIRES_AT, i64::MAX));
     }
 }
\ No newline at end of file
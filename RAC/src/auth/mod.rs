@@ -1,3 +1,4 @@
+pub(crate) mod license_error;
 pub(crate) mod license_validator;
 pub(crate) mod license_checker;
 pub(crate) mod license_keys;
\ No newline at end of file
@@ -1,8 +1,21 @@
 pub mod defaults {
     pub const TOGGLE_KEY: i32 = 0;
+    // Pause/Break, which games almost never bind.
+    pub const PANIC_KEY: i32 = 0x13;
+    // The +/= and - keys on the main row nudge Max CPS during a session; 0 disables either.
+    pub const CPS_UP_KEY: i32 = 0xBB;
+    pub const CPS_DOWN_KEY: i32 = 0xBD;
+    // Key spam repeats KEY_SPAM_KEY while armed by its own toggle key; 0 leaves either unset.
+    pub const KEY_SPAM_ENABLED: bool = false;
+    pub const KEY_SPAM_KEY: i32 = 0;
+    pub const KEY_SPAM_TOGGLE_KEY: i32 = 0;
+    pub const KEY_SPAM_CPS: u8 = 10;
+    pub const KEY_SPAM_HOLD_MODE: bool = false;
     pub const TARGET_PROCESS: &str = "craftrise-x64.exe";
     pub const ADAPTIVE_CPU_MODE: bool = false;
     pub const CLICK_DELAY_MICROS: u64 = 75;
+    // "Cps" derives each button's interval from Max CPS; "Interval" uses its Click Delay as-is.
+    pub const TIMING_MODE: &str = "Cps";
     pub const DELAY_RANGE_MIN: f64 = 69.5;
     pub const DELAY_RANGE_MAX: f64 = 70.5;
     pub const RANDOM_DEVIATION_MIN: i32 = -50;
@@ -10,4 +23,120 @@ pub mod defaults {
     pub const KEYBOARD_HOLD_MODE: bool = false;
     pub const LEFT_MAX_CPS: u8 = 15;
     pub const RIGHT_MAX_CPS: u8 = 18;
+    pub const MIN_CLICK_DELAY_MICROS: u64 = 1000;
+    // Per-button floor; 0 falls back to the global minimum click delay.
+    pub const BUTTON_MIN_CLICK_DELAY_MICROS: u64 = 0;
+    pub const DYNAMIC_CONSOLE_TITLE: bool = true;
+    pub const MIXED_INPUT_RATIO: f64 = 0.0;
+    pub const CPS_DRIFT_ENABLED: bool = false;
+    pub const CPS_DRIFT_BAND: f64 = 1.0;
+    pub const CPS_DRIFT_PERIOD_SECS: u64 = 5;
+    pub const TOGGLE_DETECTOR: &str = "Polling";
+    // Polling toggle detector: a toggle press is seen up to one interval late, and each poll costs a
+    // wake-up. Armed sessions poll fast so disarming is snappy; idle ones keep the old fixed 10ms, since
+    // the arming press lands while idle.
+    pub const TOGGLE_POLL_ACTIVE_MS: u64 = 5;
+    pub const TOGGLE_POLL_IDLE_MS: u64 = 10;
+    pub const LOG_EVERY_CLICK: bool = false;
+    pub const HOLD_MODE: bool = false;
+    // Off keeps Both mode coupled: holding either button's toggle key clicks both.
+    pub const INDEPENDENT_BUTTON_HOLD: bool = false;
+    pub const DOUBLE_CLICK: bool = false;
+    // Each double-click is two presses, so it gets a lower cap than single clicks.
+    pub const DOUBLE_CLICK_MAX_CPS: u8 = 8;
+    pub const TARGET_HWND: u64 = 0;
+    // Process finds the target through the process name; Foreground clicks whatever window has focus.
+    pub const TARGET_MODE: &str = "Process";
+    pub const PROCESS_MATCH_MODE: &str = "Exact";
+    pub const WINDOW_SELECTION_POLICY: &str = "LargestTitled";
+    pub const WINDOW_TITLE_FILTER: &str = "";
+    // Empty clicks the matched top-level window itself.
+    pub const CHILD_WINDOW_CLASS: &str = "";
+    pub const REQUIRE_WINDOW_VISIBILITY: bool = true;
+    pub const RECREATE_DELETED_SETTINGS: bool = true;
+    pub const DISPATCH_LATENCY_DIAGNOSTICS: bool = false;
+    pub const DOUBLE_PRESS_RESET_WINDOW_MS: u64 = 400;
+    // Random wait between the toggle arming and the first click; 0..0 starts clicking immediately.
+    pub const ACTIVATION_DELAY_MIN_MS: u64 = 0;
+    pub const ACTIVATION_DELAY_MAX_MS: u64 = 0;
+    pub const CLICK_STRATEGY: &str = "PostMessage";
+    pub const FALLBACK_CLICK_STRATEGY: &str = "";
+    pub const FALLBACK_FAILURE_THRESHOLD: u32 = 5;
+    pub const PAUSE_WHEN_CONSOLE_FOCUSED: bool = false;
+    // Holds clicks while the target process' window is in the background, e.g. after alt-tab.
+    pub const ONLY_WHEN_FOREGROUND: bool = false;
+    pub const LOG_SIZE_WARNING_MB: u64 = 50;
+    // Info, Warn or Error; RAC_LOG_LEVEL overrides it
+    pub const LOG_LEVEL: &str = "Info";
+    // logs.txt rotates to logs.1.txt .. logs.N.txt once it passes the size limit; 0 disables rotation
+    pub const LOG_MAX_SIZE_MB: u64 = 5;
+    pub const LOG_BACKUP_COUNT: u32 = 3;
+    pub const BACKPRESSURE_AGGRESSIVENESS: f64 = 0.25;
+    pub const ACTIVE_PROFILE: &str = "";
+    pub const WARMUP_MS: u64 = 0;
+    pub const SPIN_THRESHOLD_MICROS: u64 = 1000;
+    // -1 picks the last logical core
+    pub const CLICK_THREAD_CORE: i32 = -1;
+    pub const ANTIAFK_ENABLED: bool = false;
+    pub const ANTIAFK_INTERVAL_SECS: u64 = 60;
+    // 0 means no limit
+    pub const MAX_SESSION_SECS: u64 = 0;
+    pub const MAX_SESSION_CLICKS: u64 = 0;
+    // Empty means always allowed
+    pub const ACTIVE_FROM: &str = "";
+    pub const ACTIVE_UNTIL: &str = "";
+    // Empty uses the synthetic delay generator
+    pub const CLICK_PROFILE: &str = "";
+    // ClientArea, Centered, Custom or Origin (always 0,0)
+    pub const CLICK_REGION_MODE: &str = "ClientArea";
+    pub const CLICK_REGION_X: i32 = 0;
+    pub const CLICK_REGION_Y: i32 = 0;
+    pub const CLICK_REGION_WIDTH: i32 = 200;
+    pub const CLICK_REGION_HEIGHT: i32 = 200;
+    pub const SIMULATE_MOVEMENT: bool = false;
+    // Burst mode: BURST_SIZE clicks per burst, gaps inside a burst and the pause after it as % of the base delay
+    pub const BURST_SIZE: u32 = 2;
+    pub const BURST_INTRA_DELAY_PERCENT: u32 = 75;
+    pub const BURST_PAUSE_PERCENT: u32 = 125;
+    pub const DELAY_DISTRIBUTION: &str = "Uniform";
+    // Queried together; network time is the median of the answers that agree with each other
+    pub const NTP_SERVERS: &[&str] = &[
+        "pool.ntp.org",
+        "time.google.com",
+        "time.windows.com",
+        "time.apple.com",
+    ];
+    pub const NTP_PORT: u16 = 123;
+    pub const NTP_TIMEOUT_MS: u64 = 2000;
+    // License and clock re-check cadence; each wait is jittered so checks aren't perfectly periodic.
+    pub const LICENSE_CHECK_INTERVAL_SECS: u64 = 150;
+    pub const LICENSE_CHECK_JITTER: f64 = 0.1;
+    // License Info highlights the remaining time once it drops below this
+    pub const LICENSE_WARNING_DAYS: u64 = 3;
+    pub const ANTI_CHEAT_WARNING: bool = true;
+    pub const ANTI_CHEAT_PROCESSES: &[&str] = &[
+        "EasyAntiCheat.exe",
+        "EasyAntiCheat_EOS.exe",
+        "BEService.exe",
+        "BEService_x64.exe",
+        "vgc.exe",
+        "FACEITService.exe",
+        "FACEIT.exe",
+        "PnkBstrA.exe",
+        "GameMon.des",
+        "xxd-0.xem",
+    ];
+}
+
+pub mod limits {
+    // Hard floor for any sleep between clicks; settings can raise it but never lower it.
+    pub const MIN_CLICK_DELAY_FLOOR_MICROS: u64 = 500;
+    // Accepted Max CPS range for every button, from the menu, the command line or code.
+    pub const MIN_CPS: u8 = 1;
+    pub const MAX_CPS: u8 = 50;
+    // Bounds for hand-edited or imported files; the menus enforce min <= max on their own.
+    pub const MAX_RANDOM_DEVIATION_MICROS: i32 = 100_000;
+    pub const MAX_DELAY_RANGE_MS: f64 = 1000.0;
+    // Larger than any client area; click points are clamped to the window anyway.
+    pub const MAX_CLICK_REGION_PX: i32 = 16_384;
 }
\ No newline at end of file
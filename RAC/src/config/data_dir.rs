@@ -0,0 +1,46 @@
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static BASE_DIR: OnceLock<(PathBuf, &'static str)> = OnceLock::new();
+
+// Tries dirs::data_local_dir(), then %LOCALAPPDATA%, then the system temp dir so RAC can still run
+// (settings and logs just won't survive a temp cleanup). Resolved once and reused for the whole run.
+#[cfg(not(test))]
+fn resolve_base_dir() -> (PathBuf, &'static str) {
+    if let Some(dir) = dirs::data_local_dir() {
+        return (dir, "local data directory");
+    }
+
+    if let Some(dir) = env::var_os("LOCALAPPDATA").filter(|value| !value.is_empty()) {
+        return (PathBuf::from(dir), "LOCALAPPDATA");
+    }
+
+    (env::temp_dir(), "temp directory fallback")
+}
+
+// Unit tests load, save and log through the same paths, so keep them away from the user's real files.
+#[cfg(test)]
+fn resolve_base_dir() -> (PathBuf, &'static str) {
+    (env::temp_dir().join(format!("rac-tests-{}", std::process::id())), "test directory")
+}
+
+pub(crate) fn base_dir() -> &'static Path {
+    &BASE_DIR.get_or_init(resolve_base_dir).0
+}
+
+// Doesn't log, because the logger resolves its own path through here; callers log describe() instead.
+pub(crate) fn data_dir() -> io::Result<PathBuf> {
+    let dir = base_dir().join("RAC");
+    if !dir.exists() {
+        std::fs::create_dir_all(&dir)?;
+    }
+
+    Ok(dir)
+}
+
+pub(crate) fn describe() -> String {
+    let (base, source) = BASE_DIR.get_or_init(resolve_base_dir);
+    format!("{} ({})", base.join("RAC").display(), source)
+}
@@ -1,2 +1,5 @@
 pub(crate) mod settings;
+#[cfg_attr(not(windows), path = "settings_watcher_linux.rs")]
+pub(crate) mod settings_watcher;
 pub(crate) mod constants;
+pub(crate) mod data_dir;
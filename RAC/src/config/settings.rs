@@ -1,29 +1,115 @@
-use crate::logger::logger::{log_error, log_info};
+use crate::logger::logger::{log_error, log_info, log_warn};
+use base64::{engine::general_purpose, Engine as _};
 use serde::{Deserialize, Serialize};
-use std::io;
-use std::path::PathBuf;
-use serde::de::Error;
-use crate::config::constants::defaults;
-use tokio::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use crate::config::constants::{defaults, limits};
+use crate::config::data_dir;
+use chrono::{Local, NaiveTime};
 
-#[derive(Default, Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct Settings {
+    pub version: u32,
     pub toggle_key: i32,
+    pub left_toggle_key: i32,
+    pub right_toggle_key: i32,
+    pub panic_key: i32,
+    pub cps_up_key: i32,
+    pub cps_down_key: i32,
+    pub key_spam_enabled: bool,
+    pub key_spam_key: i32,
+    pub key_spam_toggle_key: i32,
+    pub key_spam_cps: u8,
+    pub key_spam_hold_mode: bool,
     pub target_process: String,
     pub adaptive_cpu_mode: bool,
 
     pub left_click_delay_micros: u64,
     pub right_click_delay_micros: u64,
+    pub timing_mode: String,
     pub left_random_deviation_min: i32,
     pub left_random_deviation_max: i32,
     pub right_random_deviation_min: i32,
     pub right_random_deviation_max: i32,
+    pub left_delay_distribution: String,
+    pub right_delay_distribution: String,
     pub keyboard_hold_mode: bool,
     pub left_max_cps: u8,
     pub right_max_cps: u8,
     pub left_game_mode: String,
     pub right_game_mode: String,
     pub click_mode: String,
+    pub min_click_delay_micros: u64,
+    pub left_min_click_delay_micros: u64,
+    pub right_min_click_delay_micros: u64,
+    pub dynamic_console_title: bool,
+    pub mixed_input_ratio: f64,
+    pub cps_drift_enabled: bool,
+    pub cps_drift_band: f64,
+    pub cps_drift_period_secs: u64,
+    pub warmup_ms: u64,
+    pub spin_threshold_micros: u64,
+    pub click_thread_core: i32,
+    pub antiafk_enabled: bool,
+    pub antiafk_interval_secs: u64,
+    pub max_session_secs: u64,
+    pub max_session_clicks: u64,
+    pub active_from: String,
+    pub active_until: String,
+    pub left_click_profile: String,
+    pub right_click_profile: String,
+    pub click_region_mode: String,
+    pub click_region_x: i32,
+    pub click_region_y: i32,
+    pub click_region_width: i32,
+    pub click_region_height: i32,
+    pub simulate_movement: bool,
+    pub burst_size: u32,
+    pub burst_intra_delay_percent: u32,
+    pub burst_pause_percent: u32,
+    pub toggle_detector: String,
+    pub toggle_poll_active_ms: u64,
+    pub toggle_poll_idle_ms: u64,
+    pub log_every_click: bool,
+    pub hold_mode: bool,
+    pub independent_button_hold: bool,
+    pub double_click: bool,
+    pub double_click_max_cps: u8,
+    pub target_hwnd: u64,
+    pub target_mode: String,
+    pub process_match_mode: String,
+    pub window_selection_policy: String,
+    pub window_title_filter: String,
+    pub child_window_class: String,
+    pub require_window_visibility: bool,
+    pub recreate_deleted_settings: bool,
+    pub dispatch_latency_diagnostics: bool,
+    pub double_press_reset_window_ms: u64,
+    pub activation_delay_min_ms: u64,
+    pub activation_delay_max_ms: u64,
+    pub click_strategy: String,
+    pub fallback_click_strategy: String,
+    pub fallback_failure_threshold: u32,
+    pub pause_when_console_focused: bool,
+    pub only_when_foreground: bool,
+    pub log_size_warning_mb: u64,
+    pub log_level: String,
+    pub log_max_size_mb: u64,
+    pub log_backup_count: u32,
+    pub backpressure_aggressiveness: f64,
+    pub active_profile: String,
+    pub anti_cheat_warning: bool,
+    pub anti_cheat_processes: Vec<String>,
+    pub ntp_servers: Vec<String>,
+    pub ntp_port: u16,
+    pub ntp_timeout_ms: u64,
+    pub license_check_interval_secs: u64,
+    pub license_check_jitter: f64,
+    pub license_warning_days: u64,
 
     #[serde(skip_serializing, default)]
     pub click_delay_micros: u64,
@@ -49,29 +135,181 @@ pub struct Settings {
     pub left_burst_mode: bool,
     #[serde(skip_serializing, default)]
     pub right_burst_mode: bool,
-    #[serde(skip_serializing, default)]
-    pub game_mode: String,
-    pub max_cps: u8,
+}
+
+// Bump together with a new step in Settings::migrate.
+const SETTINGS_VERSION: u32 = 1;
+
+// Keys that only appear in version 0 files; migrate folds them into their per-button replacements and drops them.
+const LEGACY_FIELDS: &[&str] = &[
+    "click_delay_micros", "delay_range_min", "delay_range_max", "left_delay_range_min", "left_delay_range_max",
+    "right_delay_range_min", "right_delay_range_max", "random_deviation_min", "random_deviation_max",
+    "burst_mode", "left_burst_mode", "right_burst_mode", "game_mode", "max_cps",
+];
+
+const SHARE_CODE_VERSION: u8 = 1;
+const SETTINGS_EXPORT_VERSION: u32 = 1;
+
+// Machine-local or legacy keys that mean nothing on another PC, so they're neither exported nor imported.
+const EXPORT_EXCLUDED_FIELDS: &[&str] = &["active_profile", "target_hwnd", "left_click_profile", "right_click_profile"];
+
+#[derive(Serialize, Deserialize)]
+struct SettingsExport {
+    version: u32,
+    checksum: String,
+    settings: serde_json::Value,
+}
+
+fn export_checksum(settings: &serde_json::Value) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(settings.to_string().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+#[derive(Serialize, Deserialize)]
+struct ShareProfile {
+    left_max_cps: u8,
+    right_max_cps: u8,
+    left_click_delay_micros: u64,
+    right_click_delay_micros: u64,
+    left_random_deviation_min: i32,
+    left_random_deviation_max: i32,
+    right_random_deviation_min: i32,
+    right_random_deviation_max: i32,
+    left_game_mode: String,
+    right_game_mode: String,
+    click_mode: String,
+    min_click_delay_micros: u64,
+}
+
+impl ShareProfile {
+    fn validate(&self) -> Result<(), String> {
+        for cps in [self.left_max_cps, self.right_max_cps] {
+            if !(limits::MIN_CPS..=limits::MAX_CPS).contains(&cps) {
+                return Err(format!("max CPS {} is outside {}-{}", cps, limits::MIN_CPS, limits::MAX_CPS));
+            }
+        }
+        if self.left_random_deviation_min > self.left_random_deviation_max
+            || self.right_random_deviation_min > self.right_random_deviation_max {
+            return Err("random deviation minimum is greater than maximum".to_string());
+        }
+        for mode in [&self.left_game_mode, &self.right_game_mode] {
+            if !matches!(mode.as_str(), "Combo" | "Default" | "Butterfly" | "Jitter") {
+                return Err(format!("unknown game mode '{}'", mode));
+            }
+        }
+        if !matches!(self.click_mode.as_str(), "LeftClick" | "RightClick" | "Both" | "MiddleClick" | "X1Click" | "X2Click") {
+            return Err(format!("unknown click mode '{}'", self.click_mode));
+        }
+        Ok(())
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::default_with_toggle_key(defaults::TOGGLE_KEY)
+    }
 }
 
 impl Settings {
     pub fn default_with_toggle_key(toggle_key: i32) -> Self {
         Self {
+            version: SETTINGS_VERSION,
             toggle_key,
+            left_toggle_key: defaults::TOGGLE_KEY,
+            right_toggle_key: defaults::TOGGLE_KEY,
+            panic_key: defaults::PANIC_KEY,
+            cps_up_key: defaults::CPS_UP_KEY,
+            cps_down_key: defaults::CPS_DOWN_KEY,
+            key_spam_enabled: defaults::KEY_SPAM_ENABLED,
+            key_spam_key: defaults::KEY_SPAM_KEY,
+            key_spam_toggle_key: defaults::KEY_SPAM_TOGGLE_KEY,
+            key_spam_cps: defaults::KEY_SPAM_CPS,
+            key_spam_hold_mode: defaults::KEY_SPAM_HOLD_MODE,
             target_process: defaults::TARGET_PROCESS.to_string(),
             adaptive_cpu_mode: defaults::ADAPTIVE_CPU_MODE,
             left_click_delay_micros: defaults::CLICK_DELAY_MICROS,
             right_click_delay_micros: defaults::CLICK_DELAY_MICROS,
+            timing_mode: defaults::TIMING_MODE.to_string(),
             left_random_deviation_min: defaults::RANDOM_DEVIATION_MIN,
             left_random_deviation_max: defaults::RANDOM_DEVIATION_MAX,
             right_random_deviation_min: defaults::RANDOM_DEVIATION_MIN,
             right_random_deviation_max: defaults::RANDOM_DEVIATION_MAX,
+            left_delay_distribution: defaults::DELAY_DISTRIBUTION.to_string(),
+            right_delay_distribution: defaults::DELAY_DISTRIBUTION.to_string(),
             keyboard_hold_mode: defaults::KEYBOARD_HOLD_MODE,
             left_max_cps: defaults::LEFT_MAX_CPS,
             right_max_cps: defaults::RIGHT_MAX_CPS,
             left_game_mode: "Combo".to_string(),
             right_game_mode: "Combo".to_string(),
             click_mode: "LeftClick".to_string(),
+            min_click_delay_micros: defaults::MIN_CLICK_DELAY_MICROS,
+            left_min_click_delay_micros: defaults::BUTTON_MIN_CLICK_DELAY_MICROS,
+            right_min_click_delay_micros: defaults::BUTTON_MIN_CLICK_DELAY_MICROS,
+            dynamic_console_title: defaults::DYNAMIC_CONSOLE_TITLE,
+            mixed_input_ratio: defaults::MIXED_INPUT_RATIO,
+            cps_drift_enabled: defaults::CPS_DRIFT_ENABLED,
+            cps_drift_band: defaults::CPS_DRIFT_BAND,
+            cps_drift_period_secs: defaults::CPS_DRIFT_PERIOD_SECS,
+            warmup_ms: defaults::WARMUP_MS,
+            spin_threshold_micros: defaults::SPIN_THRESHOLD_MICROS,
+            click_thread_core: defaults::CLICK_THREAD_CORE,
+            antiafk_enabled: defaults::ANTIAFK_ENABLED,
+            antiafk_interval_secs: defaults::ANTIAFK_INTERVAL_SECS,
+            max_session_secs: defaults::MAX_SESSION_SECS,
+            max_session_clicks: defaults::MAX_SESSION_CLICKS,
+            active_from: defaults::ACTIVE_FROM.to_string(),
+            active_until: defaults::ACTIVE_UNTIL.to_string(),
+            left_click_profile: defaults::CLICK_PROFILE.to_string(),
+            right_click_profile: defaults::CLICK_PROFILE.to_string(),
+            click_region_mode: defaults::CLICK_REGION_MODE.to_string(),
+            click_region_x: defaults::CLICK_REGION_X,
+            click_region_y: defaults::CLICK_REGION_Y,
+            click_region_width: defaults::CLICK_REGION_WIDTH,
+            click_region_height: defaults::CLICK_REGION_HEIGHT,
+            simulate_movement: defaults::SIMULATE_MOVEMENT,
+            burst_size: defaults::BURST_SIZE,
+            burst_intra_delay_percent: defaults::BURST_INTRA_DELAY_PERCENT,
+            burst_pause_percent: defaults::BURST_PAUSE_PERCENT,
+            toggle_detector: defaults::TOGGLE_DETECTOR.to_string(),
+            toggle_poll_active_ms: defaults::TOGGLE_POLL_ACTIVE_MS,
+            toggle_poll_idle_ms: defaults::TOGGLE_POLL_IDLE_MS,
+            log_every_click: defaults::LOG_EVERY_CLICK,
+            hold_mode: defaults::HOLD_MODE,
+            independent_button_hold: defaults::INDEPENDENT_BUTTON_HOLD,
+            double_click: defaults::DOUBLE_CLICK,
+            double_click_max_cps: defaults::DOUBLE_CLICK_MAX_CPS,
+            target_hwnd: defaults::TARGET_HWND,
+            target_mode: defaults::TARGET_MODE.to_string(),
+            process_match_mode: defaults::PROCESS_MATCH_MODE.to_string(),
+            window_selection_policy: defaults::WINDOW_SELECTION_POLICY.to_string(),
+            window_title_filter: defaults::WINDOW_TITLE_FILTER.to_string(),
+            child_window_class: defaults::CHILD_WINDOW_CLASS.to_string(),
+            require_window_visibility: defaults::REQUIRE_WINDOW_VISIBILITY,
+            recreate_deleted_settings: defaults::RECREATE_DELETED_SETTINGS,
+            dispatch_latency_diagnostics: defaults::DISPATCH_LATENCY_DIAGNOSTICS,
+            double_press_reset_window_ms: defaults::DOUBLE_PRESS_RESET_WINDOW_MS,
+            activation_delay_min_ms: defaults::ACTIVATION_DELAY_MIN_MS,
+            activation_delay_max_ms: defaults::ACTIVATION_DELAY_MAX_MS,
+            click_strategy: defaults::CLICK_STRATEGY.to_string(),
+            fallback_click_strategy: defaults::FALLBACK_CLICK_STRATEGY.to_string(),
+            fallback_failure_threshold: defaults::FALLBACK_FAILURE_THRESHOLD,
+            pause_when_console_focused: defaults::PAUSE_WHEN_CONSOLE_FOCUSED,
+            only_when_foreground: defaults::ONLY_WHEN_FOREGROUND,
+            log_size_warning_mb: defaults::LOG_SIZE_WARNING_MB,
+            log_level: defaults::LOG_LEVEL.to_string(),
+            log_max_size_mb: defaults::LOG_MAX_SIZE_MB,
+            log_backup_count: defaults::LOG_BACKUP_COUNT,
+            backpressure_aggressiveness: defaults::BACKPRESSURE_AGGRESSIVENESS,
+            active_profile: defaults::ACTIVE_PROFILE.to_string(),
+            anti_cheat_warning: defaults::ANTI_CHEAT_WARNING,
+            anti_cheat_processes: defaults::ANTI_CHEAT_PROCESSES.iter().map(|name| name.to_string()).collect(),
+            ntp_servers: defaults::NTP_SERVERS.iter().map(|server| server.to_string()).collect(),
+            ntp_port: defaults::NTP_PORT,
+            ntp_timeout_ms: defaults::NTP_TIMEOUT_MS,
+            license_check_interval_secs: defaults::LICENSE_CHECK_INTERVAL_SECS,
+            license_check_jitter: defaults::LICENSE_CHECK_JITTER,
+            license_warning_days: defaults::LICENSE_WARNING_DAYS,
             click_delay_micros: defaults::CLICK_DELAY_MICROS,
             delay_range_min: defaults::DELAY_RANGE_MIN,
             delay_range_max: defaults::DELAY_RANGE_MAX,
@@ -84,114 +322,804 @@ impl Settings {
             burst_mode: true,
             left_burst_mode: true,
             right_burst_mode: true,
-            game_mode: "Combo".to_string(),
-            max_cps: 15,
         }
     }
 
-    pub fn default() -> Self {
-        Self::default_with_toggle_key(defaults::TOGGLE_KEY)
+    pub fn effective_mixed_input_ratio(&self) -> f64 {
+        if self.mixed_input_ratio.is_nan() {
+            return 0.0;
+        }
+        self.mixed_input_ratio.clamp(0.0, 1.0)
     }
 
-    fn get_settings_path() -> io::Result<PathBuf> {
-        let local_app_data = dirs::data_local_dir()
-            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Could not find AppData/Local directory"))?;
+    pub fn requires_cursor_access(&self) -> bool {
+        self.effective_mixed_input_ratio() > 0.0
+            || self.click_strategy == "SendInput"
+            || self.fallback_click_strategy == "SendInput"
+    }
+
+    pub fn parse_target_processes(value: &str) -> Vec<String> {
+        value.split(',')
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .map(|name| name.to_string())
+            .collect()
+    }
 
-        let settings_dir = local_app_data.join("RAC");
-        if !settings_dir.exists() {
-            std::fs::create_dir_all(&settings_dir)?;
+    pub fn random_activation_delay(&self) -> Duration {
+        let min = self.activation_delay_min_ms.min(self.activation_delay_max_ms);
+        let max = self.activation_delay_min_ms.max(self.activation_delay_max_ms);
+        if max == 0 {
+            return Duration::ZERO;
         }
+        Duration::from_millis(rand::rng().random_range(min..=max))
+    }
 
-        Ok(settings_dir.join("settings.json"))
+    // Mouse hold in Both mode clicks both buttons while either toggle key is held, unless each button is set to gate itself.
+    pub fn couples_button_hold(&self) -> bool {
+        self.click_mode == "Both" && !self.independent_button_hold
     }
 
-    pub fn save(&self) -> io::Result<()> {
-        let context = "Settings::save";
-        match Self::get_settings_path() {
-            Ok(settings_path) => {
-                match serde_json::to_string(self) {
-                    Ok(json) => {
-                        if let Err(e) = std::fs::write(&settings_path, json) {
-                            log_error(&format!("Failed to write settings file: {}", e), context);
-                            return Err(e);
-                        }
-                        log_info("Settings saved successfully", context);
-                        Ok(())
+    // A per-button key of 0 means unset, so that button follows the shared toggle_key.
+    pub fn effective_left_toggle_key(&self) -> i32 {
+        if self.left_toggle_key != 0 { self.left_toggle_key } else { self.toggle_key }
+    }
+
+    pub fn effective_right_toggle_key(&self) -> i32 {
+        if self.right_toggle_key != 0 { self.right_toggle_key } else { self.toggle_key }
+    }
+
+    pub fn effective_min_click_delay_micros(&self) -> u64 {
+        self.min_click_delay_micros.max(limits::MIN_CLICK_DELAY_FLOOR_MICROS)
+    }
+
+    // The floor wins over Max CPS: a button can never exceed 1_000_000 / floor clicks per second.
+    pub fn effective_left_min_click_delay_micros(&self) -> u64 {
+        self.left_min_click_delay_micros.max(self.effective_min_click_delay_micros())
+    }
+
+    pub fn effective_right_min_click_delay_micros(&self) -> u64 {
+        self.right_min_click_delay_micros.max(self.effective_min_click_delay_micros())
+    }
+
+    pub fn parse_schedule_time(value: &str) -> Option<NaiveTime> {
+        NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+    }
+
+    // None when either end is empty or unparseable, which leaves clicking always allowed.
+    pub fn schedule_window(&self) -> Option<(NaiveTime, NaiveTime)> {
+        let from = Self::parse_schedule_time(&self.active_from)?;
+        let until = Self::parse_schedule_time(&self.active_until)?;
+        Some((from, until))
+    }
+
+    pub fn is_within_schedule(&self) -> bool {
+        let Some((from, until)) = self.schedule_window() else {
+            return true;
+        };
+
+        let now = Local::now().time();
+        if from <= until {
+            now >= from && now < until
+        } else {
+            // Window wraps past midnight, e.g. 22:00-02:00
+            now >= from || now < until
+        }
+    }
+
+    pub fn to_share_code(&self) -> String {
+        let profile = ShareProfile {
+            left_max_cps: self.left_max_cps,
+            right_max_cps: self.right_max_cps,
+            left_click_delay_micros: self.left_click_delay_micros,
+            right_click_delay_micros: self.right_click_delay_micros,
+            left_random_deviation_min: self.left_random_deviation_min,
+            left_random_deviation_max: self.left_random_deviation_max,
+            right_random_deviation_min: self.right_random_deviation_min,
+            right_random_deviation_max: self.right_random_deviation_max,
+            left_game_mode: self.left_game_mode.clone(),
+            right_game_mode: self.right_game_mode.clone(),
+            click_mode: self.click_mode.clone(),
+            min_click_delay_micros: self.min_click_delay_micros,
+        };
+
+        let mut payload = vec![SHARE_CODE_VERSION];
+        payload.extend(serde_json::to_vec(&profile).unwrap_or_default());
+        general_purpose::URL_SAFE_NO_PAD.encode(payload)
+    }
+
+    pub fn apply_share_code(&mut self, code: &str) -> io::Result<()> {
+        let context = "Settings::apply_share_code";
+        let invalid = |message: String| {
+            log_error(&format!("Rejected share code: {}", message), context);
+            io::Error::new(io::ErrorKind::InvalidData, message)
+        };
+
+        let payload = general_purpose::URL_SAFE_NO_PAD
+            .decode(code.trim())
+            .map_err(|e| invalid(format!("not a valid share code ({})", e)))?;
+
+        let (version, body) = payload.split_first().ok_or_else(|| invalid("share code is empty".to_string()))?;
+        if *version != SHARE_CODE_VERSION {
+            return Err(invalid(format!("unsupported share code version {}", version)));
+        }
+
+        let profile: ShareProfile = serde_json::from_slice(body)
+            .map_err(|e| invalid(format!("malformed share code ({})", e)))?;
+        profile.validate().map_err(invalid)?;
+
+        self.left_max_cps = profile.left_max_cps;
+        self.right_max_cps = profile.right_max_cps;
+        self.left_click_delay_micros = profile.left_click_delay_micros;
+        self.right_click_delay_micros = profile.right_click_delay_micros;
+        self.left_random_deviation_min = profile.left_random_deviation_min;
+        self.left_random_deviation_max = profile.left_random_deviation_max;
+        self.right_random_deviation_min = profile.right_random_deviation_min;
+        self.right_random_deviation_max = profile.right_random_deviation_max;
+        self.left_game_mode = profile.left_game_mode;
+        self.right_game_mode = profile.right_game_mode;
+        self.click_mode = profile.click_mode;
+        self.min_click_delay_micros = profile.min_click_delay_micros;
+
+        log_info("Applied settings from share code", context);
+        Ok(())
+    }
+
+    pub fn export_to(&self, path: &Path) -> io::Result<()> {
+        let context = "Settings::export_to";
+
+        let mut settings = serde_json::to_value(self).map_err(io::Error::other)?;
+        if let Some(fields) = settings.as_object_mut() {
+            for key in EXPORT_EXCLUDED_FIELDS {
+                fields.remove(*key);
+            }
+        }
+
+        let export = SettingsExport {
+            version: SETTINGS_EXPORT_VERSION,
+            checksum: export_checksum(&settings),
+            settings,
+        };
+        let json = serde_json::to_string_pretty(&export).map_err(io::Error::other)?;
+
+        std::fs::write(path, json).map_err(|e| {
+            log_error(&format!("Failed to write settings export to {}: {}", path.display(), e), context);
+            e
+        })?;
+
+        log_info(&format!("Exported settings to {}", path.display()), context);
+        Ok(())
+    }
+
+    pub fn import_from(&mut self, path: &Path) -> io::Result<()> {
+        let context = "Settings::import_from";
+        let invalid = |message: String| {
+            log_error(&format!("Rejected settings import: {}", message), context);
+            io::Error::new(io::ErrorKind::InvalidData, message)
+        };
+
+        let json = std::fs::read_to_string(path).map_err(|e| {
+            log_error(&format!("Failed to read settings import from {}: {}", path.display(), e), context);
+            e
+        })?;
+
+        let export: SettingsExport = serde_json::from_str(&json)
+            .map_err(|e| invalid(format!("not a RAC settings export ({})", e)))?;
+        if export.version != SETTINGS_EXPORT_VERSION {
+            return Err(invalid(format!("unsupported settings export version {}", export.version)));
+        }
+        if export_checksum(&export.settings) != export.checksum {
+            return Err(invalid("checksum mismatch, the file was edited or corrupted".to_string()));
+        }
+
+        let mut imported = export.settings;
+        Self::migrate(&mut imported);
+        let imported = imported.as_object()
+            .ok_or_else(|| invalid("settings must be a JSON object".to_string()))?;
+
+        // Start from the current settings so fields missing from older exports and local-only fields are kept.
+        let mut merged = serde_json::to_value(&*self).map_err(io::Error::other)?;
+        if let Some(fields) = merged.as_object_mut() {
+            for (key, value) in imported {
+                if !EXPORT_EXCLUDED_FIELDS.contains(&key.as_str()) {
+                    fields.insert(key.clone(), value.clone());
+                }
+            }
+
+            for key in ["left_max_cps", "right_max_cps"] {
+                if let Some(cps) = fields.get(key).and_then(|v| v.as_i64()) {
+                    let clamped = cps.clamp(limits::MIN_CPS as i64, limits::MAX_CPS as i64);
+                    if clamped != cps {
+                        log_warn(&format!("Clamped imported {} from {} to {}", key, cps, clamped), context);
+                        fields.insert(key.to_string(), serde_json::Value::from(clamped));
                     }
-                    Err(e) => {
-                        log_error(&format!("Failed to serialize settings: {}", e), context);
-                        Err(io::Error::new(io::ErrorKind::Other, e))
+                }
+            }
+        }
+
+        *self = serde_json::from_value(merged).map_err(|e| invalid(format!("malformed settings ({})", e)))?;
+        self.sanitize_ranges();
+
+        log_info(&format!("Imported settings from {}", path.display()), context);
+        Ok(())
+    }
+
+    pub fn get_data_dir() -> io::Result<PathBuf> {
+        data_dir::data_dir()
+    }
+
+    fn get_settings_path() -> io::Result<PathBuf> {
+        Ok(Self::get_data_dir()?.join("settings.json"))
+    }
+
+    fn get_profiles_dir() -> io::Result<PathBuf> {
+        let profiles_dir = Self::get_data_dir()?.join("profiles");
+        if !profiles_dir.exists() {
+            std::fs::create_dir_all(&profiles_dir)?;
+        }
+
+        Ok(profiles_dir)
+    }
+
+    fn get_profile_file(name: &str, extension: &str) -> io::Result<PathBuf> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().any(|c| matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|')) || name.starts_with('.') {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid profile name '{}'", name)));
+        }
+
+        Ok(Self::get_profiles_dir()?.join(format!("{}.{}", name, extension)))
+    }
+
+    fn get_profile_path(name: &str) -> io::Result<PathBuf> {
+        Self::get_profile_file(name, "json")
+    }
+
+    pub fn get_click_profile_path(name: &str) -> io::Result<PathBuf> {
+        Self::get_profile_file(name, "clickprofile")
+    }
+
+    fn list_profile_files(extension: &str) -> io::Result<Vec<String>> {
+        let mut profiles: Vec<String> = std::fs::read_dir(Self::get_profiles_dir()?)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().map(|ext| ext == extension).unwrap_or(false))
+            .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().to_string()))
+            .collect();
+
+        profiles.sort_by_key(|name| name.to_lowercase());
+        Ok(profiles)
+    }
+
+    pub fn list_profiles() -> io::Result<Vec<String>> {
+        Self::list_profile_files("json")
+    }
+
+    pub fn list_click_profiles() -> io::Result<Vec<String>> {
+        Self::list_profile_files("clickprofile")
+    }
+
+    pub fn load_profile(name: &str) -> io::Result<Self> {
+        let context = "Settings::load_profile";
+        let profile_path = Self::get_profile_path(name)?;
+
+        let json = std::fs::read_to_string(&profile_path).map_err(|e| {
+            log_error(&format!("Failed to read profile '{}': {}", name, e), context);
+            e
+        })?;
+
+        let (mut settings, _) = Self::parse_and_migrate(&json).map_err(|e| {
+            log_error(&format!("Failed to parse profile '{}': {}", name, e), context);
+            io::Error::new(io::ErrorKind::InvalidData, e)
+        })?;
+
+        settings.active_profile = name.trim().to_string();
+        log_info(&format!("Loaded profile '{}'", settings.active_profile), context);
+        Ok(settings)
+    }
+
+    pub fn save_as(&self, name: &str) -> io::Result<()> {
+        let context = "Settings::save_as";
+        let profile_path = Self::get_profile_path(name)?;
+
+        let mut profile = self.clone();
+        profile.active_profile = name.trim().to_string();
+
+        let json = serde_json::to_string(&profile).map_err(|e| {
+            log_error(&format!("Failed to serialize profile '{}': {}", name, e), context);
+            io::Error::other(e)
+        })?;
+
+        Self::write_atomically(&profile_path, &json).map_err(|e| {
+            log_error(&format!("Failed to write profile '{}': {}", name, e), context);
+            e
+        })?;
+
+        log_info(&format!("Saved profile '{}'", profile.active_profile), context);
+        Ok(())
+    }
+
+    pub fn delete_profile(name: &str) -> io::Result<()> {
+        let context = "Settings::delete_profile";
+        let profile_path = Self::get_profile_path(name)?;
+
+        std::fs::remove_file(&profile_path).map_err(|e| {
+            log_error(&format!("Failed to delete profile '{}': {}", name, e), context);
+            e
+        })?;
+
+        log_info(&format!("Deleted profile '{}'", name.trim()), context);
+        Ok(())
+    }
+
+    // Writes next to the target and renames over it, so a crash mid-write never leaves a truncated file.
+    fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+        let temp_path = path.with_extension("json.tmp");
+        {
+            let mut file = std::fs::File::create(&temp_path)?;
+            file.write_all(contents.as_bytes())?;
+            file.sync_all()?;
+        }
+
+        std::fs::rename(&temp_path, path).inspect_err(|_| {
+            let _ = std::fs::remove_file(&temp_path);
+        })
+    }
+
+    // Overwrites settings.json with defaults; save() keeps the replaced file as settings.json.bak.
+    // Returns the defaults and whether the old settings.json was copied to settings.bak.json first.
+    pub fn reset_to_defaults(keep_active_profile: bool) -> io::Result<(Self, bool)> {
+        Self::reset_to_defaults_at(&Self::get_settings_path()?, keep_active_profile)
+    }
+
+    fn reset_to_defaults_at(settings_path: &Path, keep_active_profile: bool) -> io::Result<(Self, bool)> {
+        let context = "Settings::reset_to_defaults";
+        let mut settings = Self::default();
+        if keep_active_profile {
+            settings.active_profile = Self::load_from(settings_path).map(|current| current.active_profile).unwrap_or_default();
+        }
+
+        // Copied as-is: a file that no longer parses is the likeliest reason for a reset.
+        let backed_up = settings_path.exists() && match std::fs::copy(settings_path, settings_path.with_file_name("settings.bak.json")) {
+            Ok(_) => true,
+            Err(e) => {
+                log_warn(&format!("Failed to copy settings to settings.bak.json: {}", e), context);
+                false
+            }
+        };
+
+        // save() would sync the kept profile's file with these defaults, so only settings.json is written.
+        settings.save_to(settings_path)?;
+        log_info(&format!("Settings reset to defaults (active profile {})", if keep_active_profile { "kept" } else { "cleared" }),
+                 context);
+        Ok((settings, backed_up))
+    }
+
+    // Only a file that still parses is worth keeping as the backup.
+    fn backup_current_settings(settings_path: &Path) {
+        let Ok(json) = std::fs::read_to_string(settings_path) else {
+            return;
+        };
+        if serde_json::from_str::<Settings>(&json).is_err() {
+            return;
+        }
+
+        if let Err(e) = std::fs::write(settings_path.with_extension("json.bak"), json) {
+            log_warn(&format!("Failed to back up settings file: {}", e), "Settings::backup_current_settings");
+        }
+    }
+
+    fn load_backup(settings_path: &Path) -> Option<Self> {
+        let json = std::fs::read_to_string(settings_path.with_extension("json.bak")).ok()?;
+        Self::parse_and_migrate(&json).ok().map(|(settings, _)| settings)
+    }
+
+    // Upgrades a settings document in place to SETTINGS_VERSION; returns whether anything changed.
+    fn migrate(value: &mut serde_json::Value) -> bool {
+        let context = "Settings::migrate";
+        let Some(fields) = value.as_object_mut() else {
+            return false;
+        };
+
+        let from_version = fields.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+        if from_version >= SETTINGS_VERSION as u64 {
+            return false;
+        }
+
+        if from_version < 1 {
+            // Version 0 shared one value between both buttons; only fill in what the file doesn't set per button.
+            for (legacy, current) in [
+                ("max_cps", ["left_max_cps", "right_max_cps"]),
+                ("game_mode", ["left_game_mode", "right_game_mode"]),
+                ("click_delay_micros", ["left_click_delay_micros", "right_click_delay_micros"]),
+                ("random_deviation_min", ["left_random_deviation_min", "right_random_deviation_min"]),
+                ("random_deviation_max", ["left_random_deviation_max", "right_random_deviation_max"]),
+            ] {
+                if let Some(value) = fields.get(legacy).cloned() {
+                    for key in current {
+                        fields.entry(key).or_insert_with(|| value.clone());
                     }
                 }
             }
+
+            for key in LEGACY_FIELDS {
+                fields.remove(*key);
+            }
+        }
+
+        fields.insert("version".to_string(), serde_json::Value::from(SETTINGS_VERSION));
+        log_info(&format!("Migrated settings from version {} to {}", from_version, SETTINGS_VERSION), context);
+        true
+    }
+
+    fn parse_and_migrate(json: &str) -> serde_json::Result<(Self, bool)> {
+        let mut value: serde_json::Value = serde_json::from_str(json)?;
+        let migrated = Self::migrate(&mut value);
+        let mut settings: Self = serde_json::from_value(value)?;
+        let sanitized = settings.sanitize_ranges();
+        Ok((settings, migrated || sanitized))
+    }
+
+    // Files edited by hand or imported skip the menu's checks, so bound each range and swap inverted ones.
+    // Returns whether anything was adjusted.
+    fn sanitize_ranges(&mut self) -> bool {
+        let context = "Settings::sanitize_ranges";
+        let mut adjusted = false;
+
+        let limit = limits::MAX_RANDOM_DEVIATION_MICROS;
+        for (name, min, max) in [
+            ("left_random_deviation", &mut self.left_random_deviation_min, &mut self.left_random_deviation_max),
+            ("right_random_deviation", &mut self.right_random_deviation_min, &mut self.right_random_deviation_max),
+            ("random_deviation", &mut self.random_deviation_min, &mut self.random_deviation_max),
+        ] {
+            let original = (*min, *max);
+            *min = (*min).clamp(-limit, limit);
+            *max = (*max).clamp(-limit, limit);
+            if min > max {
+                std::mem::swap(min, max);
+            }
+            if (*min, *max) != original {
+                adjusted = true;
+                log_warn(&format!("Adjusted {} from {} to {} into {} to {}", name, original.0, original.1, min, max), context);
+            }
+        }
+
+        for (name, min, max) in [
+            ("left_delay_range", &mut self.left_delay_range_min, &mut self.left_delay_range_max),
+            ("right_delay_range", &mut self.right_delay_range_min, &mut self.right_delay_range_max),
+            ("delay_range", &mut self.delay_range_min, &mut self.delay_range_max),
+        ] {
+            let original = (*min, *max);
+            *min = if min.is_finite() { (*min).clamp(0.0, limits::MAX_DELAY_RANGE_MS) } else { defaults::DELAY_RANGE_MIN };
+            *max = if max.is_finite() { (*max).clamp(0.0, limits::MAX_DELAY_RANGE_MS) } else { defaults::DELAY_RANGE_MAX };
+            if min > max {
+                std::mem::swap(min, max);
+            }
+            if (*min, *max) != original {
+                adjusted = true;
+                log_warn(&format!("Adjusted {} from {} to {} into {} to {}", name, original.0, original.1, min, max), context);
+            }
+        }
+
+        let max_px = limits::MAX_CLICK_REGION_PX;
+        for (name, value, min) in [
+            ("click_region_x", &mut self.click_region_x, 0),
+            ("click_region_y", &mut self.click_region_y, 0),
+            ("click_region_width", &mut self.click_region_width, 1),
+            ("click_region_height", &mut self.click_region_height, 1),
+        ] {
+            let original = *value;
+            *value = original.clamp(min, max_px);
+            if *value != original {
+                adjusted = true;
+                log_warn(&format!("Adjusted {} from {} to {}", name, original, value), context);
+            }
+        }
+
+        adjusted
+    }
+
+    // Cheap fingerprint of settings.json, used to skip reloads when only other files changed.
+    pub fn file_signature() -> Option<(SystemTime, u64)> {
+        let metadata = std::fs::metadata(Self::get_settings_path().ok()?).ok()?;
+        Some((metadata.modified().ok()?, metadata.len()))
+    }
+
+    pub fn file_exists() -> bool {
+        Self::get_settings_path().map(|path| path.exists()).unwrap_or(false)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let context = "Settings::save";
+        let settings_path = Self::get_settings_path().inspect_err(|e| {
+            log_error(&format!("Failed to get settings path: {}", e), context);
+        })?;
+        self.save_to(&settings_path)?;
+
+        // Keep the active profile in sync so edits survive switching away and back.
+        if !self.active_profile.is_empty() {
+            if let Err(e) = self.save_as(&self.active_profile) {
+                log_error(&format!("Failed to update active profile: {}", e), context);
+            }
+        }
+        Ok(())
+    }
+
+    fn save_to(&self, settings_path: &Path) -> io::Result<()> {
+        let context = "Settings::save";
+        let json = serde_json::to_string(self).map_err(|e| {
+            log_error(&format!("Failed to serialize settings: {}", e), context);
+            io::Error::other(e)
+        })?;
+
+        Self::backup_current_settings(settings_path);
+        Self::write_atomically(settings_path, &json).inspect_err(|e| {
+            log_error(&format!("Failed to write settings file: {}", e), context);
+        })?;
+        log_info("Settings saved successfully", context);
+        Ok(())
+    }
+
+    // Rewrites settings.json once at startup if it needs migrating or had out-of-range values, so load() never writes.
+    // The old file stays behind as settings.json.bak.
+    pub fn upgrade_file() -> io::Result<()> {
+        Self::upgrade_file_at(&Self::get_settings_path()?)
+    }
+
+    fn upgrade_file_at(settings_path: &Path) -> io::Result<()> {
+        let context = "Settings::upgrade_file";
+        if !settings_path.exists() {
+            return Ok(());
+        }
+
+        let json = std::fs::read_to_string(settings_path)?;
+        // An unreadable file is left for load() to recover from.
+        let Ok((settings, true)) = Self::parse_and_migrate(&json) else {
+            return Ok(());
+        };
+
+        Self::backup_current_settings(settings_path);
+        let json = serde_json::to_string(&settings).map_err(io::Error::other)?;
+        Self::write_atomically(settings_path, &json).inspect_err(|e| {
+            log_error(&format!("Failed to write upgraded settings: {}", e), context);
+        })?;
+        log_info("Wrote upgraded settings file", context);
+        Ok(())
+    }
+
+    pub fn load() -> io::Result<Self> {
+        match Self::get_settings_path() {
+            Ok(settings_path) => Self::load_from(&settings_path),
             Err(e) => {
-                log_error(&format!("Failed to get settings path: {}", e), context);
+                log_error(&format!("Failed to get settings path: {}", e), "Settings::load");
                 Err(e)
             }
         }
     }
 
-    pub fn load() -> io::Result<Self> {
+    fn load_from(settings_path: &Path) -> io::Result<Self> {
         let context = "Settings::load";
-        match Self::get_settings_path() {
-            Ok(settings_path) => {
-                if !settings_path.exists() {
-                    let default_settings = Settings::default();
-                    log_info("Created default settings", context);
-                    return Ok(default_settings);
-                }
+        if !settings_path.exists() {
+            let default_settings = Settings::default();
+            log_info("Created default settings", context);
+            return Ok(default_settings);
+        }
 
-                match std::fs::read_to_string(&settings_path) {
-                    Ok(json) => {
-                        match serde_json::from_str(&json) {
-                            Ok(settings) => {
-                                log_info("Settings loaded successfully", context);
-                                Ok(settings)
-                            }
-                            Err(e) => {
-                                log_error(&format!("Failed to parse settings JSON: {}", e), context);
-                                log_info("Trying to recover partial settings", context);
+        match std::fs::read_to_string(settings_path) {
+            Ok(json) => {
+                match Self::parse_and_migrate(&json) {
+                    Ok((settings, _)) => {
+                        log_info("Settings loaded successfully", context);
+                        Ok(settings)
+                    }
+                    Err(e) => {
+                        log_error(&format!("Failed to parse settings JSON: {}", e), context);
 
-                                let mut default_settings = Settings::default();
+                        if let Some(backup) = Self::load_backup(settings_path) {
+                            log_warn("Recovered settings from backup file", context);
+                            return Ok(backup);
+                        }
 
-                                if let Ok(partial) = serde_json::from_str::<serde_json::Value>(&json) {
-                                    if let Some(toggle_key) = partial.get("toggle_key").and_then(|v| v.as_i64()) {
-                                        default_settings.toggle_key = toggle_key as i32;
-                                    }
+                        log_info("Trying to recover partial settings", context);
 
-                                    if let Some(left_max_cps) = partial.get("left_max_cps").and_then(|v| v.as_u64()) {
-                                        default_settings.left_max_cps = left_max_cps as u8;
-                                    }
+                        let mut default_settings = Settings::default();
 
-                                    if let Some(right_max_cps) = partial.get("right_max_cps").and_then(|v| v.as_u64()) {
-                                        default_settings.right_max_cps = right_max_cps as u8;
-                                    }
+                        if let Ok(partial) = serde_json::from_str::<serde_json::Value>(&json) {
+                            if let Some(toggle_key) = partial.get("toggle_key").and_then(|v| v.as_i64()) {
+                                default_settings.toggle_key = toggle_key as i32;
+                            }
 
-                                    if let Some(left_game_mode) = partial.get("left_game_mode").and_then(|v| v.as_str()) {
-                                        default_settings.left_game_mode = left_game_mode.to_string();
-                                    }
+                            if let Some(left_max_cps) = partial.get("left_max_cps").and_then(|v| v.as_u64()) {
+                                default_settings.left_max_cps = left_max_cps as u8;
+                            }
 
-                                    if let Some(right_game_mode) = partial.get("right_game_mode").and_then(|v| v.as_str()) {
-                                        default_settings.right_game_mode = right_game_mode.to_string();
-                                    }
-                                }
+                            if let Some(right_max_cps) = partial.get("right_max_cps").and_then(|v| v.as_u64()) {
+                                default_settings.right_max_cps = right_max_cps as u8;
+                            }
 
-                                log_info("Recovered partial settings, but not auto-saving", context);
+                            if let Some(left_game_mode) = partial.get("left_game_mode").and_then(|v| v.as_str()) {
+                                default_settings.left_game_mode = left_game_mode.to_string();
+                            }
 
-                                Ok(default_settings)
+                            if let Some(right_game_mode) = partial.get("right_game_mode").and_then(|v| v.as_str()) {
+                                default_settings.right_game_mode = right_game_mode.to_string();
                             }
                         }
-                    }
-                    Err(e) => {
-                        log_error(&format!("Failed to read settings file: {}", e), context);
-                        Err(e)
+
+                        log_info("Recovered partial settings, but not auto-saving", context);
+
+                        Ok(default_settings)
                     }
                 }
             }
             Err(e) => {
-                log_error(&format!("Failed to get settings path: {}", e), context);
+                log_error(&format!("Failed to read settings file: {}", e), context);
                 Err(e)
             }
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Each test gets its own settings file so parallel tests never share one.
+    fn settings_path(test: &str) -> PathBuf {
+        let dir = data_dir::base_dir().join(test);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("settings.json")
+    }
+
+    #[test]
+    fn a_truncated_settings_file_is_recovered_from_the_backup() {
+        let settings_path = settings_path("truncated_settings");
+        Settings { left_max_cps: 12, ..Settings::default() }.save_to(&settings_path).unwrap();
+        Settings { left_max_cps: 14, ..Settings::default() }.save_to(&settings_path).unwrap();
+
+        std::fs::write(&settings_path, r#"{"version": 1, "left_max_cps": 14, "right_max"#).unwrap();
+
+        assert_eq!(Settings::load_from(&settings_path).unwrap().left_max_cps, 12);
+    }
+
+    #[test]
+    fn a_corrupt_file_never_replaces_a_good_backup() {
+        let settings_path = settings_path("corrupt_backup");
+        Settings { left_max_cps: 12, ..Settings::default() }.save_to(&settings_path).unwrap();
+        Settings { left_max_cps: 13, ..Settings::default() }.save_to(&settings_path).unwrap();
+        std::fs::write(&settings_path, "not json").unwrap();
+        Settings { left_max_cps: 14, ..Settings::default() }.save_to(&settings_path).unwrap();
+
+        std::fs::write(&settings_path, "not json").unwrap();
+
+        assert_eq!(Settings::load_from(&settings_path).unwrap().left_max_cps, 12);
+    }
+
+    #[test]
+    fn loading_an_old_settings_file_leaves_it_untouched() {
+        let settings_path = settings_path("load_without_writing");
+        let legacy = r#"{"max_cps": 17, "toggle_key": 5}"#;
+        std::fs::write(&settings_path, legacy).unwrap();
+
+        let settings = Settings::load_from(&settings_path).unwrap();
+        assert_eq!((settings.version, settings.left_max_cps, settings.right_max_cps, settings.toggle_key), (SETTINGS_VERSION, 17, 17, 5));
+        assert_eq!(std::fs::read_to_string(&settings_path).unwrap(), legacy);
+        assert!(!settings_path.with_extension("json.bak").exists());
+    }
+
+    #[test]
+    fn upgrade_file_rewrites_an_old_settings_file_once() {
+        let settings_path = settings_path("upgrade_file");
+        let legacy = r#"{"max_cps": 17, "toggle_key": 5}"#;
+        std::fs::write(&settings_path, legacy).unwrap();
+
+        Settings::upgrade_file_at(&settings_path).unwrap();
+        let upgraded = std::fs::read_to_string(&settings_path).unwrap();
+        let (settings, changed) = Settings::parse_and_migrate(&upgraded).unwrap();
+        assert!(!changed);
+        assert_eq!((settings.left_max_cps, settings.right_max_cps), (17, 17));
+
+        // A current file is left as it is.
+        let modified = std::fs::metadata(&settings_path).unwrap().modified().unwrap();
+        Settings::upgrade_file_at(&settings_path).unwrap();
+        assert_eq!(std::fs::metadata(&settings_path).unwrap().modified().unwrap(), modified);
+        assert_eq!(std::fs::read_to_string(&settings_path).unwrap(), upgraded);
+    }
+
+    #[test]
+    fn inverted_ranges_are_swapped_on_load() {
+        let json = r#"{"version": 1, "left_random_deviation_min": 800, "left_random_deviation_max": -200,
+                       "right_delay_range_min": 90.0, "right_delay_range_max": 30.0}"#;
+        let (settings, changed) = Settings::parse_and_migrate(json).unwrap();
+
+        assert!(changed);
+        assert_eq!((settings.left_random_deviation_min, settings.left_random_deviation_max), (-200, 800));
+        assert_eq!((settings.right_delay_range_min, settings.right_delay_range_max), (30.0, 90.0));
+    }
+
+    #[test]
+    fn out_of_range_values_are_clamped_on_load() {
+        let limit = limits::MAX_RANDOM_DEVIATION_MICROS;
+        let mut settings = Settings {
+            right_random_deviation_min: -limit * 3,
+            right_random_deviation_max: limit + 1,
+            left_delay_range_min: -5.0,
+            left_delay_range_max: limits::MAX_DELAY_RANGE_MS * 2.0,
+            delay_range_min: f64::NAN,
+            ..Settings::default()
+        };
+
+        assert!(settings.sanitize_ranges());
+        assert_eq!((settings.right_random_deviation_min, settings.right_random_deviation_max), (-limit, limit));
+        assert_eq!((settings.left_delay_range_min, settings.left_delay_range_max), (0.0, limits::MAX_DELAY_RANGE_MS));
+        assert_eq!(settings.delay_range_min, defaults::DELAY_RANGE_MIN);
+    }
+
+    #[test]
+    fn click_region_values_are_clamped_on_load() {
+        let mut settings = Settings {
+            click_region_x: -10,
+            click_region_y: i32::MAX,
+            click_region_width: i32::MAX,
+            click_region_height: 0,
+            ..Settings::default()
+        };
+
+        assert!(settings.sanitize_ranges());
+        assert_eq!((settings.click_region_x, settings.click_region_y), (0, limits::MAX_CLICK_REGION_PX));
+        assert_eq!((settings.click_region_width, settings.click_region_height), (limits::MAX_CLICK_REGION_PX, 1));
+    }
+
+    #[test]
+    fn ranges_already_in_bounds_are_left_alone() {
+        let mut settings = Settings::default();
+        assert!(!settings.sanitize_ranges());
+        assert_eq!(settings.left_random_deviation_min, Settings::default().left_random_deviation_min);
+    }
+
+    #[test]
+    fn only_both_mode_couples_the_button_hold() {
+        for (click_mode, independent, coupled) in [("Both", false, true), ("Both", true, false), ("LeftClick", false, false), ("RightClick", false, false)] {
+            let settings = Settings { click_mode: click_mode.to_string(), independent_button_hold: independent, ..Settings::default() };
+            assert_eq!(settings.couples_button_hold(), coupled, "{} independent {}", click_mode, independent);
+        }
+    }
+
+    #[test]
+    fn a_reset_keeps_the_active_profile_file_and_backs_up_the_old_settings() {
+        let settings_path = settings_path("reset_to_defaults");
+        let kept = Settings { left_max_cps: 19, active_profile: "reset_kept".to_string(), ..Settings::default() };
+        kept.save_as("reset_kept").unwrap();
+        let profile_path = Settings::get_profile_path("reset_kept").unwrap();
+        let profile = std::fs::read_to_string(&profile_path).unwrap();
+        kept.save_to(&settings_path).unwrap();
+        let current = std::fs::read_to_string(&settings_path).unwrap();
+
+        let (settings, backed_up) = Settings::reset_to_defaults_at(&settings_path, true).unwrap();
+
+        assert!(backed_up);
+        assert_eq!(settings.active_profile, "reset_kept");
+        assert_eq!(settings.left_max_cps, Settings::default().left_max_cps);
+        assert_eq!(std::fs::read_to_string(&profile_path).unwrap(), profile);
+        assert_eq!(std::fs::read_to_string(settings_path.with_file_name("settings.bak.json")).unwrap(), current);
+    }
+
+    #[test]
+    fn a_reset_backs_up_a_file_that_no_longer_parses() {
+        let settings_path = settings_path("reset_corrupt");
+        std::fs::write(&settings_path, "not json").unwrap();
+
+        let (_, backed_up) = Settings::reset_to_defaults_at(&settings_path, false).unwrap();
+
+        assert!(backed_up);
+        assert_eq!(std::fs::read_to_string(settings_path.with_file_name("settings.bak.json")).unwrap(), "not json");
+        assert_eq!(Settings::load_from(&settings_path).unwrap().left_max_cps, Settings::default().left_max_cps);
+    }
+
+    #[test]
+    fn share_codes_outside_the_cps_limits_are_rejected() {
+        for (cps, accepted) in [(0, false), (1, true), (50, true), (51, false)] {
+            let code = Settings { left_max_cps: cps, ..Settings::default() }.to_share_code();
+            assert_eq!(Settings::default().apply_share_code(&code).is_ok(), accepted, "{} CPS", cps);
+        }
+    }
 }
\ No newline at end of file
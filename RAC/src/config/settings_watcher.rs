@@ -0,0 +1,48 @@
+use crate::logger::logger::log_error;
+use std::path::Path;
+use std::time::Duration;
+use windows::core::HSTRING;
+use windows::Win32::Foundation::{HANDLE, WAIT_OBJECT_0};
+use windows::Win32::Storage::FileSystem::{
+    FindCloseChangeNotification, FindFirstChangeNotificationW, FindNextChangeNotification,
+    FILE_NOTIFY_CHANGE_FILE_NAME, FILE_NOTIFY_CHANGE_LAST_WRITE,
+};
+use windows::Win32::System::Threading::WaitForSingleObject;
+
+// Wakes on writes and renames in the settings directory. The directory also holds the log file,
+// so callers should confirm settings.json itself changed before reloading.
+pub struct SettingsWatcher {
+    handle: HANDLE,
+}
+
+impl SettingsWatcher {
+    pub fn new(directory: &Path) -> Option<Self> {
+        let path = HSTRING::from(directory.as_os_str());
+        match unsafe { FindFirstChangeNotificationW(&path, false, FILE_NOTIFY_CHANGE_LAST_WRITE | FILE_NOTIFY_CHANGE_FILE_NAME) } {
+            Ok(handle) if !handle.is_invalid() => Some(Self { handle }),
+            Ok(_) => None,
+            Err(e) => {
+                log_error(&format!("Failed to watch settings directory: {}", e), "SettingsWatcher::new");
+                None
+            }
+        }
+    }
+
+    pub fn wait(&self, timeout: Duration) -> bool {
+        let signalled = unsafe { WaitForSingleObject(self.handle, timeout.as_millis().min(u32::MAX as u128) as u32) } == WAIT_OBJECT_0;
+        if signalled {
+            if let Err(e) = unsafe { FindNextChangeNotification(self.handle) } {
+                log_error(&format!("Failed to re-arm settings watcher: {}", e), "SettingsWatcher::wait");
+            }
+        }
+        signalled
+    }
+}
+
+impl Drop for SettingsWatcher {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = FindCloseChangeNotification(self.handle);
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use crate::logger::logger::log_error;
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::time::Duration;
+
+const WATCH_EVENTS: u32 = libc::IN_CLOSE_WRITE | libc::IN_MOVED_TO | libc::IN_CREATE | libc::IN_DELETE;
+
+// inotify counterpart of the Windows change-notification watcher. Like that one it fires for any
+// file in the directory, so callers still confirm settings.json itself changed.
+pub struct SettingsWatcher {
+    fd: i32,
+}
+
+impl SettingsWatcher {
+    pub fn new(directory: &Path) -> Option<Self> {
+        let context = "SettingsWatcher::new";
+        let Ok(path) = CString::new(directory.as_os_str().as_bytes()) else {
+            return None;
+        };
+
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd < 0 {
+            log_error(&format!("Failed to create inotify instance: {}", std::io::Error::last_os_error()), context);
+            return None;
+        }
+
+        if unsafe { libc::inotify_add_watch(fd, path.as_ptr(), WATCH_EVENTS) } < 0 {
+            log_error(&format!("Failed to watch settings directory: {}", std::io::Error::last_os_error()), context);
+            unsafe {
+                libc::close(fd);
+            }
+            return None;
+        }
+
+        Some(Self { fd })
+    }
+
+    pub fn wait(&self, timeout: Duration) -> bool {
+        let mut poll_fd = libc::pollfd { fd: self.fd, events: libc::POLLIN, revents: 0 };
+        let timeout_ms = timeout.as_millis().min(i32::MAX as u128) as i32;
+        if unsafe { libc::poll(&mut poll_fd, 1, timeout_ms) } <= 0 {
+            return false;
+        }
+
+        // Drain the queued events; only the fact that something changed matters.
+        let mut buffer = [0u8; 4096];
+        while unsafe { libc::read(self.fd, buffer.as_mut_ptr() as *mut libc::c_void, buffer.len()) } > 0 {}
+        true
+    }
+}
+
+impl Drop for SettingsWatcher {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use crate::input::click_executor::MouseButton;
+use crate::input::click_region::ClickPoint;
+
+#[cfg(windows)]
+mod win32;
+#[cfg(not(windows))]
+mod x11;
+
+#[cfg(windows)]
+pub use winapi::shared::windef::HWND;
+// X11 window IDs ride in the same pointer-sized slot so Handle and the click code stay platform-neutral.
+#[cfg(not(windows))]
+#[allow(clippy::upper_case_acronyms)]
+pub type HWND = *mut std::ffi::c_void;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThreadPriority {
+    Idle,
+    Normal,
+    TimeCritical,
+}
+
+// Everything the click path needs from the OS. ClickExecutor and ClickService only talk to this
+// trait; the Win32 and X11 details live in the backend modules.
+pub trait InputBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    // Delivers a button event to a specific window without touching the real cursor where the
+    // platform allows it.
+    fn post_button(&self, hwnd: HWND, button: MouseButton, pressed: bool, point: ClickPoint) -> bool;
+    fn post_move(&self, hwnd: HWND, point: ClickPoint) -> bool;
+
+    // Second press of a double-click. Windows has a dedicated message for it; elsewhere it's an ordinary
+    // press and the target recognises the double-click from timing.
+    fn post_double_click(&self, hwnd: HWND, button: MouseButton, point: ClickPoint) -> bool {
+        self.post_button(hwnd, button, true, point)
+    }
+
+    // Keyboard counterpart of post_button, for key spam.
+    fn post_key(&self, hwnd: HWND, virtual_key: i32, pressed: bool) -> bool;
+
+    // Injects a button event at the real cursor, landing on whatever window has focus.
+    fn send_button(&self, button: MouseButton, pressed: bool) -> bool;
+
+    fn is_key_pressed(&self, virtual_key: i32) -> bool;
+
+    fn set_thread_priority(&self, priority: ThreadPriority) -> bool;
+    fn pin_current_thread(&self, core: usize) -> bool;
+
+    // Only Windows has a coarse default timer tick; elsewhere sleeps are already fine-grained.
+    fn begin_timer_resolution(&self, _resolution_ms: u32) -> bool {
+        true
+    }
+
+    fn end_timer_resolution(&self, _resolution_ms: u32) {}
+
+    fn is_window(&self, hwnd: HWND) -> bool;
+    fn is_window_visible(&self, hwnd: HWND) -> bool;
+    fn foreground_window(&self) -> HWND;
+    // The top-level window that owns `hwnd`, which is what foreground_window reports for a child.
+    fn root_window(&self, hwnd: HWND) -> HWND {
+        hwnd
+    }
+    // Null when the terminal window can't be identified.
+    fn console_window(&self) -> HWND;
+    fn client_size(&self, hwnd: HWND) -> Option<(i32, i32)>;
+    fn window_title(&self, hwnd: HWND) -> String;
+    fn window_class(&self, hwnd: HWND) -> String;
+    fn window_pid(&self, hwnd: HWND) -> u32;
+    // Top-level windows, topmost first.
+    fn top_level_windows(&self) -> Vec<HWND>;
+    // Every descendant of `hwnd`, depth-first. Only Windows games and browsers render into child windows.
+    fn child_windows(&self, _hwnd: HWND) -> Vec<HWND> {
+        Vec::new()
+    }
+    fn cursor_position(&self) -> Option<ClickPoint>;
+}
+
+#[cfg(windows)]
+pub fn backend() -> &'static dyn InputBackend {
+    &win32::Win32Backend
+}
+
+#[cfg(not(windows))]
+pub fn backend() -> &'static dyn InputBackend {
+    x11::X11Backend::get()
+}
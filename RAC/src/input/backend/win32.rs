@@ -0,0 +1,239 @@
+use crate::input::backend::{InputBackend, ThreadPriority, HWND};
+use crate::input::click_executor::MouseButton;
+use crate::input::click_region::ClickPoint;
+use crate::logger::logger::log_error;
+use winapi::shared::minwindef::{DWORD, LPARAM};
+use winapi::shared::windef::{POINT, RECT};
+use winapi::um::winuser::{EnumChildWindows, EnumWindows, GetAsyncKeyState, GetClassNameW, GetClientRect, GetCursorPos, GetForegroundWindow, GetWindowTextW};
+use winapi::um::winuser::{GetAncestor, GetWindowThreadProcessId, GA_ROOT, IsWindow, IsWindowVisible, MapVirtualKeyA, PostMessageA, MAPVK_VK_TO_VSC, WM_KEYDOWN, WM_KEYUP};
+use winapi::um::winuser::{MK_LBUTTON, MK_MBUTTON, MK_RBUTTON, MK_XBUTTON1, MK_XBUTTON2, XBUTTON1, XBUTTON2};
+use winapi::um::winuser::{WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MBUTTONDOWN, WM_MBUTTONUP, WM_MOUSEMOVE, WM_RBUTTONDOWN, WM_RBUTTONUP, WM_XBUTTONDOWN, WM_XBUTTONUP};
+use winapi::um::winuser::{WM_LBUTTONDBLCLK, WM_MBUTTONDBLCLK, WM_RBUTTONDBLCLK, WM_XBUTTONDBLCLK};
+use windows::Win32::Foundation::GetLastError;
+use windows::Win32::Media::{timeBeginPeriod, timeEndPeriod, TIMERR_NOERROR};
+use windows::Win32::System::Console::GetConsoleWindow;
+use windows::Win32::System::Threading::{GetCurrentThread, SetThreadAffinityMask, SetThreadPriority};
+use windows::Win32::System::Threading::{THREAD_PRIORITY_BELOW_NORMAL, THREAD_PRIORITY_NORMAL, THREAD_PRIORITY_TIME_CRITICAL};
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    SendInput, INPUT, INPUT_0, INPUT_MOUSE, MOUSEINPUT, MOUSE_EVENT_FLAGS,
+    MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP, MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP,
+    MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP, MOUSEEVENTF_XDOWN, MOUSEEVENTF_XUP,
+};
+
+pub struct Win32Backend;
+
+impl Win32Backend {
+    fn post(hwnd: HWND, msg: u32, wparam: usize, point: ClickPoint) -> bool {
+        Self::post_message(hwnd, msg, wparam, point.lparam())
+    }
+
+    fn post_message(hwnd: HWND, msg: u32, wparam: usize, lparam: LPARAM) -> bool {
+        unsafe {
+            if PostMessageA(hwnd, msg, wparam, lparam) != 0 {
+                return true;
+            }
+
+            let error = GetLastError();
+            log_error(&format!("PostMessageA(0x{:04X}) to HWND={:?} failed with error {}", msg, hwnd, error.0),
+                      "Win32Backend::post");
+            false
+        }
+    }
+
+    fn send(flags: MOUSE_EVENT_FLAGS, mouse_data: u32) -> bool {
+        let input = INPUT {
+            r#type: INPUT_MOUSE,
+            Anonymous: INPUT_0 {
+                mi: MOUSEINPUT {
+                    dx: 0,
+                    dy: 0,
+                    mouseData: mouse_data,
+                    dwFlags: flags,
+                    time: 0,
+                    dwExtraInfo: 0,
+                },
+            },
+        };
+
+        unsafe { SendInput(&[input], size_of::<INPUT>() as i32) == 1 }
+    }
+}
+
+unsafe extern "system" fn collect_windows_callback(hwnd: HWND, lparam: LPARAM) -> i32 {
+    let windows = &mut *(lparam as *mut Vec<HWND>);
+    windows.push(hwnd);
+    1
+}
+
+impl InputBackend for Win32Backend {
+    fn name(&self) -> &'static str {
+        "Win32"
+    }
+
+    fn post_button(&self, hwnd: HWND, button: MouseButton, pressed: bool, point: ClickPoint) -> bool {
+        // X buttons carry which side button it is in the high word of wParam.
+        let (msg, wparam) = match (button, pressed) {
+            (MouseButton::Left, true) => (WM_LBUTTONDOWN, MK_LBUTTON),
+            (MouseButton::Right, true) => (WM_RBUTTONDOWN, MK_RBUTTON),
+            (MouseButton::Middle, true) => (WM_MBUTTONDOWN, MK_MBUTTON),
+            (MouseButton::X1, true) => (WM_XBUTTONDOWN, ((XBUTTON1 as usize) << 16) | MK_XBUTTON1),
+            (MouseButton::X2, true) => (WM_XBUTTONDOWN, ((XBUTTON2 as usize) << 16) | MK_XBUTTON2),
+            (MouseButton::Left, false) => (WM_LBUTTONUP, 0),
+            (MouseButton::Right, false) => (WM_RBUTTONUP, 0),
+            (MouseButton::Middle, false) => (WM_MBUTTONUP, 0),
+            (MouseButton::X1, false) => (WM_XBUTTONUP, (XBUTTON1 as usize) << 16),
+            (MouseButton::X2, false) => (WM_XBUTTONUP, (XBUTTON2 as usize) << 16),
+        };
+        Self::post(hwnd, msg, wparam, point)
+    }
+
+    fn post_move(&self, hwnd: HWND, point: ClickPoint) -> bool {
+        Self::post(hwnd, WM_MOUSEMOVE, 0, point)
+    }
+
+    fn post_double_click(&self, hwnd: HWND, button: MouseButton, point: ClickPoint) -> bool {
+        let (msg, wparam) = match button {
+            MouseButton::Left => (WM_LBUTTONDBLCLK, MK_LBUTTON),
+            MouseButton::Right => (WM_RBUTTONDBLCLK, MK_RBUTTON),
+            MouseButton::Middle => (WM_MBUTTONDBLCLK, MK_MBUTTON),
+            MouseButton::X1 => (WM_XBUTTONDBLCLK, ((XBUTTON1 as usize) << 16) | MK_XBUTTON1),
+            MouseButton::X2 => (WM_XBUTTONDBLCLK, ((XBUTTON2 as usize) << 16) | MK_XBUTTON2),
+        };
+        Self::post(hwnd, msg, wparam, point)
+    }
+
+    // lParam carries a repeat count of 1 and the scan code; key-up also sets the previous-state and
+    // transition bits, as a real release would.
+    fn post_key(&self, hwnd: HWND, virtual_key: i32, pressed: bool) -> bool {
+        let scan_code = unsafe { MapVirtualKeyA(virtual_key as u32, MAPVK_VK_TO_VSC) } as LPARAM;
+        let lparam = 1 | (scan_code << 16);
+        if pressed {
+            Self::post_message(hwnd, WM_KEYDOWN, virtual_key as usize, lparam)
+        } else {
+            Self::post_message(hwnd, WM_KEYUP, virtual_key as usize, lparam | (0b11 << 30))
+        }
+    }
+
+    fn send_button(&self, button: MouseButton, pressed: bool) -> bool {
+        match (button, pressed) {
+            (MouseButton::Left, true) => Self::send(MOUSEEVENTF_LEFTDOWN, 0),
+            (MouseButton::Right, true) => Self::send(MOUSEEVENTF_RIGHTDOWN, 0),
+            (MouseButton::Middle, true) => Self::send(MOUSEEVENTF_MIDDLEDOWN, 0),
+            (MouseButton::X1, true) => Self::send(MOUSEEVENTF_XDOWN, XBUTTON1 as u32),
+            (MouseButton::X2, true) => Self::send(MOUSEEVENTF_XDOWN, XBUTTON2 as u32),
+            (MouseButton::Left, false) => Self::send(MOUSEEVENTF_LEFTUP, 0),
+            (MouseButton::Right, false) => Self::send(MOUSEEVENTF_RIGHTUP, 0),
+            (MouseButton::Middle, false) => Self::send(MOUSEEVENTF_MIDDLEUP, 0),
+            (MouseButton::X1, false) => Self::send(MOUSEEVENTF_XUP, XBUTTON1 as u32),
+            (MouseButton::X2, false) => Self::send(MOUSEEVENTF_XUP, XBUTTON2 as u32),
+        }
+    }
+
+    fn is_key_pressed(&self, virtual_key: i32) -> bool {
+        unsafe { GetAsyncKeyState(virtual_key) < 0 }
+    }
+
+    fn set_thread_priority(&self, priority: ThreadPriority) -> bool {
+        let priority = match priority {
+            ThreadPriority::Idle => THREAD_PRIORITY_BELOW_NORMAL,
+            ThreadPriority::Normal => THREAD_PRIORITY_NORMAL,
+            ThreadPriority::TimeCritical => THREAD_PRIORITY_TIME_CRITICAL,
+        };
+        unsafe { SetThreadPriority(GetCurrentThread(), priority).is_ok() }
+    }
+
+    fn pin_current_thread(&self, core: usize) -> bool {
+        unsafe { SetThreadAffinityMask(GetCurrentThread(), 1usize << core) != 0 }
+    }
+
+    fn begin_timer_resolution(&self, resolution_ms: u32) -> bool {
+        unsafe { timeBeginPeriod(resolution_ms) == TIMERR_NOERROR }
+    }
+
+    fn end_timer_resolution(&self, resolution_ms: u32) {
+        unsafe {
+            timeEndPeriod(resolution_ms);
+        }
+    }
+
+    fn is_window(&self, hwnd: HWND) -> bool {
+        unsafe { IsWindow(hwnd) != 0 }
+    }
+
+    fn is_window_visible(&self, hwnd: HWND) -> bool {
+        unsafe { IsWindowVisible(hwnd) != 0 }
+    }
+
+    fn foreground_window(&self) -> HWND {
+        unsafe { GetForegroundWindow() }
+    }
+
+    fn root_window(&self, hwnd: HWND) -> HWND {
+        let root = unsafe { GetAncestor(hwnd, GA_ROOT) };
+        if root.is_null() { hwnd } else { root }
+    }
+
+    fn console_window(&self) -> HWND {
+        unsafe { GetConsoleWindow().0 as HWND }
+    }
+
+    fn client_size(&self, hwnd: HWND) -> Option<(i32, i32)> {
+        let mut rect = RECT { left: 0, top: 0, right: 0, bottom: 0 };
+        if unsafe { GetClientRect(hwnd, &mut rect) } == 0 {
+            return None;
+        }
+        Some((rect.right - rect.left, rect.bottom - rect.top))
+    }
+
+    fn window_title(&self, hwnd: HWND) -> String {
+        let mut title: [u16; 512] = [0; 512];
+        let title_len = unsafe { GetWindowTextW(hwnd, title.as_mut_ptr(), title.len() as i32) };
+        if title_len > 0 {
+            String::from_utf16_lossy(&title[0..title_len as usize])
+        } else {
+            String::new()
+        }
+    }
+
+    fn window_class(&self, hwnd: HWND) -> String {
+        let mut class: [u16; 256] = [0; 256];
+        let class_len = unsafe { GetClassNameW(hwnd, class.as_mut_ptr(), class.len() as i32) };
+        if class_len > 0 {
+            String::from_utf16_lossy(&class[0..class_len as usize])
+        } else {
+            String::new()
+        }
+    }
+
+    fn window_pid(&self, hwnd: HWND) -> u32 {
+        let mut process_id: DWORD = 0;
+        unsafe {
+            GetWindowThreadProcessId(hwnd, &mut process_id);
+        }
+        process_id
+    }
+
+    fn top_level_windows(&self) -> Vec<HWND> {
+        let mut windows: Vec<HWND> = Vec::new();
+        unsafe {
+            EnumWindows(Some(collect_windows_callback), &mut windows as *mut _ as LPARAM);
+        }
+        windows
+    }
+
+    fn child_windows(&self, hwnd: HWND) -> Vec<HWND> {
+        let mut windows: Vec<HWND> = Vec::new();
+        unsafe {
+            EnumChildWindows(hwnd, Some(collect_windows_callback), &mut windows as *mut _ as LPARAM);
+        }
+        windows
+    }
+
+    fn cursor_position(&self) -> Option<ClickPoint> {
+        let mut point = POINT { x: 0, y: 0 };
+        if unsafe { GetCursorPos(&mut point) } == 0 {
+            return None;
+        }
+        Some(ClickPoint { x: point.x, y: point.y })
+    }
+}
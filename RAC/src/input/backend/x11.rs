@@ -0,0 +1,448 @@
+use crate::input::backend::{InputBackend, ThreadPriority, HWND};
+use crate::input::click_executor::MouseButton;
+use crate::input::click_region::ClickPoint;
+use crate::logger::logger::{log_info, log_warn};
+use std::ffi::{c_char, c_int, c_long, c_uchar, c_uint, c_ulong, c_void, CStr};
+use std::ptr::null_mut;
+use std::sync::OnceLock;
+
+type Display = c_void;
+type Window = c_ulong;
+type Atom = c_ulong;
+
+const BUTTON1_MASK: c_uint = 1 << 8;
+const BUTTON2_MASK: c_uint = 1 << 9;
+const BUTTON3_MASK: c_uint = 1 << 10;
+const ANY_PROPERTY_TYPE: Atom = 0;
+const MAX_PROPERTY_LONGS: c_long = 4096;
+
+// Raising priority needs CAP_SYS_NICE; without it the nice call fails and the thread keeps running at 0.
+const NICE_IDLE: c_int = 5;
+const NICE_NORMAL: c_int = 0;
+const NICE_TIME_CRITICAL: c_int = -10;
+
+type XErrorHandler = unsafe extern "C" fn(*mut Display, *mut c_void) -> c_int;
+
+// Xlib and XTest are loaded at runtime so the binary still starts (and the menu still works) on
+// machines without an X server or without libXtst installed.
+struct Xlib {
+    display: *mut Display,
+    root: Window,
+    query_pointer: unsafe extern "C" fn(*mut Display, Window, *mut Window, *mut Window, *mut c_int, *mut c_int, *mut c_int, *mut c_int, *mut c_uint) -> c_int,
+    query_keymap: unsafe extern "C" fn(*mut Display, *mut c_char) -> c_int,
+    keysym_to_keycode: unsafe extern "C" fn(*mut Display, c_ulong) -> c_uchar,
+    get_input_focus: unsafe extern "C" fn(*mut Display, *mut Window, *mut c_int) -> c_int,
+    get_geometry: unsafe extern "C" fn(*mut Display, Window, *mut Window, *mut c_int, *mut c_int, *mut c_uint, *mut c_uint, *mut c_uint, *mut c_uint) -> c_int,
+    intern_atom: unsafe extern "C" fn(*mut Display, *const c_char, c_int) -> Atom,
+    get_window_property: unsafe extern "C" fn(*mut Display, Window, Atom, c_long, c_long, c_int, Atom, *mut Atom, *mut c_int, *mut c_ulong, *mut c_ulong, *mut *mut c_uchar) -> c_int,
+    free: unsafe extern "C" fn(*mut c_void) -> c_int,
+    flush: unsafe extern "C" fn(*mut Display) -> c_int,
+    fake_button_event: Option<unsafe extern "C" fn(*mut Display, c_uint, c_int, c_ulong) -> c_int>,
+    fake_key_event: Option<unsafe extern "C" fn(*mut Display, c_uint, c_int, c_ulong) -> c_int>,
+}
+
+// XInitThreads runs before the display is opened, which makes Xlib safe to call from every click thread.
+unsafe impl Send for Xlib {}
+unsafe impl Sync for Xlib {}
+
+// The default Xlib error handler exits the process; a window closing between lookups is routine here.
+unsafe extern "C" fn ignore_x_error(_display: *mut Display, _event: *mut c_void) -> c_int {
+    0
+}
+
+unsafe fn open_library(name: &CStr) -> *mut c_void {
+    libc::dlopen(name.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL)
+}
+
+unsafe fn symbol<T: Copy>(library: *mut c_void, name: &CStr) -> Option<T> {
+    let pointer = libc::dlsym(library, name.as_ptr());
+    if pointer.is_null() {
+        None
+    } else {
+        Some(std::mem::transmute_copy(&pointer))
+    }
+}
+
+impl Xlib {
+    fn load() -> Result<Self, String> {
+        unsafe {
+            let x11 = open_library(c"libX11.so.6");
+            if x11.is_null() {
+                return Err("libX11.so.6 could not be loaded".to_string());
+            }
+
+            let missing = |name: &str| format!("libX11 is missing {}", name);
+            let init_threads: unsafe extern "C" fn() -> c_int = symbol(x11, c"XInitThreads").ok_or_else(|| missing("XInitThreads"))?;
+            let open_display: unsafe extern "C" fn(*const c_char) -> *mut Display = symbol(x11, c"XOpenDisplay").ok_or_else(|| missing("XOpenDisplay"))?;
+            let default_root_window: unsafe extern "C" fn(*mut Display) -> Window = symbol(x11, c"XDefaultRootWindow").ok_or_else(|| missing("XDefaultRootWindow"))?;
+            let set_error_handler: unsafe extern "C" fn(Option<XErrorHandler>) -> Option<XErrorHandler> = symbol(x11, c"XSetErrorHandler").ok_or_else(|| missing("XSetErrorHandler"))?;
+
+            init_threads();
+            let display = open_display(std::ptr::null());
+            if display.is_null() {
+                return Err("No X display available (is DISPLAY set?)".to_string());
+            }
+            set_error_handler(Some(ignore_x_error));
+
+            let xtst = open_library(c"libXtst.so.6");
+            let fake_button_event = if xtst.is_null() { None } else { symbol(xtst, c"XTestFakeButtonEvent") };
+            let fake_key_event = if xtst.is_null() { None } else { symbol(xtst, c"XTestFakeKeyEvent") };
+
+            Ok(Self {
+                display,
+                root: default_root_window(display),
+                query_pointer: symbol(x11, c"XQueryPointer").ok_or_else(|| missing("XQueryPointer"))?,
+                query_keymap: symbol(x11, c"XQueryKeymap").ok_or_else(|| missing("XQueryKeymap"))?,
+                keysym_to_keycode: symbol(x11, c"XKeysymToKeycode").ok_or_else(|| missing("XKeysymToKeycode"))?,
+                get_input_focus: symbol(x11, c"XGetInputFocus").ok_or_else(|| missing("XGetInputFocus"))?,
+                get_geometry: symbol(x11, c"XGetGeometry").ok_or_else(|| missing("XGetGeometry"))?,
+                intern_atom: symbol(x11, c"XInternAtom").ok_or_else(|| missing("XInternAtom"))?,
+                get_window_property: symbol(x11, c"XGetWindowProperty").ok_or_else(|| missing("XGetWindowProperty"))?,
+                free: symbol(x11, c"XFree").ok_or_else(|| missing("XFree"))?,
+                flush: symbol(x11, c"XFlush").ok_or_else(|| missing("XFlush"))?,
+                fake_button_event,
+                fake_key_event,
+            })
+        }
+    }
+
+    fn atom(&self, name: &CStr) -> Atom {
+        unsafe { (self.intern_atom)(self.display, name.as_ptr(), 1) }
+    }
+
+    // Reads a window property of the given format (8 for strings, 32 for windows/cardinals).
+    fn property<T: Copy>(&self, window: Window, name: &CStr, format: c_int) -> Vec<T> {
+        let atom = self.atom(name);
+        if atom == 0 {
+            return Vec::new();
+        }
+
+        let mut actual_type: Atom = 0;
+        let mut actual_format: c_int = 0;
+        let mut items: c_ulong = 0;
+        let mut bytes_after: c_ulong = 0;
+        let mut data: *mut c_uchar = null_mut();
+        let status = unsafe {
+            (self.get_window_property)(self.display, window, atom, 0, MAX_PROPERTY_LONGS, 0, ANY_PROPERTY_TYPE,
+                                       &mut actual_type, &mut actual_format, &mut items, &mut bytes_after, &mut data)
+        };
+        if status != 0 || data.is_null() {
+            return Vec::new();
+        }
+
+        let values = if actual_format == format {
+            unsafe { std::slice::from_raw_parts(data as *const T, items as usize) }.to_vec()
+        } else {
+            Vec::new()
+        };
+        unsafe {
+            (self.free)(data as *mut c_void);
+        }
+        values
+    }
+
+    fn pointer_state(&self) -> Option<(c_int, c_int, c_uint)> {
+        let (mut root, mut child) = (0, 0);
+        let (mut root_x, mut root_y, mut window_x, mut window_y) = (0, 0, 0, 0);
+        let mut mask: c_uint = 0;
+        let found = unsafe {
+            (self.query_pointer)(self.display, self.root, &mut root, &mut child,
+                                 &mut root_x, &mut root_y, &mut window_x, &mut window_y, &mut mask)
+        };
+        if found == 0 {
+            return None;
+        }
+        Some((root_x, root_y, mask))
+    }
+
+    fn geometry(&self, window: Window) -> Option<(c_uint, c_uint)> {
+        let mut root = 0;
+        let (mut x, mut y) = (0, 0);
+        let (mut width, mut height, mut border, mut depth) = (0, 0, 0, 0);
+        let status = unsafe {
+            (self.get_geometry)(self.display, window, &mut root, &mut x, &mut y, &mut width, &mut height, &mut border, &mut depth)
+        };
+        if status == 0 {
+            return None;
+        }
+        Some((width, height))
+    }
+
+    fn fake_button(&self, button: MouseButton, pressed: bool) -> bool {
+        let Some(fake_button_event) = self.fake_button_event else {
+            return false;
+        };
+
+        let x_button = match button {
+            MouseButton::Left => 1,
+            MouseButton::Middle => 2,
+            MouseButton::Right => 3,
+            MouseButton::X1 => 8,
+            MouseButton::X2 => 9,
+        };
+
+        unsafe {
+            let sent = fake_button_event(self.display, x_button, pressed as c_int, 0) != 0;
+            (self.flush)(self.display);
+            sent
+        }
+    }
+
+    fn fake_key(&self, virtual_key: i32, pressed: bool) -> bool {
+        let Some(fake_key_event) = self.fake_key_event else {
+            return false;
+        };
+        let Some(keysym) = keysym_for_virtual_key(virtual_key) else {
+            return false;
+        };
+        let keycode = unsafe { (self.keysym_to_keycode)(self.display, keysym) };
+        if keycode == 0 {
+            return false;
+        }
+
+        unsafe {
+            let sent = fake_key_event(self.display, keycode as c_uint, pressed as c_int, 0) != 0;
+            (self.flush)(self.display);
+            sent
+        }
+    }
+
+    fn is_key_down(&self, virtual_key: i32) -> bool {
+        let mouse_mask = match virtual_key {
+            0x01 => Some(BUTTON1_MASK),
+            0x02 => Some(BUTTON3_MASK),
+            0x04 => Some(BUTTON2_MASK),
+            // The core protocol's pointer mask only covers buttons 1-5, so side buttons can't be polled.
+            0x05 | 0x06 => return false,
+            _ => None,
+        };
+        if let Some(mask) = mouse_mask {
+            return self.pointer_state().is_some_and(|(_, _, state)| state & mask != 0);
+        }
+
+        let Some(keysym) = keysym_for_virtual_key(virtual_key) else {
+            return false;
+        };
+        let keycode = unsafe { (self.keysym_to_keycode)(self.display, keysym) } as usize;
+        if keycode == 0 {
+            return false;
+        }
+
+        let mut keys = [0 as c_char; 32];
+        unsafe {
+            (self.query_keymap)(self.display, keys.as_mut_ptr());
+        }
+        (keys[keycode / 8] as u8) & (1 << (keycode % 8)) != 0
+    }
+}
+
+// Maps the Windows virtual-key codes stored in settings to X keysyms.
+fn keysym_for_virtual_key(virtual_key: i32) -> Option<c_ulong> {
+    let keysym = match virtual_key {
+        0x08 => 0xFF08,
+        0x09 => 0xFF09,
+        0x0D => 0xFF0D,
+        0x10 | 0xA0 => 0xFFE1,
+        0xA1 => 0xFFE2,
+        0x11 | 0xA2 => 0xFFE3,
+        0xA3 => 0xFFE4,
+        0x12 | 0xA4 => 0xFFE9,
+        0xA5 => 0xFFEA,
+        0x13 => 0xFF13,
+        0x14 => 0xFFE5,
+        0x1B => 0xFF1B,
+        0x20 => 0x0020,
+        0x21 => 0xFF55,
+        0x22 => 0xFF56,
+        0x23 => 0xFF57,
+        0x24 => 0xFF50,
+        0x25 => 0xFF51,
+        0x26 => 0xFF52,
+        0x27 => 0xFF53,
+        0x28 => 0xFF54,
+        0x2D => 0xFF63,
+        0x2E => 0xFFFF,
+        0x30..=0x39 => virtual_key as c_ulong,
+        0x41..=0x5A => (virtual_key + 0x20) as c_ulong,
+        0x60..=0x69 => 0xFFB0 + (virtual_key - 0x60) as c_ulong,
+        0x6B => 0xFFAB,
+        0x6D => 0xFFAD,
+        0x70..=0x87 => 0xFFBE + (virtual_key - 0x70) as c_ulong,
+        0xBB => 0x003D,
+        0xBD => 0x002D,
+        _ => return None,
+    };
+    Some(keysym)
+}
+
+pub struct X11Backend {
+    xlib: Option<Xlib>,
+}
+
+static BACKEND: OnceLock<X11Backend> = OnceLock::new();
+
+impl X11Backend {
+    pub fn get() -> &'static Self {
+        BACKEND.get_or_init(|| {
+            let context = "X11Backend::get";
+            let xlib = match Xlib::load() {
+                Ok(xlib) => {
+                    if xlib.fake_button_event.is_none() {
+                        log_warn("libXtst.so.6 not found, clicks cannot be sent", context);
+                    } else {
+                        log_info("X11 input backend ready", context);
+                    }
+                    Some(xlib)
+                }
+                Err(e) => {
+                    log_warn(&format!("X11 input backend unavailable, clicking and key polling are disabled: {}", e), context);
+                    None
+                }
+            };
+            Self { xlib }
+        })
+    }
+
+    fn window(hwnd: HWND) -> Window {
+        hwnd as Window
+    }
+}
+
+impl InputBackend for X11Backend {
+    fn name(&self) -> &'static str {
+        "X11"
+    }
+
+    // X has no reliable way to deliver input to one window (most toolkits drop XSendEvent input),
+    // so posted clicks go through XTest at the real cursor just like SendInput.
+    fn post_button(&self, _hwnd: HWND, button: MouseButton, pressed: bool, _point: ClickPoint) -> bool {
+        self.send_button(button, pressed)
+    }
+
+    fn post_move(&self, _hwnd: HWND, _point: ClickPoint) -> bool {
+        true
+    }
+
+    // Like buttons, keys can't be aimed at a window without focus-stealing, so they go to the focused one.
+    fn post_key(&self, _hwnd: HWND, virtual_key: i32, pressed: bool) -> bool {
+        self.xlib.as_ref().is_some_and(|xlib| xlib.fake_key(virtual_key, pressed))
+    }
+
+    fn send_button(&self, button: MouseButton, pressed: bool) -> bool {
+        self.xlib.as_ref().is_some_and(|xlib| xlib.fake_button(button, pressed))
+    }
+
+    fn is_key_pressed(&self, virtual_key: i32) -> bool {
+        self.xlib.as_ref().is_some_and(|xlib| xlib.is_key_down(virtual_key))
+    }
+
+    fn set_thread_priority(&self, priority: ThreadPriority) -> bool {
+        let nice = match priority {
+            ThreadPriority::Idle => NICE_IDLE,
+            ThreadPriority::Normal => NICE_NORMAL,
+            ThreadPriority::TimeCritical => NICE_TIME_CRITICAL,
+        };
+        unsafe { libc::setpriority(libc::PRIO_PROCESS, libc::gettid() as libc::id_t, nice) == 0 }
+    }
+
+    fn pin_current_thread(&self, core: usize) -> bool {
+        unsafe {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            libc::CPU_SET(core, &mut set);
+            libc::sched_setaffinity(0, size_of::<libc::cpu_set_t>(), &set) == 0
+        }
+    }
+
+    fn is_window(&self, hwnd: HWND) -> bool {
+        !hwnd.is_null() && self.xlib.as_ref().is_some_and(|xlib| xlib.geometry(Self::window(hwnd)).is_some())
+    }
+
+    fn is_window_visible(&self, hwnd: HWND) -> bool {
+        let Some(xlib) = &self.xlib else {
+            return false;
+        };
+
+        let hidden = xlib.atom(c"_NET_WM_STATE_HIDDEN");
+        self.is_window(hwnd)
+            && !xlib.property::<c_ulong>(Self::window(hwnd), c"_NET_WM_STATE", 32).contains(&hidden)
+    }
+
+    fn foreground_window(&self) -> HWND {
+        let Some(xlib) = &self.xlib else {
+            return null_mut();
+        };
+
+        if let Some(&active) = xlib.property::<c_ulong>(xlib.root, c"_NET_ACTIVE_WINDOW", 32).first() {
+            return active as HWND;
+        }
+
+        let mut focus: Window = 0;
+        let mut revert_to: c_int = 0;
+        unsafe {
+            (xlib.get_input_focus)(xlib.display, &mut focus, &mut revert_to);
+        }
+        focus as HWND
+    }
+
+    // Most terminal emulators export their own window ID to child processes.
+    fn console_window(&self) -> HWND {
+        std::env::var("WINDOWID").ok()
+            .and_then(|id| id.trim().parse::<c_ulong>().ok())
+            .map(|id| id as HWND)
+            .unwrap_or(null_mut())
+    }
+
+    fn client_size(&self, hwnd: HWND) -> Option<(i32, i32)> {
+        let (width, height) = self.xlib.as_ref()?.geometry(Self::window(hwnd))?;
+        Some((width as i32, height as i32))
+    }
+
+    fn window_title(&self, hwnd: HWND) -> String {
+        let Some(xlib) = &self.xlib else {
+            return String::new();
+        };
+
+        let mut title = xlib.property::<u8>(Self::window(hwnd), c"_NET_WM_NAME", 8);
+        if title.is_empty() {
+            title = xlib.property::<u8>(Self::window(hwnd), c"WM_NAME", 8);
+        }
+        String::from_utf8_lossy(&title).into_owned()
+    }
+
+    // WM_CLASS is "instance\0class\0"; the second half is the one Windows' class name corresponds to.
+    fn window_class(&self, hwnd: HWND) -> String {
+        let Some(xlib) = &self.xlib else {
+            return String::new();
+        };
+
+        let class = xlib.property::<u8>(Self::window(hwnd), c"WM_CLASS", 8);
+        class.split(|&byte| byte == 0)
+            .rfind(|part| !part.is_empty())
+            .map(|part| String::from_utf8_lossy(part).into_owned())
+            .unwrap_or_default()
+    }
+
+    fn window_pid(&self, hwnd: HWND) -> u32 {
+        self.xlib.as_ref()
+            .and_then(|xlib| xlib.property::<c_ulong>(Self::window(hwnd), c"_NET_WM_PID", 32).first().copied())
+            .unwrap_or(0) as u32
+    }
+
+    // The stacking list is bottom-to-top; reverse it to match EnumWindows' topmost-first order.
+    fn top_level_windows(&self) -> Vec<HWND> {
+        let Some(xlib) = &self.xlib else {
+            return Vec::new();
+        };
+
+        let mut windows = xlib.property::<c_ulong>(xlib.root, c"_NET_CLIENT_LIST_STACKING", 32);
+        if windows.is_empty() {
+            windows = xlib.property::<c_ulong>(xlib.root, c"_NET_CLIENT_LIST", 32);
+        }
+        windows.iter().rev().map(|&window| window as HWND).collect()
+    }
+
+    fn cursor_position(&self) -> Option<ClickPoint> {
+        let (x, y, _) = self.xlib.as_ref()?.pointer_state()?;
+        Some(ClickPoint { x, y })
+    }
+}
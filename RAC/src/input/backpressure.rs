@@ -0,0 +1,115 @@
+use std::time::Duration;
+
+const MAX_MULTIPLIER: f64 = 8.0;
+const RECOVERY_RATE: f64 = 0.1;
+
+pub struct Backpressure {
+    aggressiveness: f64,
+    multiplier: f64,
+}
+
+impl Backpressure {
+    pub fn new(aggressiveness: f64) -> Self {
+        Self {
+            aggressiveness: Self::clamp_aggressiveness(aggressiveness),
+            multiplier: 1.0,
+        }
+    }
+
+    fn clamp_aggressiveness(aggressiveness: f64) -> f64 {
+        if aggressiveness.is_finite() { aggressiveness.clamp(0.0, 1.0) } else { 0.0 }
+    }
+
+    // Turning backpressure off also drops any slowdown already built up.
+    pub fn set_aggressiveness(&mut self, aggressiveness: f64) {
+        self.aggressiveness = Self::clamp_aggressiveness(aggressiveness);
+        if self.aggressiveness <= 0.0 {
+            self.multiplier = 1.0;
+        }
+    }
+
+    pub fn record_failure(&mut self) {
+        if self.aggressiveness <= 0.0 {
+            return;
+        }
+        self.multiplier = (self.multiplier * (1.0 + self.aggressiveness)).min(MAX_MULTIPLIER);
+    }
+
+    pub fn record_success(&mut self) {
+        // Each success only gives back a fraction of the slowdown so a flaky target isn't hammered again right away.
+        self.multiplier = 1.0 + (self.multiplier - 1.0) * (1.0 - RECOVERY_RATE);
+        if self.multiplier < 1.01 {
+            self.multiplier = 1.0;
+        }
+    }
+
+    pub fn is_throttling(&self) -> bool {
+        self.multiplier > 1.0
+    }
+
+    pub fn multiplier(&self) -> f64 {
+        self.multiplier
+    }
+
+    pub fn scale(&self, delay: Duration) -> Duration {
+        delay.mul_f64(self.multiplier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE_DELAY: Duration = Duration::from_millis(50);
+
+    #[test]
+    fn intermittent_failures_slow_clicking_down_until_it_recovers() {
+        let mut backpressure = Backpressure::new(0.5);
+
+        // Every other click fails: the slowdown still builds up faster than successes give it back.
+        for _ in 0..10 {
+            backpressure.record_failure();
+            backpressure.record_success();
+        }
+        assert!(backpressure.is_throttling());
+        assert!(backpressure.scale(BASE_DELAY) > BASE_DELAY * 2);
+
+        let mut previous = backpressure.scale(BASE_DELAY);
+        while backpressure.is_throttling() {
+            backpressure.record_success();
+            let delay = backpressure.scale(BASE_DELAY);
+            assert!(delay < previous);
+            previous = delay;
+        }
+        assert_eq!(backpressure.scale(BASE_DELAY), BASE_DELAY);
+    }
+
+    #[test]
+    fn the_slowdown_is_capped() {
+        let mut backpressure = Backpressure::new(1.0);
+        for _ in 0..100 {
+            backpressure.record_failure();
+        }
+        assert_eq!(backpressure.multiplier(), MAX_MULTIPLIER);
+    }
+
+    #[test]
+    fn zero_aggressiveness_never_throttles() {
+        for aggressiveness in [0.0, -1.0, f64::NAN] {
+            let mut backpressure = Backpressure::new(aggressiveness);
+            backpressure.record_failure();
+            assert!(!backpressure.is_throttling());
+        }
+    }
+
+    #[test]
+    fn set_aggressiveness_applies_to_later_failures() {
+        let mut backpressure = Backpressure::new(0.0);
+        backpressure.set_aggressiveness(2.0);
+        backpressure.record_failure();
+        assert_eq!(backpressure.multiplier(), 2.0);
+
+        backpressure.set_aggressiveness(0.0);
+        assert!(!backpressure.is_throttling());
+    }
+}
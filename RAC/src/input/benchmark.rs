@@ -0,0 +1,101 @@
+use crate::config::settings::Settings;
+use crate::input::backend::HWND;
+use crate::input::click_executor::{ClickExecutor, GameMode, MouseButton, TimingMode};
+use crate::input::click_region::{ClickPoint, ClickRegion};
+use crate::input::click_strategy::{ClickSink, ClickStrategy, ClickStrategyKind};
+use crate::input::delay_provider::DelayProvider;
+use crate::input::session_stats::SessionStatsSnapshot;
+use crate::input::thread_controller::ThreadController;
+use crate::logger::logger::log_info;
+use std::ptr::NonNull;
+use std::time::{Duration, Instant};
+
+// Swallows every click and reports it delivered, so the benchmark measures RAC's own timing
+// without a game window, a message queue or the input backend in the way.
+struct BenchmarkSink;
+
+impl ClickStrategy for BenchmarkSink {
+    fn name(&self) -> &'static str {
+        "Benchmark"
+    }
+
+    fn press(&self, _hwnd: HWND, _button: MouseButton, _point: ClickPoint) -> bool {
+        true
+    }
+
+    fn release(&self, _hwnd: HWND, _button: MouseButton, _point: ClickPoint) -> bool {
+        true
+    }
+}
+
+impl ClickSink for BenchmarkSink {
+    fn strategy(&self, _kind: ClickStrategyKind) -> &dyn ClickStrategy {
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BenchmarkResult {
+    pub target_cps: f64,
+    pub min_click_delay_micros: u64,
+    pub elapsed: Duration,
+    pub stats: SessionStatsSnapshot,
+}
+
+impl BenchmarkResult {
+    pub fn achieved_cps(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs <= 0.0 { 0.0 } else { self.stats.clicks as f64 / secs }
+    }
+}
+
+// Runs the click loop's timing path (executor cadence, delay provider, floor and sleeps) for one
+// button at the given settings. Blocks the calling thread for `duration`.
+pub fn run(settings: &Settings, button: MouseButton, duration: Duration) -> BenchmarkResult {
+    let thread_controller = ThreadController::new(settings.adaptive_cpu_mode);
+    thread_controller.set_spin_threshold_micros(settings.spin_threshold_micros);
+
+    let executor = ClickExecutor::with_io(Box::new(BenchmarkSink), Box::new(thread_controller.clone()));
+    executor.set_mouse_button(button);
+    // The sink never looks at the point, and Origin keeps the region from querying the fake window.
+    executor.set_click_region(ClickRegion::Origin);
+    let (max_cps, game_mode, min_click_delay_micros) = match button {
+        MouseButton::Right => (settings.right_max_cps, &settings.right_game_mode, settings.effective_right_min_click_delay_micros()),
+        _ => (settings.left_max_cps, &settings.left_game_mode, settings.effective_left_min_click_delay_micros()),
+    };
+    executor.set_max_cps(max_cps);
+    executor.set_game_mode(GameMode::from_setting(game_mode));
+    executor.set_double_click(settings.double_click, settings.double_click_max_cps);
+    executor.set_click_delays(settings.left_click_delay_micros, settings.right_click_delay_micros);
+    executor.set_timing_mode(TimingMode::from_setting(&settings.timing_mode));
+    executor.reset_session_stats();
+
+    let mut delay_provider = DelayProvider::for_button(settings, button);
+    // Never handed to the OS: the sink intercepts every call that would use it.
+    let hwnd: HWND = NonNull::dangling().as_ptr();
+
+    let started = Instant::now();
+    let mut last_click = started;
+    while started.elapsed() < duration {
+        executor.execute_click(hwnd);
+
+        let delay = delay_provider.get_next_delay(executor.next_click_delay());
+        let elapsed = last_click.elapsed();
+        if elapsed < delay {
+            thread_controller.smart_sleep(delay.saturating_sub(elapsed));
+        }
+        last_click = Instant::now();
+    }
+
+    let result = BenchmarkResult {
+        target_cps: executor.target_cps(),
+        min_click_delay_micros,
+        elapsed: started.elapsed(),
+        stats: executor.session_stats(),
+    };
+    log_info(&format!("Benchmark {:?}: target {:.1} CPS, achieved {:.2} CPS over {} clicks, interval min/mean/max {}/{}/{}us",
+                      button, result.target_cps, result.achieved_cps(), result.stats.clicks,
+                      result.stats.min_delay_micros, result.stats.mean_delay_micros, result.stats.max_delay_micros),
+             "benchmark::run");
+    result
+}
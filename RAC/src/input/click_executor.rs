@@ -1,70 +1,189 @@
-use crate::input::thread_controller::ThreadController;
+use crate::input::click_region::{ClickPoint, ClickRegion};
+use crate::input::click_strategy::{ClickSink, ClickStrategyKind, SystemClickSink};
+use crate::input::cps_drift::CpsDrift;
+use crate::input::dispatch_latency::{DispatchLatency, DispatchLatencySnapshot};
+use crate::input::session_stats::{SessionStats, SessionStatsSnapshot};
+use crate::input::thread_controller::{Sleeper, ThreadController};
+use crate::config::constants::limits;
 use crate::config::settings::Settings;
-use crate::logger::logger::{log_error, log_info};
+use crate::logger::logger::{log_error, log_info, log_warn};
 use rand::Rng;
-use std::time::Duration;
-use std::sync::atomic::{AtomicU8, AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use std::sync::atomic::{AtomicU8, AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
-use winapi::{
-    shared::windef::HWND,
-    um::winuser::{PostMessageA, WM_LBUTTONDOWN, WM_LBUTTONUP, WM_RBUTTONDOWN, WM_RBUTTONUP},
-};
-use winapi::um::winuser::{MK_LBUTTON, MK_RBUTTON};
+use crate::input::backend::HWND;
 
+// Middle and the side buttons run on the left executor and share its timing settings.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MouseButton {
     Left,
-    Right
+    Right,
+    Middle,
+    X1,
+    X2,
+}
+
+impl MouseButton {
+    pub fn virtual_key(&self) -> i32 {
+        match self {
+            MouseButton::Left => 0x01,
+            MouseButton::Right => 0x02,
+            MouseButton::Middle => 0x04,
+            MouseButton::X1 => 0x05,
+            MouseButton::X2 => 0x06,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum GameMode {
     Combo,
-    Default
+    Default,
+    Butterfly,
+    Jitter,
 }
 
+impl GameMode {
+    pub fn from_setting(value: &str) -> Self {
+        match value {
+            "Combo" => GameMode::Combo,
+            "Butterfly" => GameMode::Butterfly,
+            "Jitter" => GameMode::Jitter,
+            _ => GameMode::Default,
+        }
+    }
+
+    pub fn as_setting(&self) -> &'static str {
+        match self {
+            GameMode::Combo => "Combo",
+            GameMode::Default => "Default",
+            GameMode::Butterfly => "Butterfly",
+            GameMode::Jitter => "Jitter",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimingMode {
+    Cps,
+    Interval,
+}
+
+impl TimingMode {
+    pub fn from_setting(value: &str) -> Self {
+        match value {
+            "Interval" => TimingMode::Interval,
+            _ => TimingMode::Cps,
+        }
+    }
+
+    pub fn as_setting(&self) -> &'static str {
+        match self {
+            TimingMode::Cps => "Cps",
+            TimingMode::Interval => "Interval",
+        }
+    }
+}
+
+const WARMUP_START_FACTOR: f64 = 0.25;
+
 pub struct ClickExecutor {
-    thread_controller: ThreadController,
+    sink: Box<dyn ClickSink>,
+    sleeper: Box<dyn Sleeper>,
     left_game_mode: Arc<Mutex<GameMode>>,
     right_game_mode: Arc<Mutex<GameMode>>,
     left_max_cps: AtomicU8,
     right_max_cps: AtomicU8,
     left_click_delay_micros: AtomicUsize,
     right_click_delay_micros: AtomicUsize,
+    interval_mode: AtomicBool,
+    mixed_input_ratio: Mutex<f64>,
+    cps_drift: Mutex<CpsDrift>,
     active: AtomicBool,
+    hold_mode: AtomicBool,
+    double_click: AtomicBool,
+    double_click_max_cps: AtomicU8,
+    held_hwnd: AtomicUsize,
+    // The button pressed by hold_button and the strategy that pressed it, which the release has to match
+    // even if the executor has since been switched to another button or strategy.
+    held_button: Mutex<Option<(MouseButton, ClickStrategyKind)>>,
+    // Set from a press until a release is delivered, so an interrupted click or a dropped button-up
+    // can still be balanced when clicking stops.
+    pending_release: Mutex<Option<(usize, ClickStrategyKind, MouseButton, ClickPoint)>>,
+    held_point: Mutex<ClickPoint>,
+    click_region: Mutex<ClickRegion>,
+    simulate_movement: AtomicBool,
+    latency_diagnostics: AtomicBool,
+    dispatch_latency: DispatchLatency,
+    primary_strategy: Mutex<ClickStrategyKind>,
+    fallback_strategy: Mutex<Option<ClickStrategyKind>>,
+    using_fallback: AtomicBool,
+    strategy_failures: AtomicU32,
+    fallback_failure_threshold: AtomicU32,
     current_button: Mutex<MouseButton>,
+    butterfly_second_finger: AtomicBool,
+    session_stats: SessionStats,
+    warmup_ms: AtomicU64,
+    warmup_started: Mutex<Option<Instant>>,
 }
 
 impl ClickExecutor {
     pub fn new(thread_controller: ThreadController) -> Self {
+        Self::with_io(Box::new(SystemClickSink), Box::new(thread_controller))
+    }
+
+    // Production uses the system sink and the click thread's controller; any other pair can stand in
+    // to drive execute_click without a target window.
+    pub fn with_io(sink: Box<dyn ClickSink>, sleeper: Box<dyn Sleeper>) -> Self {
         let settings = Settings::load().unwrap_or_else(|_| Settings::default());
 
-        let left_mode = match settings.left_game_mode.as_str() {
-            "Combo" => GameMode::Combo,
-            _ => GameMode::Default,
-        };
-        
-        let right_mode = match settings.right_game_mode.as_str() {
-            "Combo" => GameMode::Combo,
-            _ => GameMode::Default,
-        };
+        let left_mode = GameMode::from_setting(&settings.left_game_mode);
+        let right_mode = GameMode::from_setting(&settings.right_game_mode);
 
         Self {
-            thread_controller,
+            sink,
+            sleeper,
             left_game_mode: Arc::new(Mutex::new(left_mode)),
             right_game_mode: Arc::new(Mutex::new(right_mode)),
-            left_max_cps: AtomicU8::new(settings.left_max_cps),
-            right_max_cps: AtomicU8::new(settings.right_max_cps),
+            left_max_cps: AtomicU8::new(settings.left_max_cps.clamp(limits::MIN_CPS, limits::MAX_CPS)),
+            right_max_cps: AtomicU8::new(settings.right_max_cps.clamp(limits::MIN_CPS, limits::MAX_CPS)),
             left_click_delay_micros: AtomicUsize::new(settings.left_click_delay_micros as usize),
             right_click_delay_micros: AtomicUsize::new(settings.right_click_delay_micros as usize),
+            interval_mode: AtomicBool::new(TimingMode::from_setting(&settings.timing_mode) == TimingMode::Interval),
+            mixed_input_ratio: Mutex::new(settings.effective_mixed_input_ratio()),
+            cps_drift: Mutex::new(CpsDrift::new(
+                settings.cps_drift_enabled,
+                settings.cps_drift_band,
+                settings.cps_drift_period_secs,
+            )),
             active: AtomicBool::new(true),
+            hold_mode: AtomicBool::new(settings.hold_mode),
+            double_click: AtomicBool::new(settings.double_click),
+            double_click_max_cps: AtomicU8::new(settings.double_click_max_cps.clamp(limits::MIN_CPS, limits::MAX_CPS)),
+            held_hwnd: AtomicUsize::new(0),
+            held_button: Mutex::new(None),
+            pending_release: Mutex::new(None),
+            held_point: Mutex::new(ClickPoint::ORIGIN),
+            click_region: Mutex::new(ClickRegion::from_settings(&settings)),
+            simulate_movement: AtomicBool::new(settings.simulate_movement),
+            latency_diagnostics: AtomicBool::new(settings.dispatch_latency_diagnostics),
+            dispatch_latency: DispatchLatency::new(),
+            primary_strategy: Mutex::new(ClickStrategyKind::from_setting(&settings.click_strategy).unwrap_or(ClickStrategyKind::PostMessage)),
+            fallback_strategy: Mutex::new(ClickStrategyKind::from_setting(&settings.fallback_click_strategy)),
+            using_fallback: AtomicBool::new(false),
+            strategy_failures: AtomicU32::new(0),
+            fallback_failure_threshold: AtomicU32::new(settings.fallback_failure_threshold.max(1)),
             current_button: Mutex::new(MouseButton::Left),
+            butterfly_second_finger: AtomicBool::new(false),
+            session_stats: SessionStats::new(),
+            warmup_ms: AtomicU64::new(settings.warmup_ms),
+            warmup_started: Mutex::new(None),
         }
     }
 
     pub fn update_delay(&self, click_delay_micros: u64) {
         match *self.current_button.lock().unwrap() {
-            MouseButton::Left => {
+            MouseButton::Left | MouseButton::Middle | MouseButton::X1 | MouseButton::X2 => {
                 self.left_click_delay_micros.store(click_delay_micros as usize, Ordering::SeqCst);
             },
             MouseButton::Right => {
@@ -73,17 +192,49 @@ impl ClickExecutor {
         }
     }
 
+    pub fn set_click_delays(&self, left_click_delay_micros: u64, right_click_delay_micros: u64) {
+        self.left_click_delay_micros.store(left_click_delay_micros as usize, Ordering::SeqCst);
+        self.right_click_delay_micros.store(right_click_delay_micros as usize, Ordering::SeqCst);
+    }
+
+    pub fn set_timing_mode(&self, timing_mode: TimingMode) {
+        self.interval_mode.store(timing_mode == TimingMode::Interval, Ordering::SeqCst);
+    }
+
+    pub fn set_mixed_input_ratio(&self, ratio: f64) {
+        if let Ok(mut mixed_input_ratio) = self.mixed_input_ratio.lock() {
+            *mixed_input_ratio = if ratio.is_nan() { 0.0 } else { ratio.clamp(0.0, 1.0) };
+        }
+    }
+
+    pub fn set_cps_drift(&self, enabled: bool, band: f64, period_secs: u64) {
+        if let Ok(mut cps_drift) = self.cps_drift.lock() {
+            cps_drift.configure(enabled, band, period_secs);
+        }
+    }
+
     pub fn set_left_max_cps(&self, max_cps: u8) {
-        self.left_max_cps.store(max_cps, Ordering::SeqCst);
+        self.left_max_cps.store(Self::clamp_cps(max_cps), Ordering::SeqCst);
     }
     
     pub fn set_right_max_cps(&self, max_cps: u8) {
-        self.right_max_cps.store(max_cps, Ordering::SeqCst);
+        self.right_max_cps.store(Self::clamp_cps(max_cps), Ordering::SeqCst);
+    }
+
+    pub fn is_valid_cps(cps: u8) -> bool {
+        (limits::MIN_CPS..=limits::MAX_CPS).contains(&cps)
+    }
+
+    fn clamp_cps(cps: u8) -> u8 {
+        if !Self::is_valid_cps(cps) {
+            log_warn(&format!("Max CPS {} is outside {}-{}, clamping", cps, limits::MIN_CPS, limits::MAX_CPS), "ClickExecutor::clamp_cps");
+        }
+        cps.clamp(limits::MIN_CPS, limits::MAX_CPS)
     }
 
     pub fn set_max_cps(&self, max_cps: u8) {
         match *self.current_button.lock().unwrap() {
-            MouseButton::Left => self.set_left_max_cps(max_cps),
+            MouseButton::Left | MouseButton::Middle | MouseButton::X1 | MouseButton::X2 => self.set_left_max_cps(max_cps),
             MouseButton::Right => self.set_right_max_cps(max_cps),
         }
     }
@@ -102,18 +253,22 @@ impl ClickExecutor {
 
     pub fn set_game_mode(&self, mode: GameMode) {
         match *self.current_button.lock().unwrap() {
-            MouseButton::Left => self.set_left_game_mode(mode),
+            MouseButton::Left | MouseButton::Middle | MouseButton::X1 | MouseButton::X2 => self.set_left_game_mode(mode),
             MouseButton::Right => self.set_right_game_mode(mode),
         }
     }
     
     pub fn get_game_mode(&self) -> GameMode {
         match *self.current_button.lock().unwrap() {
-            MouseButton::Left => *self.left_game_mode.lock().unwrap(),
+            MouseButton::Left | MouseButton::Middle | MouseButton::X1 | MouseButton::X2 => *self.left_game_mode.lock().unwrap(),
             MouseButton::Right => *self.right_game_mode.lock().unwrap(),
         }
     }
 
+    pub fn current_button(&self) -> MouseButton {
+        self.current_button.lock().map(|button| *button).unwrap_or(MouseButton::Left)
+    }
+
     pub fn set_mouse_button(&self, button: MouseButton) {
         if let Ok(mut current) = self.current_button.lock() {
             *current = button;
@@ -125,6 +280,8 @@ impl ClickExecutor {
             return false;
         }
 
+        let dispatch_start = if self.latency_diagnostics.load(Ordering::Relaxed) { Some(Instant::now()) } else { None };
+
         let context = "ClickExecutor::execute_click";
         let button = match self.current_button.lock() {
             Ok(button) => *button,
@@ -134,78 +291,721 @@ impl ClickExecutor {
             }
         };
 
-        let (down_msg, up_msg, flags, max_cps, game_mode, _click_delay) = match button {
-            MouseButton::Left => {
-                (
-                    WM_LBUTTONDOWN, 
-                    WM_LBUTTONUP, 
-                    MK_LBUTTON,
-                    self.left_max_cps.load(Ordering::SeqCst),
-                    *self.left_game_mode.lock().unwrap(),
-                    self.left_click_delay_micros.load(Ordering::SeqCst) as u64
-                )
-            },
-            MouseButton::Right => {
-                (
-                    WM_RBUTTONDOWN, 
-                    WM_RBUTTONUP, 
-                    MK_RBUTTON,
-                    self.right_max_cps.load(Ordering::SeqCst),
-                    *self.right_game_mode.lock().unwrap(),
-                    self.right_click_delay_micros.load(Ordering::SeqCst) as u64
-                )
-            }
-        };
+        let mixed_input_ratio = self.mixed_input_ratio.lock().map(|ratio| *ratio).unwrap_or(0.0);
+        let configured_strategy = self.current_strategy();
+        let click_region = self.click_region.lock().map(|region| *region).unwrap_or(ClickRegion::Origin);
+        let simulate_movement = self.simulate_movement.load(Ordering::Relaxed);
+        let double_click = self.double_click.load(Ordering::Relaxed);
 
-        let cps_delay = if max_cps == 0 { 1_000_000 } else { 1_000_000 / max_cps as u64 };
+        let delivered = std::panic::catch_unwind(|| {
+            let mut rng = rand::rng();
 
-        unsafe {
-            if let Err(_) = std::panic::catch_unwind(|| {
-                let mut rng = rand::rng();
+            let use_send_input = mixed_input_ratio > 0.0 && rng.random_bool(mixed_input_ratio);
+            let strategy = if use_send_input { ClickStrategyKind::SendInput } else { configured_strategy };
 
-                PostMessageA(hwnd, down_msg, flags, 0);
+            let point = click_region.pick(hwnd, &mut rng);
+            if simulate_movement {
+                self.sink.strategy(strategy).move_to(hwnd, point);
+            }
 
-                let down_time = 1; // 0.25ms
-                self.thread_controller.smart_sleep(Duration::from_micros(down_time));
+            if let Ok(mut pending_release) = self.pending_release.lock() {
+                *pending_release = Some((hwnd as usize, strategy, button, point));
+            }
+            let pressed = self.sink.strategy(strategy).press(hwnd, button, point);
 
-                PostMessageA(hwnd, up_msg, 0, 0);
+            if let Some(dispatch_start) = dispatch_start {
+                self.dispatch_latency.record(dispatch_start.elapsed());
+            }
 
-                let mut adjusted_delay = cps_delay.saturating_sub(down_time);
+            let down_time = 1; // 0.25ms
+            self.sleeper.sleep(Duration::from_micros(down_time));
 
-                if game_mode == GameMode::Combo {
-                    #[allow(deprecated)]
-                    let jitter = rng.gen_range(-500..=500);
-                    
-                    adjusted_delay = adjusted_delay.saturating_add_signed(jitter);
+            let mut released = self.sink.strategy(strategy).release(hwnd, button, point);
 
-                    if adjusted_delay < cps_delay.saturating_sub(down_time) {
-                        adjusted_delay = cps_delay.saturating_sub(down_time);
-                    }
+            // Down, up, double-click, up: the same sequence Windows produces for a real double-click.
+            let mut pressed = pressed;
+            let mut last_released = released;
+            if double_click {
+                self.sleeper.sleep(Duration::from_micros(down_time));
+                pressed &= self.sink.strategy(strategy).double_press(hwnd, button, point);
+                self.sleeper.sleep(Duration::from_micros(down_time));
+                last_released = self.sink.strategy(strategy).release(hwnd, button, point);
+                released &= last_released;
+            }
+
+            if last_released {
+                if let Ok(mut pending_release) = self.pending_release.lock() {
+                    *pending_release = None;
                 }
+            }
 
-                self.thread_controller.smart_sleep(Duration::from_micros(adjusted_delay));
-            }) {
+            if !use_send_input {
+                self.record_strategy_result(strategy, pressed && released);
+            }
+
+            pressed && released
+        });
+
+        match delivered {
+            Ok(delivered) => {
+                if delivered {
+                    self.session_stats.record_click();
+                } else {
+                    self.session_stats.record_failure();
+                }
+                delivered
+            },
+            Err(_) => {
                 log_error("Failed to execute mouse event", context);
-                return false;
+                self.release_pending_click();
+                self.session_stats.record_failure();
+                false
             }
         }
+    }
 
+    // Cadence from max CPS (or the configured interval), drift and the game mode pattern; the click
+    // loop's delay provider adds deviation and the floor.
+    pub fn next_click_delay(&self) -> Duration {
+        let (max_cps, click_delay_micros, game_mode) = match *self.current_button.lock().unwrap() {
+            MouseButton::Left | MouseButton::Middle | MouseButton::X1 | MouseButton::X2 =>
+                (self.left_max_cps.load(Ordering::SeqCst), self.left_click_delay_micros.load(Ordering::SeqCst), *self.left_game_mode.lock().unwrap()),
+            MouseButton::Right =>
+                (self.right_max_cps.load(Ordering::SeqCst), self.right_click_delay_micros.load(Ordering::SeqCst), *self.right_game_mode.lock().unwrap()),
+        };
+        let double_click_max_cps = if self.double_click.load(Ordering::Relaxed) {
+            Some(self.double_click_max_cps.load(Ordering::SeqCst))
+        } else {
+            None
+        };
+
+        let cps_delay = if self.interval_mode.load(Ordering::SeqCst) {
+            // Drift varies a CPS target, so it has nothing to act on here; warmup still stretches the interval.
+            let interval = match double_click_max_cps {
+                Some(cap) => (click_delay_micros as f64).max(1_000_000.0 / cap.max(1) as f64),
+                None => click_delay_micros as f64,
+            };
+            (interval / self.warmup_factor()) as u64
+        } else {
+            let max_cps = double_click_max_cps.map_or(max_cps, |cap| max_cps.min(cap));
+            let effective_cps = match self.cps_drift.lock() {
+                Ok(mut cps_drift) => cps_drift.apply(max_cps),
+                Err(_) => max_cps as f64,
+            } * self.warmup_factor();
+            if effective_cps <= 0.0 { 1_000_000 } else { (1_000_000.0 / effective_cps) as u64 }
+        };
+
+        let mut rng = rand::rng();
+        let delay = match game_mode {
+            GameMode::Combo => cps_delay.saturating_add(rng.random_range(0..=500)),
+            GameMode::Butterfly => {
+                // Two fingers: a tight gap inside each pair and a long gap after it, so bursts run at
+                // twice max_cps while the average over a pair stays at max_cps.
+                let second_finger = self.butterfly_second_finger.fetch_xor(true, Ordering::SeqCst);
+                let base = if second_finger { cps_delay + cps_delay / 2 } else { cps_delay / 2 };
+                base.saturating_add_signed(rng.random_range(-300..=300))
+            },
+            GameMode::Jitter => {
+                let spread = (cps_delay / 4) as i64;
+                let mut delay = cps_delay.saturating_add_signed(rng.random_range(-spread..=spread));
+                if rng.random_bool(0.05) {
+                    delay = delay.saturating_add(rng.random_range(2_000..=8_000));
+                }
+                delay
+            },
+            GameMode::Default => cps_delay,
+        };
+
+        Duration::from_micros(delay)
+    }
+
+    pub fn set_latency_diagnostics(&self, enabled: bool) {
+        self.latency_diagnostics.store(enabled, Ordering::SeqCst);
+        if !enabled {
+            self.dispatch_latency.reset();
+        }
+    }
+
+    pub fn dispatch_latency(&self) -> Option<DispatchLatencySnapshot> {
+        self.dispatch_latency.snapshot()
+    }
+
+    pub fn reset_dispatch_latency(&self) {
+        self.dispatch_latency.reset();
+    }
+
+    pub fn set_hold_mode(&self, enabled: bool) {
+        self.hold_mode.store(enabled, Ordering::SeqCst);
+        if !enabled {
+            self.release_hold();
+        }
+    }
+
+    pub fn set_double_click(&self, enabled: bool, max_cps: u8) {
+        self.double_click.store(enabled, Ordering::SeqCst);
+        self.double_click_max_cps.store(max_cps.clamp(limits::MIN_CPS, limits::MAX_CPS), Ordering::SeqCst);
+    }
+
+    pub fn is_hold_mode(&self) -> bool {
+        self.hold_mode.load(Ordering::SeqCst)
+    }
+
+    pub fn hold_button(&self, hwnd: HWND) -> bool {
+        if hwnd.is_null() || !self.active.load(Ordering::SeqCst) {
+            return false;
+        }
+
+        let held = self.held_hwnd.load(Ordering::SeqCst);
+        if held == hwnd as usize {
+            return true;
+        }
+        if held != 0 {
+            self.release_hold();
+        }
+
+        let context = "ClickExecutor::hold_button";
+        let button = *self.current_button.lock().unwrap();
+        let strategy = self.current_strategy();
+        let point = self.click_region.lock().map(|region| *region).unwrap_or(ClickRegion::Origin).pick(hwnd, &mut rand::rng());
+        let pressed = self.sink.strategy(strategy).press(hwnd, button, point);
+        self.record_strategy_result(strategy, pressed);
+        if !pressed {
+            log_warn(&format!("Failed to hold {:?} button down with {}", button, strategy.strategy().name()), context);
+            return false;
+        }
+
+        if let Ok(mut held_point) = self.held_point.lock() {
+            *held_point = point;
+        }
+        if let Ok(mut held_button) = self.held_button.lock() {
+            *held_button = Some((button, strategy));
+        }
+        self.held_hwnd.store(hwnd as usize, Ordering::SeqCst);
+
+        log_info(&format!("Holding {:?} button down on hwnd={:?}", button, hwnd), context);
         true
     }
 
+    pub fn release_hold(&self) {
+        let held = self.held_hwnd.swap(0, Ordering::SeqCst);
+        if held == 0 {
+            return;
+        }
+
+        let (button, strategy) = self.held_button.lock().ok().and_then(|mut held_button| held_button.take())
+            .unwrap_or_else(|| (self.current_button(), self.current_strategy()));
+        let point = self.held_point.lock().map(|point| *point).unwrap_or(ClickPoint::ORIGIN);
+        self.sink.strategy(strategy).release(held as HWND, button, point);
+
+        log_info(&format!("Released held {:?} button", button), "ClickExecutor::release_hold");
+    }
+
+    // PostMessage is asynchronous, so the extra WM_MOUSEMOVE adds no wait to the click cadence.
+    pub fn set_simulate_movement(&self, simulate_movement: bool) {
+        self.simulate_movement.store(simulate_movement, Ordering::SeqCst);
+    }
+
+    pub fn set_click_region(&self, click_region: ClickRegion) {
+        if let Ok(mut region) = self.click_region.lock() {
+            *region = click_region;
+        }
+    }
+
+    pub fn release_pending_click(&self) {
+        let pending = match self.pending_release.lock() {
+            Ok(mut pending_release) => pending_release.take(),
+            Err(_) => return,
+        };
+
+        if let Some((hwnd, strategy, button, point)) = pending {
+            self.sink.strategy(strategy).release(hwnd as HWND, button, point);
+            log_info(&format!("Sent balancing button-up for {:?} left down by an interrupted click", button), "ClickExecutor::release_pending_click");
+        }
+    }
+
+    pub fn set_click_strategy(&self, primary: ClickStrategyKind, fallback: Option<ClickStrategyKind>, failure_threshold: u32) {
+        if let Ok(mut primary_strategy) = self.primary_strategy.lock() {
+            *primary_strategy = primary;
+        }
+        if let Ok(mut fallback_strategy) = self.fallback_strategy.lock() {
+            *fallback_strategy = fallback;
+        }
+        self.fallback_failure_threshold.store(failure_threshold.max(1), Ordering::SeqCst);
+        self.using_fallback.store(false, Ordering::SeqCst);
+        self.strategy_failures.store(0, Ordering::SeqCst);
+    }
+
+    pub fn current_strategy(&self) -> ClickStrategyKind {
+        let primary = *self.primary_strategy.lock().unwrap();
+        if self.using_fallback.load(Ordering::SeqCst) {
+            if let Some(fallback) = *self.fallback_strategy.lock().unwrap() {
+                return fallback;
+            }
+        }
+        primary
+    }
+
+    fn record_strategy_result(&self, strategy: ClickStrategyKind, success: bool) {
+        if success {
+            self.strategy_failures.store(0, Ordering::SeqCst);
+            return;
+        }
+
+        let failures = self.strategy_failures.fetch_add(1, Ordering::SeqCst) + 1;
+        if failures < self.fallback_failure_threshold.load(Ordering::SeqCst) || self.using_fallback.load(Ordering::SeqCst) {
+            return;
+        }
+
+        if let Some(fallback) = *self.fallback_strategy.lock().unwrap() {
+            if fallback == strategy {
+                return;
+            }
+
+            self.using_fallback.store(true, Ordering::SeqCst);
+            self.strategy_failures.store(0, Ordering::SeqCst);
+            log_warn(&format!("{} failed {} times in a row, switching to fallback {} for this session",
+                              strategy.strategy().name(), failures, fallback.strategy().name()),
+                     "ClickExecutor::record_strategy_result");
+        }
+    }
+
+    // The rate the current button aims for before deviation and the floor: Max CPS, or the rate
+    // implied by the interval in Interval mode, capped by double click.
+    pub fn target_cps(&self) -> f64 {
+        let cps = if self.interval_mode.load(Ordering::SeqCst) {
+            let click_delay_micros = match *self.current_button.lock().unwrap() {
+                MouseButton::Left | MouseButton::Middle | MouseButton::X1 | MouseButton::X2 => self.left_click_delay_micros.load(Ordering::SeqCst),
+                MouseButton::Right => self.right_click_delay_micros.load(Ordering::SeqCst),
+            };
+            1_000_000.0 / click_delay_micros.max(1) as f64
+        } else {
+            self.get_current_max_cps() as f64
+        };
+
+        if self.double_click.load(Ordering::Relaxed) {
+            cps.min(self.double_click_max_cps.load(Ordering::SeqCst) as f64)
+        } else {
+            cps
+        }
+    }
+
     pub fn get_current_max_cps(&self) -> u8 {
         match *self.current_button.lock().unwrap() {
-            MouseButton::Left => self.left_max_cps.load(Ordering::SeqCst),
+            MouseButton::Left | MouseButton::Middle | MouseButton::X1 | MouseButton::X2 => self.left_max_cps.load(Ordering::SeqCst),
             MouseButton::Right => self.right_max_cps.load(Ordering::SeqCst),
         }
     }
 
     pub fn set_active(&self, active: bool) {
         self.active.store(active, Ordering::SeqCst);
+        if !active {
+            self.release_hold();
+            self.release_pending_click();
+        }
+    }
+
+    pub fn set_warmup_ms(&self, warmup_ms: u64) {
+        self.warmup_ms.store(warmup_ms, Ordering::SeqCst);
+    }
+
+    // Called whenever clicking stops so the next burst ramps up again.
+    pub fn reset_warmup(&self) {
+        if let Ok(mut warmup_started) = self.warmup_started.lock() {
+            *warmup_started = None;
+        }
+    }
+
+    // Ramps linearly from a quarter of the target rate up to the full rate over warmup_ms.
+    fn warmup_factor(&self) -> f64 {
+        let warmup_ms = self.warmup_ms.load(Ordering::SeqCst);
+        if warmup_ms == 0 {
+            return 1.0;
+        }
+
+        let elapsed = match self.warmup_started.lock() {
+            Ok(mut warmup_started) => warmup_started.get_or_insert_with(Instant::now).elapsed(),
+            Err(_) => return 1.0,
+        };
+
+        let progress = (elapsed.as_secs_f64() * 1000.0 / warmup_ms as f64).min(1.0);
+        WARMUP_START_FACTOR + (1.0 - WARMUP_START_FACTOR) * progress
+    }
+
+    pub fn click_count(&self) -> usize {
+        self.session_stats.clicks()
+    }
+
+    pub fn session_stats(&self) -> SessionStatsSnapshot {
+        self.session_stats.snapshot()
+    }
+
+    pub fn reset_session_stats(&self) {
+        self.session_stats.reset();
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
     }
 
     pub fn force_right_cps(&self, cps: u8) {
+        let cps = Self::clamp_cps(cps);
         self.right_max_cps.store(cps, Ordering::SeqCst);
         log_info(&format!("Right click CPS forced to: {}", cps), "ClickExecutor::force_right_cps");
     }
-}
\ No newline at end of file
+}
+
+impl Drop for ClickExecutor {
+    fn drop(&mut self) {
+        self.release_hold();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::click_strategy::ClickStrategy;
+    use crate::input::delay_provider::DelayProvider;
+
+    const GAME_MODES: [GameMode; 4] = [GameMode::Combo, GameMode::Default, GameMode::Butterfly, GameMode::Jitter];
+
+    // Accepts every click without a window behind it.
+    struct AcceptingSink;
+
+    impl ClickStrategy for AcceptingSink {
+        fn name(&self) -> &'static str {
+            "Accepting"
+        }
+
+        fn press(&self, _hwnd: HWND, _button: MouseButton, _point: ClickPoint) -> bool {
+            true
+        }
+
+        fn release(&self, _hwnd: HWND, _button: MouseButton, _point: ClickPoint) -> bool {
+            true
+        }
+    }
+
+    impl ClickSink for AcceptingSink {
+        fn strategy(&self, _kind: ClickStrategyKind) -> &dyn ClickStrategy {
+            self
+        }
+    }
+
+    // Rejects every click, like a game that silently drops posted messages.
+    struct RejectingStrategy;
+
+    impl ClickStrategy for RejectingStrategy {
+        fn name(&self) -> &'static str {
+            "Rejecting"
+        }
+
+        fn press(&self, _hwnd: HWND, _button: MouseButton, _point: ClickPoint) -> bool {
+            false
+        }
+
+        fn release(&self, _hwnd: HWND, _button: MouseButton, _point: ClickPoint) -> bool {
+            false
+        }
+    }
+
+    // PostMessage clicks are dropped and SendInput clicks land.
+    struct PostMessageDroppingSink;
+
+    impl ClickSink for PostMessageDroppingSink {
+        fn strategy(&self, kind: ClickStrategyKind) -> &dyn ClickStrategy {
+            match kind {
+                ClickStrategyKind::PostMessage => &RejectingStrategy,
+                ClickStrategyKind::SendInput => &AcceptingSink,
+            }
+        }
+    }
+
+    // Accepts every click and records the button of each release.
+    struct RecordingSink {
+        releases: Arc<Mutex<Vec<MouseButton>>>,
+    }
+
+    impl ClickStrategy for RecordingSink {
+        fn name(&self) -> &'static str {
+            "Recording"
+        }
+
+        fn press(&self, _hwnd: HWND, _button: MouseButton, _point: ClickPoint) -> bool {
+            true
+        }
+
+        fn release(&self, _hwnd: HWND, button: MouseButton, _point: ClickPoint) -> bool {
+            self.releases.lock().unwrap().push(button);
+            true
+        }
+    }
+
+    impl ClickSink for RecordingSink {
+        fn strategy(&self, _kind: ClickStrategyKind) -> &dyn ClickStrategy {
+            self
+        }
+    }
+
+    // Records every release and drops the first, like a button-up lost between press and release.
+    struct FirstUpDroppingSink {
+        releases: Arc<Mutex<Vec<MouseButton>>>,
+    }
+
+    impl ClickStrategy for FirstUpDroppingSink {
+        fn name(&self) -> &'static str {
+            "FirstUpDropping"
+        }
+
+        fn press(&self, _hwnd: HWND, _button: MouseButton, _point: ClickPoint) -> bool {
+            true
+        }
+
+        fn release(&self, _hwnd: HWND, button: MouseButton, _point: ClickPoint) -> bool {
+            let mut releases = self.releases.lock().unwrap();
+            releases.push(button);
+            releases.len() > 1
+        }
+    }
+
+    impl ClickSink for FirstUpDroppingSink {
+        fn strategy(&self, _kind: ClickStrategyKind) -> &dyn ClickStrategy {
+            self
+        }
+    }
+
+    // Accepts every click and counts the presses and releases sent.
+    struct CountingSink {
+        sent: Arc<AtomicUsize>,
+    }
+
+    impl ClickStrategy for CountingSink {
+        fn name(&self) -> &'static str {
+            "Counting"
+        }
+
+        fn press(&self, _hwnd: HWND, _button: MouseButton, _point: ClickPoint) -> bool {
+            self.sent.fetch_add(1, Ordering::SeqCst);
+            true
+        }
+
+        fn release(&self, _hwnd: HWND, _button: MouseButton, _point: ClickPoint) -> bool {
+            self.sent.fetch_add(1, Ordering::SeqCst);
+            true
+        }
+    }
+
+    impl ClickSink for CountingSink {
+        fn strategy(&self, _kind: ClickStrategyKind) -> &dyn ClickStrategy {
+            self
+        }
+    }
+
+    struct NoSleep;
+
+    impl Sleeper for NoSleep {
+        fn sleep(&self, _duration: Duration) {}
+    }
+
+    fn test_hwnd() -> HWND {
+        std::ptr::NonNull::dangling().as_ptr()
+    }
+
+    fn executor(sink: impl ClickSink + 'static) -> ClickExecutor {
+        let executor = ClickExecutor::with_io(Box::new(sink), Box::new(NoSleep));
+        executor.set_click_region(ClickRegion::Origin);
+        executor
+    }
+
+    #[test]
+    fn click_loop_delays_respect_the_floor_for_every_cadence() {
+        let settings = Settings {
+            min_click_delay_micros: 0,
+            random_deviation_min: -limits::MAX_RANDOM_DEVIATION_MICROS,
+            random_deviation_max: -limits::MAX_RANDOM_DEVIATION_MICROS / 2,
+            ..Settings::default()
+        };
+        let floor = Duration::from_micros(limits::MIN_CLICK_DELAY_FLOOR_MICROS);
+
+        let executor = executor(AcceptingSink);
+        executor.set_click_delays(0, 0);
+        for button in [MouseButton::Left, MouseButton::Right] {
+            executor.set_mouse_button(button);
+            for timing_mode in [TimingMode::Cps, TimingMode::Interval] {
+                executor.set_timing_mode(timing_mode);
+                for max_cps in [0, limits::MIN_CPS, limits::MAX_CPS, u8::MAX] {
+                    executor.set_max_cps(max_cps);
+                    for double_click in [false, true] {
+                        executor.set_double_click(double_click, u8::MAX);
+                        for game_mode in GAME_MODES {
+                            executor.set_game_mode(game_mode);
+                            assert!(executor.target_cps().is_finite() && executor.target_cps() > 0.0);
+
+                            // The same pairing click_loop uses: executor cadence, then the provider's deviation and floor.
+                            let mut delay_provider = DelayProvider::from_settings(&settings, Some(3));
+                            for _ in 0..16 {
+                                let delay = delay_provider.get_next_delay(executor.next_click_delay());
+                                assert!(delay >= floor, "{:?} below {:?} ({:?}, {:?}, {} CPS, double click {}, {:?})",
+                                        delay, floor, button, timing_mode, max_cps, double_click, game_mode);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn release_hold_releases_the_button_that_was_held() {
+        let releases = Arc::new(Mutex::new(Vec::new()));
+        let executor = executor(RecordingSink { releases: Arc::clone(&releases) });
+        let hwnd = test_hwnd();
+
+        executor.set_mouse_button(MouseButton::Left);
+        assert!(executor.hold_button(hwnd));
+        executor.set_mouse_button(MouseButton::Right);
+        executor.release_hold();
+        executor.release_hold();
+
+        assert_eq!(*releases.lock().unwrap(), vec![MouseButton::Left]);
+    }
+
+    #[test]
+    fn a_failed_hold_press_is_retried_and_uses_the_configured_strategy() {
+        let executor = executor(PostMessageDroppingSink);
+        executor.set_click_strategy(ClickStrategyKind::PostMessage, Some(ClickStrategyKind::SendInput), 2);
+
+        // Nothing is held after a dropped press, so each call tries again until the fallback takes over.
+        assert!(!executor.hold_button(test_hwnd()));
+        assert!(!executor.hold_button(test_hwnd()));
+        assert_eq!(executor.current_strategy(), ClickStrategyKind::SendInput);
+        assert!(executor.hold_button(test_hwnd()));
+        assert!(executor.hold_button(test_hwnd()));
+    }
+
+    #[test]
+    fn repeated_primary_failures_switch_to_the_fallback_strategy() {
+        let executor = executor(PostMessageDroppingSink);
+        executor.set_mixed_input_ratio(0.0);
+        executor.set_click_strategy(ClickStrategyKind::PostMessage, Some(ClickStrategyKind::SendInput), 3);
+
+        for _ in 0..2 {
+            assert!(!executor.execute_click(test_hwnd()));
+            assert_eq!(executor.current_strategy(), ClickStrategyKind::PostMessage);
+        }
+
+        assert!(!executor.execute_click(test_hwnd()));
+        assert_eq!(executor.current_strategy(), ClickStrategyKind::SendInput);
+        assert!(executor.execute_click(test_hwnd()));
+
+        // Reconfiguring starts over on the primary.
+        executor.set_click_strategy(ClickStrategyKind::PostMessage, Some(ClickStrategyKind::SendInput), 3);
+        assert_eq!(executor.current_strategy(), ClickStrategyKind::PostMessage);
+    }
+
+    #[test]
+    fn failures_without_a_fallback_stay_on_the_primary_strategy() {
+        let executor = executor(PostMessageDroppingSink);
+        executor.set_mixed_input_ratio(0.0);
+        executor.set_click_strategy(ClickStrategyKind::PostMessage, None, 1);
+
+        for _ in 0..5 {
+            assert!(!executor.execute_click(test_hwnd()));
+        }
+        assert_eq!(executor.current_strategy(), ClickStrategyKind::PostMessage);
+    }
+
+    #[test]
+    fn set_active_round_trips_through_is_active() {
+        let executor = executor(AcceptingSink);
+        assert!(executor.is_active());
+
+        executor.set_active(false);
+        assert!(!executor.is_active());
+        assert!(!executor.execute_click(test_hwnd()));
+
+        executor.set_active(true);
+        assert!(executor.is_active());
+        assert!(executor.execute_click(test_hwnd()));
+    }
+
+    #[test]
+    fn disabling_after_a_dropped_up_sends_a_balancing_up() {
+        let releases = Arc::new(Mutex::new(Vec::new()));
+        let executor = executor(FirstUpDroppingSink { releases: Arc::clone(&releases) });
+        executor.set_mixed_input_ratio(0.0);
+        executor.set_mouse_button(MouseButton::Right);
+
+        assert!(!executor.execute_click(test_hwnd()));
+        executor.set_active(false);
+        executor.set_active(false);
+
+        assert_eq!(*releases.lock().unwrap(), vec![MouseButton::Right, MouseButton::Right]);
+    }
+
+    #[test]
+    fn disabling_after_a_complete_click_sends_nothing_more() {
+        let releases = Arc::new(Mutex::new(Vec::new()));
+        let executor = executor(RecordingSink { releases: Arc::clone(&releases) });
+
+        assert!(executor.execute_click(test_hwnd()));
+        executor.set_active(false);
+
+        assert_eq!(releases.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn max_cps_is_limited_to_one_through_fifty() {
+        assert!(!ClickExecutor::is_valid_cps(0));
+        assert!(ClickExecutor::is_valid_cps(1));
+        assert!(ClickExecutor::is_valid_cps(50));
+        assert!(!ClickExecutor::is_valid_cps(51));
+
+        let executor = executor(AcceptingSink);
+        for button in [MouseButton::Left, MouseButton::Right] {
+            executor.set_mouse_button(button);
+            for (requested, applied) in [(0, 1), (1, 1), (50, 50), (51, 50)] {
+                executor.set_max_cps(requested);
+                assert_eq!(executor.get_current_max_cps(), applied, "{:?} at {} CPS", button, requested);
+            }
+        }
+    }
+
+    #[test]
+    fn combo_mode_jitters_the_cps_delay() {
+        let executor = executor(AcceptingSink);
+        executor.set_max_cps(10);
+        executor.set_game_mode(GameMode::Combo);
+
+        let delays: Vec<Duration> = (0..64).map(|_| executor.next_click_delay()).collect();
+        let (low, high) = (Duration::from_micros(100_000), Duration::from_micros(100_500));
+        assert!(delays.iter().all(|delay| (low..=high).contains(delay)), "{:?}", delays);
+        assert!(delays.iter().any(|delay| *delay != delays[0]), "every Combo delay was {:?}", delays[0]);
+    }
+
+    #[test]
+    fn default_mode_keeps_a_constant_cps_delay() {
+        let executor = executor(AcceptingSink);
+        executor.set_max_cps(10);
+        executor.set_game_mode(GameMode::Default);
+
+        for _ in 0..64 {
+            assert_eq!(executor.next_click_delay(), Duration::from_micros(100_000));
+        }
+    }
+
+    #[test]
+    fn an_inactive_executor_sends_nothing() {
+        let sent = Arc::new(AtomicUsize::new(0));
+        let executor = executor(CountingSink { sent: Arc::clone(&sent) });
+
+        executor.set_active(false);
+        for _ in 0..5 {
+            assert!(!executor.execute_click(test_hwnd()));
+        }
+        assert_eq!(sent.load(Ordering::SeqCst), 0);
+
+        executor.set_active(true);
+        assert!(executor.execute_click(test_hwnd()));
+        assert_eq!(sent.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::config::settings::Settings;
+use crate::input::backend::backend;
+use crate::logger::logger::{log_info, log_warn};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Fewer samples than this can't represent a distribution, so the synthetic generator is used instead.
+pub const MIN_CLICK_PROFILE_SAMPLES: usize = 30;
+// Gaps longer than this are pauses between bursts, not click cadence.
+const MAX_RECORDED_GAP_MICROS: u64 = 1_000_000;
+const RECORD_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ClickProfile {
+    samples: Vec<u64>,
+}
+
+impl ClickProfile {
+    // Polls the real button and stores press-to-press intervals until the duration elapses.
+    pub fn record(virtual_key: i32, duration: Duration) -> Self {
+        let started = Instant::now();
+        let mut samples = Vec::new();
+        let mut was_pressed = false;
+        let mut last_press: Option<Instant> = None;
+
+        while started.elapsed() < duration {
+            let is_pressed = backend().is_key_pressed(virtual_key);
+            if is_pressed && !was_pressed {
+                let now = Instant::now();
+                if let Some(last) = last_press {
+                    let gap = now.duration_since(last).as_micros() as u64;
+                    if gap <= MAX_RECORDED_GAP_MICROS {
+                        samples.push(gap);
+                    }
+                }
+                last_press = Some(now);
+            }
+            was_pressed = is_pressed;
+            thread::sleep(RECORD_POLL_INTERVAL);
+        }
+
+        Self { samples }
+    }
+
+    pub fn sample_count(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_usable(&self) -> bool {
+        self.samples.len() >= MIN_CLICK_PROFILE_SAMPLES
+    }
+
+    pub fn mean_micros(&self) -> u64 {
+        if self.samples.is_empty() {
+            return 0;
+        }
+        self.samples.iter().sum::<u64>() / self.samples.len() as u64
+    }
+
+    pub fn sample(&self, rng: &mut impl Rng) -> Duration {
+        Duration::from_micros(self.samples[rng.random_range(0..self.samples.len())])
+    }
+
+    pub fn save(&self, name: &str) -> io::Result<()> {
+        let path = Settings::get_click_profile_path(name)?;
+        let json = serde_json::to_string(self)?;
+        std::fs::write(&path, json)?;
+        log_info(&format!("Saved click profile '{}' with {} samples", name, self.samples.len()), "ClickProfile::save");
+        Ok(())
+    }
+
+    pub fn load(name: &str) -> io::Result<Self> {
+        let path = Settings::get_click_profile_path(name)?;
+        let contents = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    // An empty name means no profile; missing or undersized profiles fall back to the synthetic generator.
+    pub fn load_usable(name: &str) -> Option<Self> {
+        let context = "ClickProfile::load_usable";
+        if name.trim().is_empty() {
+            return None;
+        }
+
+        match Self::load(name) {
+            Ok(profile) if profile.is_usable() => Some(profile),
+            Ok(profile) => {
+                log_warn(&format!("Click profile '{}' has only {} samples (need {}), using synthetic delays",
+                                  name, profile.sample_count(), MIN_CLICK_PROFILE_SAMPLES), context);
+                None
+            }
+            Err(e) => {
+                log_warn(&format!("Failed to load click profile '{}': {}, using synthetic delays", name, e), context);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use crate::config::settings::Settings;
+use crate::input::backend::{backend, HWND};
+use rand::Rng;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ClickPoint {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl ClickPoint {
+    pub const ORIGIN: ClickPoint = ClickPoint { x: 0, y: 0 };
+
+    // Mouse messages carry client coordinates as X in the low word and Y in the high word.
+    pub fn lparam(&self) -> isize {
+        (((self.y as u32 & 0xFFFF) << 16) | (self.x as u32 & 0xFFFF)) as isize
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClickRegion {
+    // Always (0,0), the behaviour before click regions existed.
+    Origin,
+    ClientArea,
+    Centered { width: i32, height: i32 },
+    Custom { x: i32, y: i32, width: i32, height: i32 },
+}
+
+impl ClickRegion {
+    pub fn from_settings(settings: &Settings) -> Self {
+        match settings.click_region_mode.as_str() {
+            "Origin" => ClickRegion::Origin,
+            "Centered" => ClickRegion::Centered {
+                width: settings.click_region_width,
+                height: settings.click_region_height,
+            },
+            "Custom" => ClickRegion::Custom {
+                x: settings.click_region_x,
+                y: settings.click_region_y,
+                width: settings.click_region_width,
+                height: settings.click_region_height,
+            },
+            _ => ClickRegion::ClientArea,
+        }
+    }
+
+    fn client_size(hwnd: HWND) -> Option<(i32, i32)> {
+        let size = backend().client_size(hwnd)?;
+        if size.0 <= 0 || size.1 <= 0 {
+            return None;
+        }
+        Some(size)
+    }
+
+    // Picks a random point in the region, clamped to the client rect. Falls back to (0,0) when the
+    // client rect is unavailable (minimized or destroyed window).
+    pub fn pick(&self, hwnd: HWND, rng: &mut impl Rng) -> ClickPoint {
+        if *self == ClickRegion::Origin {
+            return ClickPoint::ORIGIN;
+        }
+
+        match Self::client_size(hwnd) {
+            Some(client_size) => self.pick_within(client_size, rng),
+            None => ClickPoint::ORIGIN,
+        }
+    }
+
+    fn pick_within(&self, (client_width, client_height): (i32, i32), rng: &mut impl Rng) -> ClickPoint {
+        let (left, top, width, height) = match *self {
+            ClickRegion::Origin | ClickRegion::ClientArea => (0, 0, client_width, client_height),
+            ClickRegion::Centered { width, height } => {
+                let width = width.clamp(1, client_width);
+                let height = height.clamp(1, client_height);
+                ((client_width - width) / 2, (client_height - height) / 2, width, height)
+            }
+            ClickRegion::Custom { x, y, width, height } => (x, y, width.max(1), height.max(1)),
+        };
+
+        let left = left.clamp(0, client_width - 1);
+        let top = top.clamp(0, client_height - 1);
+        let right = left.saturating_add(width).clamp(left + 1, client_width);
+        let bottom = top.saturating_add(height).clamp(top + 1, client_height);
+
+        ClickPoint {
+            x: rng.random_range(left..right),
+            y: rng.random_range(top..bottom),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn a_huge_custom_region_stays_inside_the_client_rect() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let region = ClickRegion::Custom { x: 100, y: 50, width: i32::MAX, height: i32::MAX };
+
+        for _ in 0..64 {
+            let point = region.pick_within((800, 600), &mut rng);
+            assert!((100..800).contains(&point.x) && (50..600).contains(&point.y), "{:?}", point);
+        }
+    }
+
+    #[test]
+    fn a_custom_region_off_the_client_rect_is_pulled_inside() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let region = ClickRegion::Custom { x: i32::MAX, y: -5, width: 10, height: 10 };
+
+        let point = region.pick_within((800, 600), &mut rng);
+        assert_eq!(point.x, 799);
+        assert!((0..10).contains(&point.y), "{:?}", point);
+    }
+}
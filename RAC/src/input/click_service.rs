@@ -1,22 +1,37 @@
-use crate::input::click_executor::{ClickExecutor, MouseButton, GameMode};
-use crate::input::delay_provider::DelayProvider;
+use crate::input::backend::{backend, HWND};
+use crate::input::backpressure::Backpressure;
+use crate::input::click_executor::{ClickExecutor, MouseButton, GameMode, TimingMode};
+use crate::input::click_profile::ClickProfile;
+use crate::input::click_region::ClickRegion;
+use crate::input::click_strategy::ClickStrategyKind;
+use crate::input::toggle_state::{KeyStateSource, SystemKeyState, ToggleInput, ToggleMode, ToggleState, ToggleTransition};
+use crate::input::delay_provider::{BurstPattern, DelayDistribution, DelayProvider};
 use crate::input::handle::Handle;
+use crate::input::key_spammer::KeySpammer;
 use crate::input::sync_controller::SyncController;
 use crate::input::thread_controller::ThreadController;
-use crate::input::window_finder::WindowFinder;
-use crate::logger::logger::{log_error, log_info};
+use crate::input::window_finder::{ProcessMatchMode, TargetMode, TargetWindow, WindowFinder, WindowSelectionPolicy};
+use crate::logger::logger::{log_error, log_info, log_warn, set_log_level, set_log_rotation};
 use crate::config::settings::Settings;
+use crate::config::settings_watcher::SettingsWatcher;
 use std::sync::{Arc, Mutex};
-use std::thread;
+use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
-use std::sync::atomic::{AtomicBool, Ordering};
-use winapi::um::winuser::GetAsyncKeyState;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, Ordering};
+use rand::Rng;
 
 pub struct ClickServiceConfig {
     pub target_process: String,
     pub window_check_active_interval: Duration,
     pub window_check_idle_interval: Duration,
     pub adaptive_cpu_mode: bool,
+    pub target_hwnd: Option<usize>,
+    pub target_mode: TargetMode,
+    pub process_match_mode: ProcessMatchMode,
+    pub window_selection_policy: WindowSelectionPolicy,
+    pub window_title_filter: String,
+    pub child_window_class: String,
+    pub require_window_visibility: bool,
 }
 
 impl Default for ClickServiceConfig {
@@ -28,10 +43,39 @@ impl Default for ClickServiceConfig {
             window_check_active_interval: Duration::from_secs(1),
             window_check_idle_interval: Duration::from_secs(3),
             adaptive_cpu_mode: settings.adaptive_cpu_mode,
+            target_hwnd: if settings.target_hwnd == 0 { None } else { Some(settings.target_hwnd as usize) },
+            target_mode: TargetMode::from_setting(&settings.target_mode),
+            process_match_mode: ProcessMatchMode::from_setting(&settings.process_match_mode),
+            window_selection_policy: WindowSelectionPolicy::from_setting(&settings.window_selection_policy),
+            window_title_filter: settings.window_title_filter,
+            child_window_class: settings.child_window_class,
+            require_window_visibility: settings.require_window_visibility,
         }
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct ClickServiceSnapshot {
+    pub enabled: bool,
+    pub left_active: bool,
+    pub right_active: bool,
+    pub left_max_cps: u8,
+    pub right_max_cps: u8,
+    pub window_found: bool,
+}
+
+const SETTINGS_FALLBACK_POLL: Duration = Duration::from_secs(60);
+const SETTINGS_DEBOUNCE: Duration = Duration::from_millis(150);
+const SETTINGS_DEBOUNCE_MAX: Duration = Duration::from_secs(2);
+// A click loop beats at least every 50ms, or announces a longer click delay before sleeping through it,
+// so a heartbeat this far overdue means the thread is gone or wedged.
+const CLICK_THREAD_STALE_AFTER: Duration = Duration::from_secs(5);
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(1);
+// Every service thread sleeps in slices of at most a quarter second, so this only runs out on a wedged one.
+const SHUTDOWN_JOIN_TIMEOUT: Duration = Duration::from_secs(5);
+// Anti-AFK clicks land within +/-20% of the configured interval
+const ANTIAFK_JITTER: f64 = 0.2;
+
 pub struct ClickService {
     sync_controller: Arc<SyncController>,
     pub(crate) delay_provider: Arc<Mutex<DelayProvider>>,
@@ -41,8 +85,29 @@ pub struct ClickService {
     config: ClickServiceConfig,
     settings: Arc<Mutex<Settings>>,
     window_finder_running: Arc<AtomicBool>,
-    left_click_enabled: Arc<AtomicBool>,
-    right_click_enabled: Arc<AtomicBool>,
+    foreground_target: AtomicBool,
+    shutdown: Arc<AtomicBool>,
+    thread_handles: Mutex<Vec<JoinHandle<()>>>,
+    settings_file_existed: AtomicBool,
+    window_lost_notice: AtomicBool,
+    pause_when_console_focused: AtomicBool,
+    only_when_foreground: AtomicBool,
+    left_toggle_key: AtomicI32,
+    right_toggle_key: AtomicI32,
+    keyboard_hold_mode: AtomicBool,
+    coupled_button_hold: AtomicBool,
+    antiafk_enabled: AtomicBool,
+    antiafk_interval_secs: AtomicU64,
+    log_every_click: AtomicBool,
+    backpressure_aggressiveness: Mutex<f64>,
+    epoch: Instant,
+    left_heartbeat_millis: AtomicU64,
+    right_heartbeat_millis: AtomicU64,
+    left_worker_generation: AtomicU64,
+    right_worker_generation: AtomicU64,
+    left_click_thread: Mutex<Option<JoinHandle<()>>>,
+    right_click_thread: Mutex<Option<JoinHandle<()>>>,
+    affinity_core: Option<i32>,
     left_click_controller: Arc<SyncController>,
     right_click_controller: Arc<SyncController>,
     left_delay_provider: Arc<Mutex<DelayProvider>>,
@@ -51,6 +116,7 @@ pub struct ClickService {
     right_thread_controller: Arc<ThreadController>,
     pub(crate) left_click_executor: Arc<ClickExecutor>,
     pub(crate) right_click_executor: Arc<ClickExecutor>,
+    key_spammer: KeySpammer,
 }
 
 impl ClickService {
@@ -60,46 +126,70 @@ impl ClickService {
         let settings_clone = settings.clone();
         let adaptive_cpu_mode = config.adaptive_cpu_mode;
 
+        set_log_level(&settings.log_level);
+        log_info(&format!("Using the {} input backend", backend().name()), context);
+        log_info(&format!("Window visibility requirement: {}", if config.require_window_visibility { "visible windows only" } else { "any window" }), context);
+        set_log_rotation(settings.log_max_size_mb, settings.log_backup_count);
+        ThreadController::begin_timer_resolution();
+
         let left_thread_controller = Arc::new(ThreadController::new(adaptive_cpu_mode));
         let right_thread_controller = Arc::new(ThreadController::new(adaptive_cpu_mode));
+        left_thread_controller.set_spin_threshold_micros(settings.spin_threshold_micros);
+        right_thread_controller.set_spin_threshold_micros(settings.spin_threshold_micros);
 
         let service = Arc::new(Self {
             sync_controller: Arc::new(SyncController::new()),
-            delay_provider: Arc::new(Mutex::new(DelayProvider::new())),
+            delay_provider: Arc::new(Mutex::new(DelayProvider::new(None))),
             hwnd: Arc::new(Mutex::new(Handle::new())),
-            window_finder: Arc::new(WindowFinder::new(&config.target_process)),
+            window_finder: Arc::new(WindowFinder::new(&config.target_process, config.process_match_mode, config.window_selection_policy, &config.window_title_filter, &config.child_window_class, config.require_window_visibility)),
             click_executor: Arc::new(ClickExecutor::new((*left_thread_controller).clone())),
+            foreground_target: AtomicBool::new(config.target_mode == TargetMode::Foreground),
             config,
             settings: Arc::new(Mutex::new(settings)),
             window_finder_running: Arc::new(AtomicBool::new(true)),
-            left_click_enabled: Arc::new(AtomicBool::new(false)),
-            right_click_enabled: Arc::new(AtomicBool::new(false)),
+            shutdown: Arc::new(AtomicBool::new(false)),
+            thread_handles: Mutex::new(Vec::new()),
+            settings_file_existed: AtomicBool::new(Settings::file_exists()),
+            window_lost_notice: AtomicBool::new(false),
+            pause_when_console_focused: AtomicBool::new(settings_clone.pause_when_console_focused),
+            only_when_foreground: AtomicBool::new(settings_clone.only_when_foreground),
+            left_toggle_key: AtomicI32::new(settings_clone.effective_left_toggle_key()),
+            right_toggle_key: AtomicI32::new(settings_clone.effective_right_toggle_key()),
+            keyboard_hold_mode: AtomicBool::new(settings_clone.keyboard_hold_mode),
+            coupled_button_hold: AtomicBool::new(settings_clone.couples_button_hold()),
+            antiafk_enabled: AtomicBool::new(settings_clone.antiafk_enabled),
+            antiafk_interval_secs: AtomicU64::new(settings_clone.antiafk_interval_secs.max(1)),
+            log_every_click: AtomicBool::new(settings_clone.log_every_click),
+            backpressure_aggressiveness: Mutex::new(settings_clone.backpressure_aggressiveness),
+            epoch: Instant::now(),
+            left_heartbeat_millis: AtomicU64::new(0),
+            right_heartbeat_millis: AtomicU64::new(0),
+            left_worker_generation: AtomicU64::new(0),
+            right_worker_generation: AtomicU64::new(0),
+            left_click_thread: Mutex::new(None),
+            right_click_thread: Mutex::new(None),
+            affinity_core: if adaptive_cpu_mode { None } else { Some(settings_clone.click_thread_core) },
             left_click_controller: Arc::new(SyncController::new()),
             right_click_controller: Arc::new(SyncController::new()),
-            left_delay_provider: Arc::new(Mutex::new(DelayProvider::new())),
-            right_delay_provider: Arc::new(Mutex::new(DelayProvider::new())),
+            left_delay_provider: Arc::new(Mutex::new(DelayProvider::for_button(&settings_clone, MouseButton::Left))),
+            right_delay_provider: Arc::new(Mutex::new(DelayProvider::for_button(&settings_clone, MouseButton::Right))),
             left_thread_controller: left_thread_controller.clone(),
             right_thread_controller: right_thread_controller.clone(),
             left_click_executor: Arc::new(ClickExecutor::new((*left_thread_controller).clone())),
             right_click_executor: Arc::new(ClickExecutor::new((*right_thread_controller).clone())),
+            key_spammer: KeySpammer::new(&settings_clone),
         });
 
         let left_click_executor = Arc::clone(&service.left_click_executor);
         left_click_executor.set_max_cps(settings_clone.left_max_cps);
         left_click_executor.set_mouse_button(MouseButton::Left);
-        let left_mode = match settings_clone.left_game_mode.as_str() {
-            "Combo" => GameMode::Combo,
-            _ => GameMode::Default,
-        };
+        let left_mode = GameMode::from_setting(&settings_clone.left_game_mode);
         left_click_executor.set_game_mode(left_mode);
 
         let right_click_executor = Arc::clone(&service.right_click_executor);
         right_click_executor.set_max_cps(settings_clone.right_max_cps);
         right_click_executor.set_mouse_button(MouseButton::Right);
-        let right_mode = match settings_clone.right_game_mode.as_str() {
-            "Combo" => GameMode::Combo,
-            _ => GameMode::Default,
-        };
+        let right_mode = GameMode::from_setting(&settings_clone.right_game_mode);
         right_click_executor.set_game_mode(right_mode);
 
         let service_clone = service.clone();
@@ -108,7 +198,8 @@ impl ClickService {
             .spawn(move || {
                 service_clone.window_finder_loop();
             }) {
-            Ok(_) => {
+            Ok(handle) => {
+                service.thread_handles.lock().unwrap().push(handle);
                 log_info("Window finder thread spawned successfully", context);
             }
             Err(e) => {
@@ -122,7 +213,8 @@ impl ClickService {
             .spawn(move || {
                 service_clone.settings_sync_loop();
             }) {
-            Ok(_) => {
+            Ok(handle) => {
+                service.thread_handles.lock().unwrap().push(handle);
                 log_info("Settings synchronization thread spawned successfully", context);
             }
             Err(e) => {
@@ -136,6 +228,36 @@ impl ClickService {
         let service_clone = service.clone();
         spawn_click_thread("RightClickThread", service_clone.clone(), MouseButton::Right);
 
+        let service_clone = service.clone();
+        match thread::Builder::new()
+            .name("KeySpamThread".to_string())
+            .spawn(move || {
+                service_clone.key_spam_loop();
+            }) {
+            Ok(handle) => {
+                service.thread_handles.lock().unwrap().push(handle);
+                log_info("Key spam thread spawned successfully", context);
+            }
+            Err(e) => {
+                log_error(&format!("Failed to spawn key spam thread: {}", e), context);
+            }
+        }
+
+        let service_clone = service.clone();
+        match thread::Builder::new()
+            .name("WatchdogThread".to_string())
+            .spawn(move || {
+                service_clone.watchdog_loop();
+            }) {
+            Ok(handle) => {
+                service.thread_handles.lock().unwrap().push(handle);
+                log_info("Watchdog thread spawned successfully", context);
+            }
+            Err(e) => {
+                log_error(&format!("Failed to spawn watchdog thread: {}", e), context);
+            }
+        }
+
         service
     }
 
@@ -145,16 +267,47 @@ impl ClickService {
 
         self.left_thread_controller.set_idle_priority();
 
-        while !thread::panicking() && self.window_finder_running.load(Ordering::SeqCst) {
+        let mut direct_hwnd_valid = true;
+        let mut window_was_found = false;
+
+        while !thread::panicking() && !self.is_shutting_down() && self.window_finder_running.load(Ordering::SeqCst) {
             let check_interval = if self.is_enabled() {
                 self.config.window_check_active_interval
             } else {
                 self.config.window_check_idle_interval
             };
 
-            self.window_finder.find_target_window(&self.hwnd);
+            // Foreground mode resolves the window per click, so there is nothing to find or lose here.
+            if self.foreground_target.load(Ordering::SeqCst) {
+                window_was_found = false;
+                self.sleep_unless_shutdown(check_interval);
+                continue;
+            }
+
+            let mut window_found = false;
+
+            if let Some(target_hwnd) = self.config.target_hwnd {
+                let hwnd = target_hwnd as HWND;
+                if backend().is_window(hwnd) {
+                    if !direct_hwnd_valid {
+                        log_info(&format!("Direct target window {:?} is valid again", hwnd), context);
+                    }
+                    direct_hwnd_valid = true;
+                    self.hwnd.lock().unwrap().set(hwnd);
+                    window_found = true;
+                } else if direct_hwnd_valid {
+                    log_error(&format!("Direct target window {:?} is not a valid window, falling back to process search", hwnd), context);
+                    direct_hwnd_valid = false;
+                }
+            }
+
+            if !window_found {
+                window_found = self.window_finder.find_target_window(&self.hwnd).is_some();
+            }
+
+            window_was_found = self.track_window_presence(window_was_found, window_found);
 
-            thread::sleep(check_interval);
+            self.sleep_unless_shutdown(check_interval);
         }
 
         log_info("Window finder thread terminated", context);
@@ -166,115 +319,456 @@ impl ClickService {
 
         self.left_thread_controller.set_idle_priority();
 
-        while !thread::panicking() {
-            self.check_and_update_settings();
+        let watcher = Settings::get_data_dir().ok().and_then(|dir| SettingsWatcher::new(&dir));
+        if watcher.is_none() {
+            log_warn(&format!("Settings watcher unavailable, polling every {}s instead", SETTINGS_FALLBACK_POLL.as_secs()), context);
+        }
+
+        let mut last_signature = Settings::file_signature();
+        while !thread::panicking() && !self.is_shutting_down() {
+            let poll_due = match &watcher {
+                Some(watcher) => !self.wait_for_settings_change(watcher),
+                None => {
+                    self.sleep_unless_shutdown(SETTINGS_FALLBACK_POLL);
+                    true
+                }
+            };
+            if self.is_shutting_down() {
+                break;
+            }
 
-            thread::sleep(Duration::from_secs(5));
+            let signature = Settings::file_signature();
+            if poll_due || signature != last_signature {
+                last_signature = signature;
+                self.check_and_update_settings();
+            }
         }
 
-        log_error("Settings sync loop terminated due to thread panic", context);
+        if self.is_shutting_down() {
+            log_info("Settings synchronization thread stopped", context);
+        } else {
+            log_error("Settings sync loop terminated due to thread panic", context);
+        }
     }
 
-    fn check_and_update_settings(&self) {
-        let context = "ClickService::check_and_update_settings";
+    // Returns true on a change (after writes settle), false when the safety poll interval ran out.
+    fn wait_for_settings_change(&self, watcher: &SettingsWatcher) -> bool {
+        let deadline = Instant::now() + SETTINGS_FALLBACK_POLL;
+        while !self.is_shutting_down() && Instant::now() < deadline {
+            if watcher.wait(Duration::from_millis(250)) {
+                let debounce_deadline = Instant::now() + SETTINGS_DEBOUNCE_MAX;
+                while watcher.wait(SETTINGS_DEBOUNCE) && Instant::now() < debounce_deadline {}
+                return true;
+            }
+        }
+        false
+    }
 
-        match Settings::load() {
-            Ok(new_settings) => {
-                let target_process;
-                let target_process_new = new_settings.target_process.clone();
-                let adaptive_cpu_mode;
-                let click_delay_micros;
-                let delay_range_min;
-                let delay_range_max;
-                let random_deviation_min;
-                let random_deviation_max;
-                
-                {
-                    let current_settings = self.settings.lock().unwrap();
-                    target_process = current_settings.target_process.clone();
-                    adaptive_cpu_mode = current_settings.adaptive_cpu_mode;
-                    click_delay_micros = current_settings.click_delay_micros;
-                    delay_range_min = current_settings.delay_range_min;
-                    delay_range_max = current_settings.delay_range_max;
-                    random_deviation_min = current_settings.random_deviation_min;
-                    random_deviation_max = current_settings.random_deviation_max;
-                }
+    fn heartbeat(&self, button: MouseButton) -> &AtomicU64 {
+        match button {
+            MouseButton::Left | MouseButton::Middle | MouseButton::X1 | MouseButton::X2 => &self.left_heartbeat_millis,
+            MouseButton::Right => &self.right_heartbeat_millis,
+        }
+    }
 
-                let target_process_changed = target_process != target_process_new;
-                let adaptive_cpu_mode_changed = adaptive_cpu_mode != new_settings.adaptive_cpu_mode;
-                let click_delay_changed = click_delay_micros != new_settings.click_delay_micros;
-                let delay_range_changed = 
-                    delay_range_min != new_settings.delay_range_min || 
-                    delay_range_max != new_settings.delay_range_max;
-                let deviation_changed = 
-                    random_deviation_min != new_settings.random_deviation_min || 
-                    random_deviation_max != new_settings.random_deviation_max;
-
-                {
-                    let mut current_settings = self.settings.lock().unwrap();
-                    *current_settings = new_settings;
-                }
+    fn worker_generation(&self, button: MouseButton) -> &AtomicU64 {
+        match button {
+            MouseButton::Left | MouseButton::Middle | MouseButton::X1 | MouseButton::X2 => &self.left_worker_generation,
+            MouseButton::Right => &self.right_worker_generation,
+        }
+    }
+
+    fn click_thread(&self, button: MouseButton) -> &Mutex<Option<JoinHandle<()>>> {
+        match button {
+            MouseButton::Left | MouseButton::Middle | MouseButton::X1 | MouseButton::X2 => &self.left_click_thread,
+            MouseButton::Right => &self.right_click_thread,
+        }
+    }
+
+    // The heartbeat holds the time the thread is next due to beat, so quiet_for covers a sleep it is about to start.
+    fn beat(&self, button: MouseButton, quiet_for: Duration) {
+        let due = self.epoch.elapsed() + quiet_for;
+        self.heartbeat(button).store(due.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    // Respawns a click thread whose heartbeat went stale while its button is enabled. The new worker
+    // runs against this same service, so settings, CPS and delay providers carry over unchanged. Bumping
+    // the generation retires the old worker if it was only stuck in a long sleep, so two never click at once.
+    fn watchdog_loop(self: &Arc<Self>) {
+        let context = "ClickService::watchdog_loop";
+
+        while !self.is_shutting_down() {
+            self.sleep_unless_shutdown(WATCHDOG_INTERVAL);
+            if self.is_shutting_down() {
+                break;
+            }
 
-                if target_process_changed {
-                    log_info(&format!("Target process updated to: {}", target_process_new), context);
-                    let _ = self.window_finder.update_target_process(&target_process_new);
+            for (name, button, controller) in [
+                ("LeftClickThread", MouseButton::Left, &self.left_click_controller),
+                ("RightClickThread", MouseButton::Right, &self.right_click_controller),
+            ] {
+                if !controller.is_enabled() {
+                    continue;
                 }
-                
-                if adaptive_cpu_mode_changed {
-                    log_info(&format!("Adaptive CPU mode updated to: {}", if adaptive_cpu_mode { "disabled" } else { "enabled" }), context);
-                    self.left_thread_controller.set_adaptive_mode(!adaptive_cpu_mode);
+
+                let now = self.epoch.elapsed().as_millis() as u64;
+                let last_beat = self.heartbeat(button).load(Ordering::Relaxed);
+                if now.saturating_sub(last_beat) < CLICK_THREAD_STALE_AFTER.as_millis() as u64 {
+                    continue;
                 }
-                
-                if click_delay_changed || delay_range_changed || deviation_changed {
-                    log_info("Click timing parameters updated", context);
-
-                    if delay_range_changed || deviation_changed {
-                        if let Ok(mut delay_provider) = self.delay_provider.lock() {
-                            delay_provider.update_settings(
-                                delay_range_min,
-                                delay_range_max,
-                                random_deviation_min,
-                                random_deviation_max
-                            );
-                        }
-                    }
 
-                    if click_delay_changed {
-                        self.click_executor.update_delay(click_delay_micros);
-                    }
+                log_warn(&format!("{} heartbeat is {}ms old, respawning it", name, now.saturating_sub(last_beat)), context);
+                self.heartbeat(button).store(now, Ordering::Relaxed);
+                self.worker_generation(button).fetch_add(1, Ordering::SeqCst);
+                spawn_click_thread(name, Arc::clone(self), button);
+            }
+        }
+    }
+
+    fn is_shutting_down(&self) -> bool {
+        self.shutdown.load(Ordering::SeqCst)
+    }
+
+    // Sleeps in short slices so shutdown() never waits out a full interval.
+    fn sleep_unless_shutdown(&self, duration: Duration) {
+        let deadline = Instant::now() + duration;
+        while !self.is_shutting_down() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            thread::sleep(remaining.min(Duration::from_millis(100)));
+        }
+    }
+
+    fn check_and_update_settings(&self) {
+        let context = "ClickService::check_and_update_settings";
+
+        let file_exists = Settings::file_exists();
+        let file_existed = self.settings_file_existed.swap(file_exists, Ordering::SeqCst);
+
+        if file_existed && !file_exists {
+            let current_settings = self.settings.lock().unwrap().clone();
+            if current_settings.recreate_deleted_settings {
+                log_warn("Settings file was deleted externally, keeping in-memory settings and recreating it", context);
+                match current_settings.save() {
+                    Ok(()) => self.settings_file_existed.store(true, Ordering::SeqCst),
+                    Err(e) => log_error(&format!("Failed to recreate settings file: {}", e), context),
                 }
-            },
+                return;
+            }
+            log_warn("Settings file was deleted externally, reverting to defaults", context);
+        }
+
+        let new_settings = match Settings::load() {
+            Ok(new_settings) => new_settings,
             Err(e) => {
                 log_error(&format!("Failed to reload settings: {}", e), context);
+                return;
+            }
+        };
+        let old_settings = self.settings.lock().unwrap().clone();
+
+        // Key spam draws its deviation and floor from the global delay settings, so those rebuild it too.
+        if changed(&old_settings, &new_settings, |s| (s.key_spam_enabled, s.key_spam_key, s.key_spam_toggle_key, s.key_spam_cps, s.key_spam_hold_mode))
+            || changed(&old_settings, &new_settings, |s| (s.delay_range_min, s.delay_range_max, s.random_deviation_min, s.random_deviation_max))
+            || changed(&old_settings, &new_settings, |s| s.effective_min_click_delay_micros()) {
+            self.key_spammer.configure(&new_settings);
+        }
+
+        *self.settings.lock().unwrap() = new_settings.clone();
+
+        self.apply_window_settings(&old_settings, &new_settings);
+        self.apply_gate_settings(&old_settings, &new_settings);
+        self.apply_executor_settings(&old_settings, &new_settings);
+        self.apply_thread_settings(&old_settings, &new_settings);
+        self.apply_delay_settings(&old_settings, &new_settings);
+        self.apply_log_settings(&old_settings, &new_settings);
+    }
+
+    fn apply_window_settings(&self, old: &Settings, new: &Settings) {
+        let context = "ClickService::apply_window_settings";
+
+        if changed(old, new, |s| s.target_mode.clone()) {
+            let target_mode = TargetMode::from_setting(&new.target_mode);
+            log_info(&format!("Target mode updated to: {}", target_mode.as_setting()), context);
+            self.foreground_target.store(target_mode == TargetMode::Foreground, Ordering::SeqCst);
+        }
+
+        if changed(old, new, |s| s.process_match_mode.clone()) {
+            self.window_finder.update_match_mode(ProcessMatchMode::from_setting(&new.process_match_mode));
+        }
+
+        if changed(old, new, |s| s.window_selection_policy.clone()) {
+            self.window_finder.update_selection_policy(WindowSelectionPolicy::from_setting(&new.window_selection_policy));
+        }
+
+        if changed(old, new, |s| s.window_title_filter.clone()) {
+            self.window_finder.update_title_filter(&new.window_title_filter);
+        }
+
+        if changed(old, new, |s| s.child_window_class.clone()) {
+            self.window_finder.update_child_class(&new.child_window_class);
+        }
+
+        if changed(old, new, |s| s.require_window_visibility) {
+            self.window_finder.set_require_visibility(new.require_window_visibility);
+        }
+
+        if changed(old, new, |s| Settings::parse_target_processes(&s.target_process)) {
+            log_info(&format!("Target process updated to: {}", new.target_process), context);
+            let _ = self.window_finder.update_target_process(&new.target_process);
+        }
+    }
+
+    // Settings that decide whether a click loop clicks at all, read by the loops on every iteration.
+    fn apply_gate_settings(&self, old: &Settings, new: &Settings) {
+        let context = "ClickService::apply_gate_settings";
+
+        if changed(old, new, |s| s.pause_when_console_focused) {
+            log_info(&format!("Pause when console focused set to: {}", new.pause_when_console_focused), context);
+            self.pause_when_console_focused.store(new.pause_when_console_focused, Ordering::SeqCst);
+        }
+
+        if changed(old, new, |s| s.only_when_foreground) {
+            log_info(&format!("Only click when target is foreground set to: {}", new.only_when_foreground), context);
+            self.only_when_foreground.store(new.only_when_foreground, Ordering::SeqCst);
+        }
+
+        if changed(old, new, |s| (s.effective_left_toggle_key(), s.effective_right_toggle_key(), s.keyboard_hold_mode)) {
+            let (left_key, right_key) = (new.effective_left_toggle_key(), new.effective_right_toggle_key());
+            log_info(&format!("Hold gate updated: left key={}, right key={}, keyboard hold={}", left_key, right_key, new.keyboard_hold_mode), context);
+            self.set_hold_gate(left_key, right_key, new.keyboard_hold_mode);
+        }
+
+        if changed(old, new, |s| s.couples_button_hold()) {
+            log_info(&format!("Coupled button hold set to: {}", new.couples_button_hold()), context);
+            self.set_coupled_button_hold(new.couples_button_hold());
+        }
+
+        if changed(old, new, |s| s.backpressure_aggressiveness) {
+            log_info(&format!("Backpressure aggressiveness set to: {:.2}", new.backpressure_aggressiveness), context);
+            if let Ok(mut aggressiveness) = self.backpressure_aggressiveness.lock() {
+                *aggressiveness = new.backpressure_aggressiveness;
             }
         }
+
+        if changed(old, new, |s| (s.antiafk_enabled, s.antiafk_interval_secs)) {
+            log_info(&format!("Anti-AFK updated to: {} (every {}s)", if new.antiafk_enabled { "enabled" } else { "disabled" }, new.antiafk_interval_secs), context);
+            self.set_antiafk(new.antiafk_enabled, new.antiafk_interval_secs);
+        }
     }
 
-    pub fn click_loop(&self, button: MouseButton) {
+    fn apply_executor_settings(&self, old: &Settings, new: &Settings) {
+        let context = "ClickService::apply_executor_settings";
+        let executors = [&self.left_click_executor, &self.right_click_executor, &self.click_executor];
+
+        if changed(old, new, |s| s.dispatch_latency_diagnostics) {
+            log_info(&format!("Dispatch latency diagnostics set to: {}", new.dispatch_latency_diagnostics), context);
+            for executor in executors {
+                executor.set_latency_diagnostics(new.dispatch_latency_diagnostics);
+            }
+        }
+
+        if changed(old, new, |s| (s.click_strategy.clone(), s.fallback_click_strategy.clone(), s.fallback_failure_threshold)) {
+            let primary = ClickStrategyKind::from_setting(&new.click_strategy).unwrap_or(ClickStrategyKind::PostMessage);
+            let fallback = ClickStrategyKind::from_setting(&new.fallback_click_strategy);
+            let threshold = new.fallback_failure_threshold;
+            log_info(&format!("Click strategy set to: {:?}, fallback: {:?} after {} failures", primary, fallback, threshold), context);
+            for executor in executors {
+                executor.set_click_strategy(primary, fallback, threshold);
+            }
+        }
+
+        if changed(old, new, |s| s.effective_mixed_input_ratio()) {
+            let ratio = new.effective_mixed_input_ratio();
+            log_info(&format!("Mixed input ratio updated to: {:.2}", ratio), context);
+            for executor in executors {
+                executor.set_mixed_input_ratio(ratio);
+            }
+        }
+
+        if changed(old, new, |s| (s.cps_drift_enabled, s.cps_drift_band, s.cps_drift_period_secs)) {
+            log_info(&format!("CPS drift updated: enabled={}, band={}, period={}s", new.cps_drift_enabled, new.cps_drift_band, new.cps_drift_period_secs), context);
+            for executor in executors {
+                executor.set_cps_drift(new.cps_drift_enabled, new.cps_drift_band, new.cps_drift_period_secs);
+            }
+        }
+
+        if changed(old, new, |s| (s.double_click, s.double_click_max_cps)) {
+            log_info(&format!("Double click updated to: {} (cap {} CPS)", if new.double_click { "enabled" } else { "disabled" }, new.double_click_max_cps), context);
+            for executor in executors {
+                executor.set_double_click(new.double_click, new.double_click_max_cps);
+            }
+        }
+
+        if changed(old, new, |s| (s.timing_mode.clone(), s.left_click_delay_micros, s.right_click_delay_micros)) {
+            let timing_mode = TimingMode::from_setting(&new.timing_mode);
+            log_info(&format!("Timing mode updated to: {:?} (left interval {}us, right interval {}us)", timing_mode, new.left_click_delay_micros, new.right_click_delay_micros), context);
+            for executor in executors {
+                executor.set_timing_mode(timing_mode);
+                executor.set_click_delays(new.left_click_delay_micros, new.right_click_delay_micros);
+            }
+        }
+
+        if changed(old, new, |s| s.hold_mode) {
+            log_info(&format!("Hold mode updated to: {}", new.hold_mode), context);
+            for executor in executors {
+                executor.set_hold_mode(new.hold_mode);
+            }
+        }
+
+        if changed(old, new, |s| s.simulate_movement) {
+            log_info(&format!("Simulated mouse movement updated to: {}", if new.simulate_movement { "enabled" } else { "disabled" }), context);
+            for executor in executors {
+                executor.set_simulate_movement(new.simulate_movement);
+            }
+        }
+
+        if changed(old, new, ClickRegion::from_settings) {
+            let click_region = ClickRegion::from_settings(new);
+            log_info(&format!("Click region updated to: {:?}", click_region), context);
+            for executor in executors {
+                executor.set_click_region(click_region);
+            }
+        }
+
+        if changed(old, new, |s| s.warmup_ms) {
+            log_info(&format!("CPS warmup updated to: {}ms", new.warmup_ms), context);
+            for executor in executors {
+                executor.set_warmup_ms(new.warmup_ms);
+            }
+        }
+
+        if changed(old, new, |s| s.click_delay_micros) {
+            log_info("Click timing parameters updated", context);
+            self.click_executor.update_delay(new.click_delay_micros);
+        }
+
+        if changed(old, new, |s| (s.left_click_profile.clone(), s.right_click_profile.clone())) {
+            log_info(&format!("Click profiles updated to: left={}, right={}",
+                              if new.left_click_profile.is_empty() { "none" } else { &new.left_click_profile },
+                              if new.right_click_profile.is_empty() { "none" } else { &new.right_click_profile }), context);
+            self.set_click_profile(MouseButton::Left, ClickProfile::load_usable(&new.left_click_profile));
+            self.set_click_profile(MouseButton::Right, ClickProfile::load_usable(&new.right_click_profile));
+        }
+    }
+
+    fn apply_thread_settings(&self, old: &Settings, new: &Settings) {
+        let context = "ClickService::apply_thread_settings";
+
+        if changed(old, new, |s| s.adaptive_cpu_mode) {
+            log_info(&format!("Adaptive CPU mode updated to: {}", if new.adaptive_cpu_mode { "enabled" } else { "disabled" }), context);
+            self.left_thread_controller.set_adaptive_mode(new.adaptive_cpu_mode);
+        }
+
+        if changed(old, new, |s| s.spin_threshold_micros) {
+            log_info(&format!("Spin threshold updated to: {}us", new.spin_threshold_micros), context);
+            self.left_thread_controller.set_spin_threshold_micros(new.spin_threshold_micros);
+            self.right_thread_controller.set_spin_threshold_micros(new.spin_threshold_micros);
+        }
+    }
+
+    fn apply_delay_settings(&self, old: &Settings, new: &Settings) {
+        let context = "ClickService::apply_delay_settings";
+
+        if changed(old, new, |s| (s.effective_min_click_delay_micros(), s.effective_left_min_click_delay_micros(), s.effective_right_min_click_delay_micros())) {
+            let (global, left, right) = (new.effective_min_click_delay_micros(), new.effective_left_min_click_delay_micros(), new.effective_right_min_click_delay_micros());
+            log_info(&format!("Minimum click delay updated to: {}us (left {}us, right {}us)", global, left, right), context);
+            for (delay_provider, min_delay) in [(&self.delay_provider, global), (&self.left_delay_provider, left), (&self.right_delay_provider, right)] {
+                if let Ok(mut delay_provider) = delay_provider.lock() {
+                    delay_provider.set_min_delay_micros(min_delay);
+                }
+            }
+        }
+
+        if changed(old, new, |s| (s.delay_range_min, s.delay_range_max, s.random_deviation_min, s.random_deviation_max)) {
+            log_info(&format!("Random deviation updated to: {}..{}us", new.random_deviation_min, new.random_deviation_max), context);
+            if let Ok(mut delay_provider) = self.delay_provider.lock() {
+                delay_provider.update_settings(new.delay_range_min, new.delay_range_max, new.random_deviation_min, new.random_deviation_max);
+            }
+        }
+
+        if changed(old, new, |s| (s.left_delay_range_min, s.left_delay_range_max, s.left_random_deviation_min, s.left_random_deviation_max)) {
+            log_info(&format!("Left random deviation updated to: {}..{}us", new.left_random_deviation_min, new.left_random_deviation_max), context);
+            if let Ok(mut delay_provider) = self.left_delay_provider.lock() {
+                delay_provider.update_settings(new.left_delay_range_min, new.left_delay_range_max, new.left_random_deviation_min, new.left_random_deviation_max);
+            }
+        }
+
+        if changed(old, new, |s| (s.right_delay_range_min, s.right_delay_range_max, s.right_random_deviation_min, s.right_random_deviation_max)) {
+            log_info(&format!("Right random deviation updated to: {}..{}us", new.right_random_deviation_min, new.right_random_deviation_max), context);
+            if let Ok(mut delay_provider) = self.right_delay_provider.lock() {
+                delay_provider.update_settings(new.right_delay_range_min, new.right_delay_range_max, new.right_random_deviation_min, new.right_random_deviation_max);
+            }
+        }
+
+        if changed(old, new, BurstPattern::from_settings) {
+            let burst_pattern = BurstPattern::from_settings(new);
+            log_info(&format!("Burst pattern updated to: {} clicks, {}% gap, {}% pause",
+                burst_pattern.size, burst_pattern.intra_delay_percent, burst_pattern.pause_percent), context);
+            for provider in [&self.delay_provider, &self.left_delay_provider, &self.right_delay_provider] {
+                if let Ok(mut delay_provider) = provider.lock() {
+                    delay_provider.set_burst_pattern(burst_pattern);
+                }
+            }
+        }
+
+        if changed(old, new, |s| (s.left_delay_distribution.clone(), s.right_delay_distribution.clone())) {
+            log_info(&format!("Delay distribution updated to: left={}, right={}", new.left_delay_distribution, new.right_delay_distribution), context);
+            if let Ok(mut delay_provider) = self.left_delay_provider.lock() {
+                delay_provider.set_distribution(DelayDistribution::from_setting(&new.left_delay_distribution));
+            }
+            if let Ok(mut delay_provider) = self.right_delay_provider.lock() {
+                delay_provider.set_distribution(DelayDistribution::from_setting(&new.right_delay_distribution));
+            }
+        }
+    }
+
+    fn apply_log_settings(&self, old: &Settings, new: &Settings) {
+        let context = "ClickService::apply_log_settings";
+
+        if changed(old, new, |s| (s.log_max_size_mb, s.log_backup_count)) {
+            log_info(&format!("Log rotation updated to: {} MB, {} backups", new.log_max_size_mb, new.log_backup_count), context);
+            set_log_rotation(new.log_max_size_mb, new.log_backup_count);
+        }
+
+        if changed(old, new, |s| s.log_level.clone()) {
+            log_info(&format!("Log level updated to: {}", new.log_level), context);
+            set_log_level(&new.log_level);
+        }
+
+        if changed(old, new, |s| s.log_every_click) {
+            log_info(&format!("Per-click logging set to: {}", new.log_every_click), context);
+            self.log_every_click.store(new.log_every_click, Ordering::Relaxed);
+        }
+    }
+
+    pub fn click_loop(&self, button: MouseButton, generation: u64) {
         let context = match button {
-            MouseButton::Left => "ClickService::left_click_loop",
+            MouseButton::Left | MouseButton::Middle | MouseButton::X1 | MouseButton::X2 => "ClickService::left_click_loop",
             MouseButton::Right => "ClickService::right_click_loop",
         };
 
         log_info(&format!("{} thread started", context), context);
 
         let click_controller = match button {
-            MouseButton::Left => Arc::clone(&self.left_click_controller),
+            MouseButton::Left | MouseButton::Middle | MouseButton::X1 | MouseButton::X2 => Arc::clone(&self.left_click_controller),
             MouseButton::Right => Arc::clone(&self.right_click_controller),
         };
 
         let delay_provider = match button {
-            MouseButton::Left => Arc::clone(&self.left_delay_provider),
+            MouseButton::Left | MouseButton::Middle | MouseButton::X1 | MouseButton::X2 => Arc::clone(&self.left_delay_provider),
             MouseButton::Right => Arc::clone(&self.right_delay_provider),
         };
 
         let thread_controller = match button {
-            MouseButton::Left => Arc::clone(&self.left_thread_controller),
+            MouseButton::Left | MouseButton::Middle | MouseButton::X1 | MouseButton::X2 => Arc::clone(&self.left_thread_controller),
             MouseButton::Right => Arc::clone(&self.right_thread_controller),
         };
 
         let click_executor = match button {
-            MouseButton::Left => Arc::clone(&self.left_click_executor),
+            MouseButton::Left | MouseButton::Middle | MouseButton::X1 | MouseButton::X2 => Arc::clone(&self.left_click_executor),
             MouseButton::Right => Arc::clone(&self.right_click_executor),
         };
 
@@ -283,77 +777,311 @@ impl ClickService {
 
         let mut consecutive_failures = 0;
         let mut last_click = Instant::now();
+        let mut first_success_logged = false;
+        let mut next_antiafk_click: Option<Instant> = None;
+        let mut last_foreground: HWND = std::ptr::null_mut();
+        let mut held_for_background = false;
 
         let settings = Settings::load().unwrap_or_default();
+        let mut backpressure = Backpressure::new(settings.backpressure_aggressiveness);
         match button {
-            MouseButton::Left => {
+            MouseButton::Left | MouseButton::Middle | MouseButton::X1 | MouseButton::X2 => {
                 click_executor.set_max_cps(settings.left_max_cps);
-                let mode = match settings.left_game_mode.as_str() {
-                    "Combo" => GameMode::Combo,
-                    _ => GameMode::Default,
-                };
+                let mode = GameMode::from_setting(&settings.left_game_mode);
                 click_executor.set_game_mode(mode);
             },
             MouseButton::Right => {
                 click_executor.set_max_cps(settings.right_max_cps);
-                let mode = match settings.right_game_mode.as_str() {
-                    "Combo" => GameMode::Combo,
-                    _ => GameMode::Default,
-                };
+                let mode = GameMode::from_setting(&settings.right_game_mode);
                 click_executor.set_game_mode(mode);
             }
         }
 
-        while !thread::panicking() {
+        while !thread::panicking() && !self.is_shutting_down() {
+            if self.worker_generation(button).load(Ordering::SeqCst) != generation {
+                click_executor.release_hold();
+                log_info(&format!("{} thread replaced by watchdog, exiting", context), context);
+                return;
+            }
+            self.beat(button, Duration::ZERO);
             if !click_controller.wait_for_signal(Duration::from_millis(50)) {
+                click_executor.release_hold();
+                click_executor.reset_warmup();
+                first_success_logged = false;
+                next_antiafk_click = None;
                 continue;
             }
 
-            let is_pressed = match button {
-                MouseButton::Left => {
-                    unsafe { GetAsyncKeyState(0x01) < 0 }
-                },
-                MouseButton::Right => {
-                    unsafe { GetAsyncKeyState(0x02) < 0 }
+            // Anti-AFK clicks on its own schedule, so the hold gate doesn't apply.
+            let antiafk = self.antiafk_enabled.load(Ordering::SeqCst);
+            if !antiafk {
+                next_antiafk_click = None;
+
+                let is_pressed = self.hold_gate_keys(button)
+                    .into_iter()
+                    .any(|key| key != 0 && SystemKeyState.is_down(key));
+
+                if !is_pressed {
+                    click_executor.release_hold();
+                    click_executor.reset_warmup();
+                    continue;
                 }
-            };
+            }
 
-            if !is_pressed {
+            if self.pause_when_console_focused.load(Ordering::SeqCst) && Self::is_console_focused() {
+                click_executor.release_hold();
+                thread_controller.smart_sleep(Duration::from_millis(50));
                 continue;
             }
 
-            let hwnd = {
+            let hwnd = if self.foreground_target.load(Ordering::SeqCst) {
+                let hwnd = backend().foreground_window();
+                if hwnd != last_foreground {
+                    log_info(&format!("Foreground window changed to {:?} ({})", hwnd, backend().window_title(hwnd)), context);
+                    last_foreground = hwnd;
+                }
+                if hwnd.is_null() {
+                    click_executor.release_hold();
+                    thread_controller.smart_sleep(Duration::from_millis(50));
+                    continue;
+                }
+                hwnd
+            } else {
                 let hwnd_guard = self.hwnd.lock().unwrap();
                 hwnd_guard.get()
             };
 
-            if click_executor.execute_click(hwnd) {
+            // SendInput lands on whatever has focus, so only click while the target is the foreground window.
+            if click_executor.current_strategy() == ClickStrategyKind::SendInput && !Self::is_target_foreground(hwnd) {
+                click_executor.release_hold();
+                thread_controller.smart_sleep(Duration::from_millis(50));
+                continue;
+            }
+
+            // Posted clicks reach a background window too; this guard refuses to, say after an alt-tab.
+            // Foreground target mode already clicks only the focused window.
+            if self.only_when_foreground.load(Ordering::SeqCst) && !self.foreground_target.load(Ordering::SeqCst) {
+                let in_foreground = Self::is_target_foreground(hwnd);
+                if in_foreground == held_for_background {
+                    held_for_background = !in_foreground;
+                    log_info(if held_for_background {
+                        "Target window is in the background, holding clicks until it is focused again"
+                    } else {
+                        "Target window is focused again, resuming clicks"
+                    }, context);
+                }
+                if !in_foreground {
+                    click_executor.release_hold();
+                    thread_controller.smart_sleep(Duration::from_millis(50));
+                    continue;
+                }
+            }
+
+            if antiafk {
+                let now = Instant::now();
+                let due = *next_antiafk_click.get_or_insert(now);
+                if now < due {
+                    thread_controller.smart_sleep(due.saturating_duration_since(now).min(Duration::from_millis(100)));
+                    continue;
+                }
+
+                let interval = self.next_antiafk_interval();
+                next_antiafk_click = Some(now + interval);
+                if click_executor.execute_click(hwnd) {
+                    log_info(&format!("Anti-AFK click delivered: button={:?}, next in {:.1}s", button, interval.as_secs_f64()), context);
+                } else {
+                    log_warn(&format!("Anti-AFK click failed: button={:?}", button), context);
+                }
+                continue;
+            }
+
+            if let Ok(aggressiveness) = self.backpressure_aggressiveness.lock() {
+                backpressure.set_aggressiveness(*aggressiveness);
+            }
+
+            // A failed hold press falls through to the same backoff as a failed click.
+            let hold_mode = click_executor.is_hold_mode();
+            if hold_mode && click_executor.hold_button(hwnd) {
+                consecutive_failures = 0;
+                backpressure.record_success();
+                thread_controller.smart_sleep(Duration::from_millis(10));
+                continue;
+            }
+
+            if !hold_mode && click_executor.execute_click(hwnd) {
                 consecutive_failures = 0;
 
+                let was_throttling = backpressure.is_throttling();
+                backpressure.record_success();
+                if was_throttling && !backpressure.is_throttling() {
+                    log_info("Clicks succeeding again, restored full click rate", context);
+                }
+
+                if !first_success_logged || self.log_every_click.load(Ordering::Relaxed) {
+                    log_info(&format!("Click delivered: button={:?}, hwnd={:?}", button, hwnd), context);
+                    first_success_logged = true;
+                }
+
                 let delay = {
                     let mut delay_provider = delay_provider.lock().unwrap();
-                    delay_provider.get_next_delay()
+                    backpressure.scale(delay_provider.get_next_delay(click_executor.next_click_delay()))
                 };
 
                 let elapsed = last_click.elapsed();
                 if elapsed < delay {
-                    thread_controller.smart_sleep(delay.saturating_sub(elapsed));
+                    let remaining = delay.saturating_sub(elapsed);
+                    self.beat(button, remaining);
+                    thread_controller.smart_sleep(remaining);
                 }
                 last_click = Instant::now();
             } else {
                 consecutive_failures += 1;
 
+                let was_throttling = backpressure.is_throttling();
+                backpressure.record_failure();
+                if !was_throttling && backpressure.is_throttling() {
+                    log_warn("Click failures detected, throttling click rate until clicks succeed again", context);
+                }
+
                 if consecutive_failures >= 3 {
-                    log_info("Multiple click failures detected, continuing with next cycle", &context);
+                    log_info(&format!("Multiple click failures detected, continuing with next cycle (delay x{:.2})", backpressure.multiplier()), context);
                     consecutive_failures = 0;
+                    first_success_logged = false;
                 }
 
-                thread_controller.smart_sleep(Duration::from_millis(20));
+                thread_controller.smart_sleep(backpressure.scale(Duration::from_millis(20)));
             }
         }
 
+        click_executor.release_hold();
+        if self.is_shutting_down() {
+            log_info(&format!("{} thread stopped", context), context);
+            return;
+        }
+
         self.window_finder_running.store(false, Ordering::SeqCst);
-        log_error("Click loop terminated due to thread panic", &context);
+        log_error("Click loop terminated due to thread panic", context);
+    }
+
+    // Runs beside the click loops: its own toggle key arms it, and it posts to the same target window.
+    fn key_spam_loop(&self) {
+        let context = "ClickService::key_spam_loop";
+        log_info("Key spam thread started", context);
+
+        let thread_controller = self.key_spammer.thread_controller();
+        let mut toggle_state = ToggleState::new();
+        let mut last_press = Instant::now();
+
+        while !thread::panicking() && !self.is_shutting_down() {
+            if !self.key_spammer.is_configured() {
+                toggle_state = ToggleState::new();
+                self.key_spammer.release_key();
+                self.sleep_unless_shutdown(Duration::from_millis(250));
+                continue;
+            }
+
+            // The session being started stands in for the schedule, so stopping RAC disarms key spam too.
+            let input = ToggleInput {
+                mode: ToggleMode::from_keyboard_hold(self.key_spammer.is_hold_mode()),
+                in_schedule: self.key_spammer.is_active(),
+                reset_window: Duration::ZERO,
+                now: Instant::now(),
+            };
+            match toggle_state.poll(&SystemKeyState, self.key_spammer.toggle_key(), input) {
+                ToggleTransition::Activated => log_info("Key spam armed", context),
+                ToggleTransition::Deactivated | ToggleTransition::ScheduleEnded => log_info("Key spam disarmed", context),
+                _ => {}
+            }
+            if !toggle_state.is_active() {
+                self.key_spammer.release_key();
+                thread_controller.smart_sleep(Duration::from_millis(10));
+                continue;
+            }
+
+            let hwnd = if self.foreground_target.load(Ordering::SeqCst) {
+                backend().foreground_window()
+            } else {
+                self.hwnd.lock().unwrap().get()
+            };
+            if hwnd.is_null() {
+                thread_controller.smart_sleep(Duration::from_millis(50));
+                continue;
+            }
+
+            // Wait in short slices so releasing or toggling the activation key is noticed promptly.
+            let delay = self.key_spammer.next_delay();
+            let elapsed = last_press.elapsed();
+            if elapsed < delay {
+                thread_controller.smart_sleep(delay.saturating_sub(elapsed).min(Duration::from_millis(10)));
+                continue;
+            }
+
+            last_press = Instant::now();
+            if !self.key_spammer.press_once(hwnd) {
+                log_warn(&format!("Key spam press failed for HWND={:?}", hwnd), context);
+                thread_controller.smart_sleep(Duration::from_millis(20));
+            }
+        }
+
+        self.key_spammer.release_key();
+        log_info("Key spam thread stopped", context);
+    }
+
+    pub fn set_hold_gate(&self, left_toggle_key: i32, right_toggle_key: i32, keyboard_hold_mode: bool) {
+        self.left_toggle_key.store(left_toggle_key, Ordering::SeqCst);
+        self.right_toggle_key.store(right_toggle_key, Ordering::SeqCst);
+        self.keyboard_hold_mode.store(keyboard_hold_mode, Ordering::SeqCst);
+    }
+
+    pub fn set_coupled_button_hold(&self, coupled: bool) {
+        self.coupled_button_hold.store(coupled, Ordering::SeqCst);
+    }
+
+    // Keys that keep this executor clicking; 0 marks an unused slot. Each button follows its bound toggle key,
+    // so an X1 or keyboard binding works in either hold mode. Mouse hold in a coupled Both mode takes either key.
+    fn hold_gate_keys(&self, button: MouseButton) -> [i32; 2] {
+        let (left_key, right_key) = (self.left_toggle_key.load(Ordering::SeqCst), self.right_toggle_key.load(Ordering::SeqCst));
+        if !self.keyboard_hold_mode.load(Ordering::SeqCst) && self.coupled_button_hold.load(Ordering::SeqCst) {
+            return [left_key, right_key];
+        }
+
+        match button {
+            MouseButton::Right => [right_key, 0],
+            _ => [left_key, 0],
+        }
+    }
+
+    pub fn set_antiafk(&self, enabled: bool, interval_secs: u64) {
+        self.antiafk_interval_secs.store(interval_secs.max(1), Ordering::SeqCst);
+        self.antiafk_enabled.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn set_click_profile(&self, button: MouseButton, click_profile: Option<ClickProfile>) {
+        let delay_provider = match button {
+            MouseButton::Left | MouseButton::Middle | MouseButton::X1 | MouseButton::X2 => &self.left_delay_provider,
+            MouseButton::Right => &self.right_delay_provider,
+        };
+
+        if let Ok(mut delay_provider) = delay_provider.lock() {
+            delay_provider.set_click_profile(click_profile);
+        }
+    }
+
+    fn next_antiafk_interval(&self) -> Duration {
+        let base = self.antiafk_interval_secs.load(Ordering::SeqCst) as f64;
+        let factor = rand::rng().random_range((1.0 - ANTIAFK_JITTER)..=(1.0 + ANTIAFK_JITTER));
+        Duration::from_secs_f64(base * factor)
+    }
+
+    // A child render target counts as focused when its top-level window is.
+    fn is_target_foreground(hwnd: HWND) -> bool {
+        let foreground = backend().foreground_window();
+        !foreground.is_null() && (foreground == hwnd || foreground == backend().root_window(hwnd))
+    }
+
+    fn is_console_focused() -> bool {
+        let console = backend().console_window();
+        !console.is_null() && backend().foreground_window() == console
     }
 
     pub fn toggle(&self) -> bool {
@@ -383,10 +1111,92 @@ impl ClickService {
         if self.sync_controller.is_enabled() {
             self.sync_controller.toggle();
         }
+        for executor in [&self.left_click_executor, &self.right_click_executor, &self.click_executor] {
+            executor.release_pending_click();
+        }
 
         true
     }
 
+    // Stops clicking when a check misses a window the previous check found, and returns window_found
+    // for the next check.
+    fn track_window_presence(&self, window_was_found: bool, window_found: bool) -> bool {
+        if window_was_found && !window_found {
+            self.stop_for_lost_window();
+        }
+        window_found
+    }
+
+    // Clicking stays off when the window comes back; the user has to start RAC again.
+    fn stop_for_lost_window(&self) {
+        let context = "ClickService::stop_for_lost_window";
+
+        if !self.is_enabled() && !self.left_click_controller.is_enabled() && !self.right_click_controller.is_enabled() {
+            return;
+        }
+
+        log_warn("Target window closed or hidden, stopping clicking", context);
+
+        self.left_click_executor.set_active(false);
+        self.right_click_executor.set_active(false);
+        self.force_disable_clicking();
+        self.force_disable_left_clicking();
+        self.force_disable_right_clicking();
+
+        self.window_lost_notice.store(true, Ordering::SeqCst);
+    }
+
+    // The window a session would click: the direct target HWND while it's valid, otherwise a fresh search.
+    pub fn locate_target_window(&self) -> Option<HWND> {
+        if let Some(target_hwnd) = self.config.target_hwnd {
+            let hwnd = target_hwnd as HWND;
+            if backend().is_window(hwnd) {
+                self.hwnd.lock().unwrap().set(hwnd);
+                return Some(hwnd);
+            }
+        }
+        self.window_finder.find_target_window(&self.hwnd)
+    }
+
+    // Fires `count` clicks at the target outside any session, paced like a session would, on a
+    // throwaway executor so session state and stats are untouched. None when no window was found;
+    // otherwise the window and how many clicks were delivered.
+    pub fn send_test_clicks(&self, button: MouseButton, count: u32) -> Option<(HWND, u32)> {
+        let context = "ClickService::send_test_clicks";
+        let hwnd = self.locate_target_window()?;
+
+        let thread_controller = ThreadController::new(self.config.adaptive_cpu_mode);
+        let executor = ClickExecutor::new(thread_controller.clone());
+        executor.set_mouse_button(button);
+
+        let mut delivered = 0;
+        for index in 0..count {
+            if index > 0 {
+                thread_controller.smart_sleep(executor.next_click_delay());
+            }
+            if executor.execute_click(hwnd) {
+                delivered += 1;
+            }
+        }
+
+        log_info(&format!("Test clicks: {}/{} {:?} clicks delivered to HWND={:?} ({})",
+                          delivered, count, button, hwnd, backend().window_title(hwnd)), context);
+        Some((hwnd, delivered))
+    }
+
+    pub fn list_target_windows(&self) -> Vec<TargetWindow> {
+        self.window_finder.list_target_windows()
+    }
+
+    pub fn pin_target_window(&self, hwnd: HWND) {
+        self.window_finder.pin_window(hwnd);
+        self.hwnd.lock().unwrap().set(hwnd);
+    }
+
+    pub fn take_window_lost_notice(&self) -> bool {
+        self.window_lost_notice.swap(false, Ordering::SeqCst)
+    }
+
     pub fn force_enable_left_clicking(&self) -> bool {
         if self.left_click_controller.is_enabled() {
             return true;
@@ -408,6 +1218,8 @@ impl ClickService {
             return true;
         }
         log_info("Forcing left click to disable state", "ClickService::force_disable_left_clicking");
+        self.left_click_executor.release_hold();
+        self.left_click_executor.release_pending_click();
         self.left_click_controller.toggle()
     }
 
@@ -416,9 +1228,41 @@ impl ClickService {
             return true;
         }
         log_info("Forcing right click to disable state", "ClickService::force_disable_right_clicking");
+        self.right_click_executor.release_hold();
+        self.right_click_executor.release_pending_click();
         self.right_click_controller.toggle()
     }
 
+    pub fn is_left_armed(&self) -> bool {
+        self.left_click_controller.is_enabled() && self.left_click_executor.is_active()
+    }
+
+    pub fn is_right_armed(&self) -> bool {
+        self.right_click_controller.is_enabled() && self.right_click_executor.is_active()
+    }
+
+    // The settings the sync thread last applied, so frequent pollers needn't reread settings.json.
+    pub fn current_settings(&self) -> Settings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    pub fn snapshot(&self) -> ClickServiceSnapshot {
+        let window_found = if self.foreground_target.load(Ordering::SeqCst) {
+            !backend().foreground_window().is_null()
+        } else {
+            !self.hwnd.lock().unwrap().get().is_null()
+        };
+
+        ClickServiceSnapshot {
+            enabled: self.is_enabled(),
+            left_active: self.is_left_armed(),
+            right_active: self.is_right_armed(),
+            left_max_cps: self.left_click_executor.get_current_max_cps(),
+            right_max_cps: self.right_click_executor.get_current_max_cps(),
+            window_found,
+        }
+    }
+
     pub fn get_left_click_executor(&self) -> Arc<ClickExecutor> {
         Arc::clone(&self.left_click_executor)
     }
@@ -441,6 +1285,7 @@ impl ClickService {
 
         self.left_click_executor.set_active(true);
         self.right_click_executor.set_active(true);
+        self.key_spammer.set_active(true);
 
         log_info(
             &format!(
@@ -452,28 +1297,224 @@ impl ClickService {
         );
     }
     
+    pub fn reset_state(&self) {
+        let context = "ClickService::reset_state";
+        log_info("Resetting click state", context);
+
+        self.left_click_executor.set_active(false);
+        self.right_click_executor.set_active(false);
+        self.release_all_holds();
+
+        self.hwnd.lock().unwrap().set(std::ptr::null_mut());
+        if self.window_finder.find_target_window(&self.hwnd).is_none() {
+            log_error("Target window not found after reset", context);
+        }
+    }
+
+    pub fn emergency_stop(&self) {
+        self.key_spammer.set_active(false);
+        self.left_click_executor.set_active(false);
+        self.right_click_executor.set_active(false);
+        self.click_executor.set_active(false);
+        self.force_disable_left_clicking();
+        self.force_disable_right_clicking();
+        self.force_disable_clicking();
+    }
+
+    // Hard stop: clicking stays off until RAC is started again from the menu.
+    pub fn panic_stop(&self) {
+        log_warn("Panic key pressed, stopping all clicking", "ClickService::panic_stop");
+        self.emergency_stop();
+        self.release_all_holds();
+    }
+
+    pub fn release_all_holds(&self) {
+        for executor in [&self.left_click_executor, &self.right_click_executor, &self.click_executor] {
+            executor.release_hold();
+            executor.release_pending_click();
+        }
+        self.key_spammer.release_key();
+    }
+
     pub fn stop(&self) {
         let context = "ClickService::stop";
         log_info("Stopping click service", context);
 
+        self.key_spammer.set_active(false);
         self.left_click_executor.set_active(false);
         self.right_click_executor.set_active(false);
     }
+
+    // Must not be called from one of the service's own threads, since it joins them.
+    pub fn shutdown(&self) {
+        let context = "ClickService::shutdown";
+        if self.shutdown.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        log_info("Shutting down click service", context);
+        self.emergency_stop();
+        self.release_all_holds();
+
+        let mut handles: Vec<JoinHandle<()>> = self.thread_handles.lock().unwrap().drain(..).collect();
+        handles.extend([&self.left_click_thread, &self.right_click_thread].into_iter().filter_map(|thread| thread.lock().unwrap().take()));
+        let deadline = Instant::now() + SHUTDOWN_JOIN_TIMEOUT;
+        while handles.iter().any(|handle| !handle.is_finished()) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        // A thread still running past the deadline is left detached rather than hanging exit.
+        for handle in handles {
+            let name = handle.thread().name().unwrap_or("unnamed").to_string();
+            if !handle.is_finished() {
+                log_warn(&format!("{} did not stop within {}s, leaving it behind", name, SHUTDOWN_JOIN_TIMEOUT.as_secs()), context);
+            } else if handle.join().is_err() {
+                log_error(&format!("{} panicked before shutdown", name), context);
+            }
+        }
+
+        ThreadController::end_timer_resolution();
+
+        log_info("Click service shut down", context);
+    }
+}
+
+// Compares one setting, or a tuple of settings that are applied together, between two snapshots.
+fn changed<T: PartialEq>(old: &Settings, new: &Settings, field: impl Fn(&Settings) -> T) -> bool {
+    field(old) != field(new)
 }
 
 fn spawn_click_thread(name: &str, service: Arc<ClickService>, button: MouseButton) {
     let context = format!("ClickService::{}", name);
     
+    let affinity_core = service.affinity_core;
+    let generation = service.worker_generation(button).load(Ordering::SeqCst);
+    let service_clone = Arc::clone(&service);
     match thread::Builder::new()
         .name(name.to_string())
         .spawn(move || {
-            service.click_loop(button);
+            if let Some(core) = affinity_core {
+                ThreadController::pin_current_thread(core);
+            }
+            service_clone.beat(button, Duration::ZERO);
+            service_clone.click_loop(button, generation);
         }) {
-        Ok(_) => {
+        Ok(handle) => {
+            // A replaced worker that hasn't exited yet stays joinable at shutdown; a finished one is dropped.
+            let previous = service.click_thread(button).lock().unwrap().replace(handle);
+            if let Some(previous) = previous.filter(|previous| !previous.is_finished()) {
+                service.thread_handles.lock().unwrap().push(previous);
+            }
             log_info(&format!("{} spawned successfully", name), &context);
         }
         Err(e) => {
             log_error(&format!("Failed to spawn {}: {}", name, e), &context);
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_reports_a_button_active_only_when_armed_and_started() {
+        let service = ClickService::new(ClickServiceConfig::default());
+        service.stop();
+
+        service.force_enable_left_clicking();
+        let snapshot = service.snapshot();
+        assert!(!snapshot.left_active && !snapshot.right_active);
+
+        service.start();
+        let snapshot = service.snapshot();
+        assert!(snapshot.left_active && !snapshot.right_active);
+        assert_eq!(snapshot.left_active, service.is_left_armed());
+        assert_eq!(snapshot.right_active, service.is_right_armed());
+
+        service.force_disable_left_clicking();
+        service.force_enable_right_clicking();
+        let snapshot = service.snapshot();
+        assert!(!snapshot.left_active && snapshot.right_active);
+
+        service.shutdown();
+    }
+
+    #[test]
+    fn losing_a_found_window_stops_clicking_until_restarted() {
+        let service = ClickService::new(ClickServiceConfig::default());
+        service.start();
+        service.force_enable_left_clicking();
+        service.force_enable_right_clicking();
+
+        // A window that was never found doesn't count as lost.
+        assert!(!service.track_window_presence(false, false));
+        assert!(service.is_left_armed() && service.is_right_armed());
+        assert!(!service.take_window_lost_notice());
+
+        assert!(service.track_window_presence(false, true));
+        assert!(!service.track_window_presence(true, false));
+        assert!(!service.is_left_armed() && !service.is_right_armed());
+        assert!(service.take_window_lost_notice());
+        assert!(!service.take_window_lost_notice());
+
+        service.track_window_presence(false, true);
+        assert!(!service.is_left_armed() && !service.is_right_armed());
+
+        service.shutdown();
+    }
+
+    #[test]
+    fn coupled_hold_gates_both_buttons_on_either_toggle_key() {
+        let service = ClickService::new(ClickServiceConfig::default());
+        let (left, right) = (MouseButton::Left.virtual_key(), MouseButton::Right.virtual_key());
+        service.set_hold_gate(left, right, false);
+
+        service.set_coupled_button_hold(true);
+        assert_eq!(service.hold_gate_keys(MouseButton::Left), [left, right]);
+        assert_eq!(service.hold_gate_keys(MouseButton::Right), [left, right]);
+
+        service.set_coupled_button_hold(false);
+        assert_eq!(service.hold_gate_keys(MouseButton::Left), [left, 0]);
+        assert_eq!(service.hold_gate_keys(MouseButton::Right), [right, 0]);
+
+        // Keyboard hold follows each button's bound key either way.
+        service.set_hold_gate(0x46, 0x47, true);
+        service.set_coupled_button_hold(true);
+        assert_eq!(service.hold_gate_keys(MouseButton::Left), [0x46, 0]);
+        assert_eq!(service.hold_gate_keys(MouseButton::Right), [0x47, 0]);
+
+        service.shutdown();
+    }
+
+    #[test]
+    fn mouse_hold_gates_on_a_bound_side_button() {
+        let service = ClickService::new(ClickServiceConfig::default());
+        let x1 = MouseButton::X1.virtual_key();
+        assert_eq!(x1, 0x05);
+        service.set_hold_gate(x1, x1, false);
+        service.set_coupled_button_hold(false);
+
+        for button in [MouseButton::Left, MouseButton::Right, MouseButton::Middle] {
+            assert_eq!(service.hold_gate_keys(button), [x1, 0], "{:?}", button);
+        }
+
+        service.shutdown();
+    }
+
+    #[test]
+    fn shutdown_stops_every_service_thread_before_the_deadline() {
+        let service = ClickService::new(ClickServiceConfig::default());
+        let workers = service.thread_handles.lock().unwrap().len();
+        assert!(workers >= 2, "only {} background threads were tracked", workers);
+
+        // Shutdown only waits out the full timeout when some thread is still running.
+        let started = Instant::now();
+        service.shutdown();
+        assert!(started.elapsed() < SHUTDOWN_JOIN_TIMEOUT, "shutdown took {:?}", started.elapsed());
+
+        assert!(service.thread_handles.lock().unwrap().is_empty());
+        assert!(service.left_click_thread.lock().unwrap().is_none());
+        assert!(service.right_click_thread.lock().unwrap().is_none());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,100 @@
+use crate::input::backend::{backend, HWND};
+use crate::input::click_executor::MouseButton;
+use crate::input::click_region::ClickPoint;
+use std::panic::RefUnwindSafe;
+
+pub trait ClickStrategy: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn press(&self, hwnd: HWND, button: MouseButton, point: ClickPoint) -> bool;
+    fn release(&self, hwnd: HWND, button: MouseButton, point: ClickPoint) -> bool;
+
+    // The second press of a double-click; real input turns two quick presses into one on its own.
+    fn double_press(&self, hwnd: HWND, button: MouseButton, point: ClickPoint) -> bool {
+        self.press(hwnd, button, point)
+    }
+
+    // Only meaningful for strategies that address a window directly.
+    fn move_to(&self, _hwnd: HWND, _point: ClickPoint) -> bool {
+        true
+    }
+}
+
+pub struct PostMessageClickStrategy;
+
+impl ClickStrategy for PostMessageClickStrategy {
+    fn name(&self) -> &'static str {
+        "PostMessage"
+    }
+
+    fn press(&self, hwnd: HWND, button: MouseButton, point: ClickPoint) -> bool {
+        backend().post_button(hwnd, button, true, point)
+    }
+
+    fn release(&self, hwnd: HWND, button: MouseButton, point: ClickPoint) -> bool {
+        backend().post_button(hwnd, button, false, point)
+    }
+
+    fn double_press(&self, hwnd: HWND, button: MouseButton, point: ClickPoint) -> bool {
+        backend().post_double_click(hwnd, button, point)
+    }
+
+    fn move_to(&self, hwnd: HWND, point: ClickPoint) -> bool {
+        backend().post_move(hwnd, point)
+    }
+}
+
+// SendInput goes to whichever window has focus, so these clicks only land while the game is in the foreground.
+pub struct SendInputClickStrategy;
+
+impl ClickStrategy for SendInputClickStrategy {
+    fn name(&self) -> &'static str {
+        "SendInput"
+    }
+
+    // SendInput clicks wherever the real cursor is, so the point is ignored.
+    fn press(&self, _hwnd: HWND, button: MouseButton, _point: ClickPoint) -> bool {
+        backend().send_button(button, true)
+    }
+
+    fn release(&self, _hwnd: HWND, button: MouseButton, _point: ClickPoint) -> bool {
+        backend().send_button(button, false)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClickStrategyKind {
+    PostMessage,
+    SendInput,
+}
+
+impl ClickStrategyKind {
+    pub fn from_setting(value: &str) -> Option<Self> {
+        match value {
+            "PostMessage" => Some(ClickStrategyKind::PostMessage),
+            "SendInput" => Some(ClickStrategyKind::SendInput),
+            _ => None,
+        }
+    }
+
+    pub fn strategy(&self) -> &'static dyn ClickStrategy {
+        match self {
+            ClickStrategyKind::PostMessage => &PostMessageClickStrategy,
+            ClickStrategyKind::SendInput => &SendInputClickStrategy,
+        }
+    }
+}
+
+// Where an executor's presses and releases end up. Swapping it out lets the click logic run
+// without a real window or input backend behind it.
+pub trait ClickSink: Send + Sync + RefUnwindSafe {
+    fn strategy(&self, kind: ClickStrategyKind) -> &dyn ClickStrategy;
+}
+
+// Delivers clicks through the platform input backend.
+pub struct SystemClickSink;
+
+impl ClickSink for SystemClickSink {
+    fn strategy(&self, kind: ClickStrategyKind) -> &dyn ClickStrategy {
+        kind.strategy()
+    }
+}
@@ -0,0 +1,87 @@
+use rand::Rng;
+use std::time::{Duration, Instant};
+
+pub struct CpsDrift {
+    enabled: bool,
+    band: f64,
+    period: Duration,
+    offset: f64,
+    last_update: Instant,
+}
+
+impl CpsDrift {
+    pub fn new(enabled: bool, band: f64, period_secs: u64) -> Self {
+        let mut drift = Self {
+            enabled: false,
+            band: 0.0,
+            period: Duration::ZERO,
+            offset: 0.0,
+            last_update: Instant::now(),
+        };
+        drift.configure(enabled, band, period_secs);
+        drift
+    }
+
+    pub fn configure(&mut self, enabled: bool, band: f64, period_secs: u64) {
+        self.enabled = enabled;
+        self.band = if band.is_finite() { band.abs() } else { 0.0 };
+        self.period = Duration::from_secs(period_secs.max(1));
+        self.offset = self.offset.clamp(-self.band, self.band);
+    }
+
+    pub fn apply(&mut self, max_cps: u8) -> f64 {
+        if !self.enabled || self.band <= 0.0 || max_cps == 0 {
+            return max_cps as f64;
+        }
+
+        if self.last_update.elapsed() >= self.period {
+            self.step(&mut rand::rng());
+            self.last_update = Instant::now();
+        }
+
+        (max_cps as f64 + self.offset).max(1.0)
+    }
+
+    // Mean-reverting walk: the pull back toward zero keeps the long-run average at max_cps.
+    fn step(&mut self, rng: &mut impl Rng) {
+        let step = rng.random_range(-self.band..=self.band) * 0.5;
+        self.offset = (self.offset * 0.5 + step).clamp(-self.band, self.band);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn drift_stays_in_band_and_averages_to_max_cps() {
+        let max_cps = 15;
+        let band = 3.0;
+        let mut drift = CpsDrift::new(true, band, 1);
+        let mut rng = StdRng::seed_from_u64(11);
+
+        let steps = 20_000;
+        let mut total = 0.0;
+        for _ in 0..steps {
+            drift.step(&mut rng);
+            let cps = drift.apply(max_cps);
+            assert!((cps - max_cps as f64).abs() <= band, "{} outside {} +/- {}", cps, max_cps, band);
+            total += cps;
+        }
+
+        let mean = total / steps as f64;
+        assert!((mean - max_cps as f64).abs() < 0.1, "mean {} drifted away from {}", mean, max_cps);
+    }
+
+    #[test]
+    fn disabled_drift_returns_max_cps() {
+        let mut drift = CpsDrift::new(false, 3.0, 1);
+        let mut rng = StdRng::seed_from_u64(11);
+        for _ in 0..100 {
+            drift.step(&mut rng);
+            assert_eq!(drift.apply(15), 15.0);
+        }
+    }
+}
@@ -1,34 +1,179 @@
 use crate::logger::logger::{log_error, log_info};
+use crate::config::constants::limits;
 use crate::config::settings::Settings;
-use rand::Rng;
+use crate::input::click_executor::MouseButton;
+use crate::input::click_profile::ClickProfile;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::time::Duration;
 
+pub(crate) fn enforce_min_delay(delay: Duration, min_delay_micros: u64) -> Duration {
+    let floor = Duration::from_micros(min_delay_micros.max(limits::MIN_CLICK_DELAY_FLOOR_MICROS));
+    if delay < floor {
+        return floor;
+    }
+    delay
+}
+
+const DELAY_BUFFER_SIZE: usize = 512;
+const MICRO_ADJUST_MICROS: f64 = 50.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DelayDistribution {
+    Uniform,
+    Gaussian,
+}
+
+impl DelayDistribution {
+    pub fn from_setting(value: &str) -> Self {
+        match value {
+            "Gaussian" => DelayDistribution::Gaussian,
+            _ => DelayDistribution::Uniform,
+        }
+    }
+
+    // Uniform spans mean ± 2σ, so both distributions cover the same configured range.
+    fn sample(&self, rng: &mut impl Rng, mean: f64, std_dev: f64) -> f64 {
+        if std_dev <= 0.0 {
+            return mean;
+        }
+
+        match self {
+            DelayDistribution::Uniform => rng.random_range((mean - 2.0 * std_dev)..=(mean + 2.0 * std_dev)),
+            DelayDistribution::Gaussian => {
+                // Box-Muller transform; 1 - u keeps the log argument away from zero.
+                let u1: f64 = 1.0 - rng.random::<f64>();
+                let u2: f64 = rng.random();
+                let z = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+                mean + z * std_dev
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BurstPattern {
+    pub size: u32,
+    pub intra_delay_percent: u32,
+    pub pause_percent: u32,
+}
+
+impl BurstPattern {
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            size: settings.burst_size.max(1),
+            intra_delay_percent: settings.burst_intra_delay_percent,
+            pause_percent: settings.burst_pause_percent,
+        }
+    }
+
+    // Percentages of the base delay, so the pattern follows Max CPS instead of fixing absolute gaps.
+    fn factor(&self, position: u32) -> f64 {
+        let percent = if position + 1 < self.size { self.intra_delay_percent } else { self.pause_percent };
+        percent as f64 / 100.0
+    }
+}
+
 pub struct DelayProvider {
-    delay_buffer: Vec<Duration>,
+    deviation_buffer: Vec<i64>,
     current_index: usize,
     delay_range_min: f64,
     delay_range_max: f64,
     random_deviation_min: i32,
     random_deviation_max: i32,
     pub(crate) burst_mode: bool,
-    burst_counter: u8,
+    burst_pattern: BurstPattern,
+    burst_counter: u32,
+    min_delay_micros: u64,
+    distribution: DelayDistribution,
+    click_profile: Option<ClickProfile>,
+    rng: StdRng,
 }
 
 impl DelayProvider {
-    pub fn new() -> Self {
-        let context = "DelayProvider::new";
-
+    // Pass a seed to get a reproducible delay sequence; None seeds from OS entropy.
+    pub fn new(seed: Option<u64>) -> Self {
         let settings = Settings::load().unwrap_or_else(|_| Settings::default());
+        Self::from_settings(&settings, seed)
+    }
+
+    pub fn from_settings(settings: &Settings, seed: Option<u64>) -> Self {
+        Self::build(settings.delay_range_min,
+                    settings.delay_range_max,
+                    settings.random_deviation_min,
+                    settings.random_deviation_max,
+                    settings.burst_mode,
+                    BurstPattern::from_settings(settings),
+                    DelayDistribution::from_setting(&settings.left_delay_distribution),
+                    settings.effective_min_click_delay_micros(),
+                    seed)
+    }
+
+    pub fn for_button(settings: &Settings, button: MouseButton) -> Self {
+        match button {
+            MouseButton::Left | MouseButton::Middle | MouseButton::X1 | MouseButton::X2 => {
+                let mut provider = Self::build(settings.left_delay_range_min,
+                                               settings.left_delay_range_max,
+                                               settings.left_random_deviation_min,
+                                               settings.left_random_deviation_max,
+                                               settings.left_burst_mode,
+                                               BurstPattern::from_settings(settings),
+                                               DelayDistribution::from_setting(&settings.left_delay_distribution),
+                                               settings.effective_left_min_click_delay_micros(),
+                                               None);
+                provider.set_click_profile(ClickProfile::load_usable(&settings.left_click_profile));
+                provider
+            },
+            MouseButton::Right => {
+                let mut provider = Self::build(settings.right_delay_range_min,
+                                               settings.right_delay_range_max,
+                                               settings.right_random_deviation_min,
+                                               settings.right_random_deviation_max,
+                                               settings.right_burst_mode,
+                                               BurstPattern::from_settings(settings),
+                                               DelayDistribution::from_setting(&settings.right_delay_distribution),
+                                               settings.effective_right_min_click_delay_micros(),
+                                               None);
+                provider.set_click_profile(ClickProfile::load_usable(&settings.right_click_profile));
+                provider
+            },
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn build(delay_range_min: f64,
+             delay_range_max: f64,
+             random_deviation_min: i32,
+             random_deviation_max: i32,
+             burst_mode: bool,
+             burst_pattern: BurstPattern,
+             distribution: DelayDistribution,
+             min_delay_micros: u64,
+             seed: Option<u64>) -> Self {
+        let context = "DelayProvider::build";
+
+        let rng = match seed {
+            Some(seed) => {
+                log_info(&format!("Using fixed RNG seed {}", seed), context);
+                StdRng::seed_from_u64(seed)
+            }
+            None => StdRng::from_os_rng(),
+        };
 
         let mut provider = Self {
-            delay_buffer: vec![Duration::ZERO; 512],
+            deviation_buffer: vec![0; DELAY_BUFFER_SIZE],
             current_index: 0,
-            delay_range_min: settings.delay_range_min,
-            delay_range_max: settings.delay_range_max,
-            random_deviation_min: settings.random_deviation_min,
-            random_deviation_max: settings.random_deviation_max,
-            burst_mode: settings.burst_mode,
+            delay_range_min,
+            delay_range_max,
+            random_deviation_min,
+            random_deviation_max,
+            burst_mode,
+            burst_pattern,
             burst_counter: 0,
+            min_delay_micros,
+            distribution,
+            click_profile: None,
+            rng,
         };
 
         match provider.initialize_delay_buffer() {
@@ -49,6 +194,33 @@ impl DelayProvider {
         self.burst_mode
     }
 
+    pub fn set_burst_pattern(&mut self, burst_pattern: BurstPattern) {
+        if self.burst_pattern != burst_pattern {
+            self.burst_pattern = burst_pattern;
+            self.burst_counter = 0;
+        }
+    }
+
+    pub fn set_min_delay_micros(&mut self, min_delay_micros: u64) {
+        self.min_delay_micros = min_delay_micros.max(limits::MIN_CLICK_DELAY_FLOOR_MICROS);
+    }
+
+    pub fn set_distribution(&mut self, distribution: DelayDistribution) {
+        if self.distribution == distribution {
+            return;
+        }
+
+        self.distribution = distribution;
+        if let Err(e) = self.initialize_delay_buffer() {
+            log_error(&format!("Failed to reinitialize delay buffer: {}", e), "DelayProvider::set_distribution");
+        }
+    }
+
+    // A recorded profile replaces the synthetic delays entirely, including Max CPS and burst pacing.
+    pub fn set_click_profile(&mut self, click_profile: Option<ClickProfile>) {
+        self.click_profile = click_profile.filter(|profile| profile.is_usable());
+    }
+
     pub fn update_settings(&mut self,
                            delay_range_min: f64,
                            delay_range_max: f64,
@@ -79,39 +251,282 @@ impl DelayProvider {
     }
 
     fn initialize_delay_buffer(&mut self) -> Result<(), String> {
-        let mut rng = rand::rng();
-        for delay in self.delay_buffer.iter_mut() {
-            let ms = rng.random_range(2.0..=5.0);
-            *delay = Duration::from_micros((ms * 1000.0) as u64);
+        let (min, max) = if self.random_deviation_min <= self.random_deviation_max {
+            (self.random_deviation_min, self.random_deviation_max)
+        } else {
+            (self.random_deviation_max, self.random_deviation_min)
+        };
+
+        let mean = (min as f64 + max as f64) / 2.0;
+        let std_dev = (max as f64 - min as f64) / 4.0;
+
+        for deviation in self.deviation_buffer.iter_mut() {
+            let sample = self.distribution.sample(&mut self.rng, mean, std_dev);
+            *deviation = sample.round() as i64;
         }
         Ok(())
     }
 
-    pub fn get_next_delay(&mut self) -> Duration {
-        let mut rng = rand::rng();
-
-        if self.burst_mode && self.burst_counter < 1 {
-            self.burst_counter += 1;
-            return Duration::from_micros(rng.random_range(3000..4000));
-        } else if self.burst_mode {
-            self.burst_counter = 0;
+    pub fn get_next_delay(&mut self, base_delay: Duration) -> Duration {
+        if let Some(profile) = &self.click_profile {
+            let recorded = profile.sample(&mut self.rng);
+            let micro_adjust = self.distribution.sample(&mut self.rng, 0.0, MICRO_ADJUST_MICROS / 2.0).round() as i64;
+            let final_delay = if micro_adjust < 0 {
+                recorded.saturating_sub(Duration::from_micros(-micro_adjust as u64))
+            } else {
+                recorded.saturating_add(Duration::from_micros(micro_adjust as u64))
+            };
+            return enforce_min_delay(final_delay, self.min_delay_micros);
         }
 
-        let base_delay = self.delay_buffer[self.current_index];
-        self.current_index = (self.current_index + 1) & 511;
+        // A burst is size - 1 short gaps followed by one pause; the defaults (2, 75%, 125%) pair a short
+        // gap with a long one so the average still matches base_delay.
+        let base_delay = if self.burst_mode {
+            let factor = self.burst_pattern.factor(self.burst_counter);
+            self.burst_counter = (self.burst_counter + 1) % self.burst_pattern.size;
+            base_delay.mul_f64(factor)
+        } else {
+            base_delay
+        };
+
+        let deviation = self.deviation_buffer[self.current_index];
+        self.current_index = (self.current_index + 1) % DELAY_BUFFER_SIZE;
 
-        let micro_adjust: i32 = rng.random_range(-50..50);
+        let micro_adjust = self.distribution.sample(&mut self.rng, 0.0, MICRO_ADJUST_MICROS / 2.0).round() as i64;
+        let adjust = deviation + micro_adjust;
 
-        let final_delay = if micro_adjust < 0 {
-            base_delay.saturating_sub(Duration::from_micros(-micro_adjust as u64))
+        let final_delay = if adjust < 0 {
+            base_delay.saturating_sub(Duration::from_micros(-adjust as u64))
         } else {
-            base_delay.saturating_add(Duration::from_micros(micro_adjust as u64))
+            base_delay.saturating_add(Duration::from_micros(adjust as u64))
         };
 
-        if final_delay < Duration::from_micros(200) {
-            return Duration::from_micros(200);
+        enforce_min_delay(final_delay, self.min_delay_micros)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::constants::defaults;
+
+    const DEFAULT_BURST: BurstPattern = BurstPattern {
+        size: defaults::BURST_SIZE,
+        intra_delay_percent: defaults::BURST_INTRA_DELAY_PERCENT,
+        pause_percent: defaults::BURST_PAUSE_PERCENT,
+    };
+
+    fn provider(deviation: (i32, i32), burst_mode: bool, min_delay_micros: u64, seed: u64) -> DelayProvider {
+        DelayProvider::build(defaults::DELAY_RANGE_MIN,
+                             defaults::DELAY_RANGE_MAX,
+                             deviation.0,
+                             deviation.1,
+                             burst_mode,
+                             DEFAULT_BURST,
+                             DelayDistribution::Uniform,
+                             min_delay_micros,
+                             Some(seed))
+    }
+
+    #[test]
+    fn enforce_min_delay_never_goes_below_the_hard_floor() {
+        let floor = Duration::from_micros(limits::MIN_CLICK_DELAY_FLOOR_MICROS);
+        for min_delay_micros in [0, 1, limits::MIN_CLICK_DELAY_FLOOR_MICROS - 1, limits::MIN_CLICK_DELAY_FLOOR_MICROS] {
+            for delay in [Duration::ZERO, Duration::from_micros(1), floor - Duration::from_micros(1)] {
+                assert_eq!(enforce_min_delay(delay, min_delay_micros), floor);
+            }
+            assert_eq!(enforce_min_delay(floor, min_delay_micros), floor);
+        }
+    }
+
+    #[test]
+    fn enforce_min_delay_raises_to_a_configured_floor_and_keeps_longer_delays() {
+        assert_eq!(enforce_min_delay(Duration::ZERO, 2_000), Duration::from_micros(2_000));
+        assert_eq!(enforce_min_delay(Duration::from_micros(1_999), 2_000), Duration::from_micros(2_000));
+        assert_eq!(enforce_min_delay(Duration::from_micros(2_001), 2_000), Duration::from_micros(2_001));
+        assert_eq!(enforce_min_delay(Duration::from_secs(1), 0), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn get_next_delay_respects_the_floor_for_every_input_combination() {
+        let bases = [Duration::ZERO, Duration::from_micros(1), Duration::from_micros(499), Duration::from_millis(10)];
+        let deviations = [(0, 0), (-50, 50), (-100_000, -50_000), (50_000, -100_000)];
+
+        for min_delay_micros in [0, 1_000, 5_000] {
+            let floor = Duration::from_micros(min_delay_micros.max(limits::MIN_CLICK_DELAY_FLOOR_MICROS));
+            for deviation in deviations {
+                for burst_mode in [false, true] {
+                    let mut provider = provider(deviation, burst_mode, min_delay_micros, 7);
+                    for base in bases {
+                        for _ in 0..64 {
+                            let delay = provider.get_next_delay(base);
+                            assert!(delay >= floor, "{:?} below {:?} (base {:?}, deviation {:?}, burst {})",
+                                    delay, floor, base, deviation, burst_mode);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn set_min_delay_micros_cannot_lower_the_hard_floor() {
+        let mut provider = provider((-100_000, -100_000), false, 5_000, 1);
+        provider.set_min_delay_micros(0);
+        assert_eq!(provider.get_next_delay(Duration::ZERO), Duration::from_micros(limits::MIN_CLICK_DELAY_FLOOR_MICROS));
+    }
+
+    #[test]
+    fn the_same_seed_produces_the_same_delays() {
+        let mut first = provider((-2_000, 2_000), true, 0, 42);
+        let mut second = provider((-2_000, 2_000), true, 0, 42);
+        let mut other = provider((-2_000, 2_000), true, 0, 43);
+
+        let base = Duration::from_millis(50);
+        let first_delays: Vec<Duration> = (0..64).map(|_| first.get_next_delay(base)).collect();
+        let second_delays: Vec<Duration> = (0..64).map(|_| second.get_next_delay(base)).collect();
+        let other_delays: Vec<Duration> = (0..64).map(|_| other.get_next_delay(base)).collect();
+
+        assert_eq!(first_delays, second_delays);
+        assert_ne!(first_delays, other_delays);
+    }
+
+    #[test]
+    fn a_seeded_provider_from_settings_repeats_its_first_delays_within_bounds() {
+        let settings = Settings {
+            burst_mode: false,
+            left_delay_distribution: "Uniform".to_string(),
+            random_deviation_min: -1_500,
+            random_deviation_max: 2_500,
+            ..Settings::default()
+        };
+        let base = Duration::from_millis(40);
+        let first_delays = |seed| {
+            let mut provider = DelayProvider::from_settings(&settings, Some(seed));
+            (0..32).map(|_| provider.get_next_delay(base)).collect::<Vec<Duration>>()
+        };
+
+        let delays = first_delays(21);
+        assert_eq!(delays, first_delays(21));
+
+        let low = Duration::from_micros(40_000 - 1_500 - MICRO_ADJUST_MICROS as u64);
+        let high = Duration::from_micros(40_000 + 2_500 + MICRO_ADJUST_MICROS as u64);
+        assert!(delays.iter().all(|delay| (low..=high).contains(delay)), "{:?} outside {:?}..={:?}", delays, low, high);
+    }
+
+    #[test]
+    fn uniform_deviations_stay_within_the_configured_bounds() {
+        for (min, max) in [(-2_000, 2_000), (100, 900), (900, 100), (-50, -50)] {
+            let provider = provider((min, max), false, 0, 5);
+            let (low, high) = (min.min(max) as i64, min.max(max) as i64);
+            assert!(provider.deviation_buffer.iter().all(|deviation| (low..=high).contains(deviation)),
+                    "deviation outside {}..={}", low, high);
         }
+    }
+
+    #[test]
+    fn the_buffer_index_wraps_after_every_entry_is_used() {
+        let mut provider = provider((-2_000, 2_000), false, 0, 9);
+        let base = Duration::from_millis(50);
+
+        for _ in 0..DELAY_BUFFER_SIZE - 1 {
+            provider.get_next_delay(base);
+        }
+        assert_eq!(provider.current_index, DELAY_BUFFER_SIZE - 1);
+
+        provider.get_next_delay(base);
+        assert_eq!(provider.current_index, 0);
+
+        // The second pass replays the same deviations; only the small micro adjustment differs.
+        let expected = Duration::from_micros((50_000 + provider.deviation_buffer[0]) as u64);
+        let delay = provider.get_next_delay(base);
+        assert!(delay.abs_diff(expected) <= Duration::from_micros(MICRO_ADJUST_MICROS as u64));
+    }
+
+    #[test]
+    fn update_settings_refills_the_buffer_only_when_something_changed() {
+        let mut provider = provider((-50, 50), false, 0, 11);
+        let original = provider.deviation_buffer.clone();
+
+        provider.update_settings(defaults::DELAY_RANGE_MIN, defaults::DELAY_RANGE_MAX, -50, 50);
+        assert_eq!(provider.deviation_buffer, original);
+
+        provider.update_settings(defaults::DELAY_RANGE_MIN, defaults::DELAY_RANGE_MAX, 4_000, 6_000);
+        assert_ne!(provider.deviation_buffer, original);
+        assert!(provider.deviation_buffer.iter().all(|deviation| (4_000..=6_000).contains(deviation)));
+    }
+
+    #[test]
+    fn burst_mode_alternates_short_gaps_and_pauses() {
+        let mut provider = provider((0, 0), true, 0, 13);
+        let base = Duration::from_millis(10);
+        let tolerance = Duration::from_micros(MICRO_ADJUST_MICROS as u64);
+
+        for click in 0..(DEFAULT_BURST.size * 8) {
+            let delay = provider.get_next_delay(base);
+            let expected = base.mul_f64(DEFAULT_BURST.factor(click % DEFAULT_BURST.size));
+            assert!(delay.abs_diff(expected) <= tolerance, "click {}: {:?} vs {:?}", click, delay, expected);
+        }
+
+        // Turning burst mode off and on restarts the pattern on a short gap.
+        provider.get_next_delay(base);
+        provider.toggle_burst_mode();
+        provider.toggle_burst_mode();
+        let expected = base.mul_f64(DEFAULT_BURST.factor(0));
+        assert!(provider.get_next_delay(base).abs_diff(expected) <= tolerance);
+    }
+
+    #[test]
+    fn a_wider_left_deviation_widens_only_left_click_delays() {
+        let spread = |settings: &Settings, button: MouseButton| {
+            let mut provider = DelayProvider::for_button(settings, button);
+            let delays: Vec<Duration> = (0..DELAY_BUFFER_SIZE).map(|_| provider.get_next_delay(Duration::from_millis(50))).collect();
+            *delays.iter().max().unwrap() - *delays.iter().min().unwrap()
+        };
+
+        // Burst pacing and Gaussian tails would swamp the deviation range, so measure plain uniform delays.
+        let mut settings = Settings {
+            left_burst_mode: false,
+            right_burst_mode: false,
+            left_delay_distribution: "Uniform".to_string(),
+            right_delay_distribution: "Uniform".to_string(),
+            left_random_deviation_min: -500,
+            left_random_deviation_max: 500,
+            right_random_deviation_min: -500,
+            right_random_deviation_max: 500,
+            ..Settings::default()
+        };
+        let narrow_left = spread(&settings, MouseButton::Left);
+        let narrow_right = spread(&settings, MouseButton::Right);
+
+        settings.left_random_deviation_max = 10_000;
+        let wide_left = spread(&settings, MouseButton::Left);
+        let right = spread(&settings, MouseButton::Right);
 
-        final_delay
+        assert!(wide_left > narrow_left * 4, "{:?} vs {:?}", wide_left, narrow_left);
+        assert!(right < Duration::from_micros(500 * 2 + MICRO_ADJUST_MICROS as u64 * 2), "{:?} (was {:?})", right, narrow_right);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn gaussian_samples_have_the_requested_mean_and_std_dev() {
+        let mut rng = StdRng::seed_from_u64(17);
+        let (mean, std_dev) = (1_000.0, 200.0);
+        let samples: Vec<f64> = (0..20_000).map(|_| DelayDistribution::Gaussian.sample(&mut rng, mean, std_dev)).collect();
+
+        let sample_mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let sample_variance = samples.iter().map(|sample| (sample - sample_mean).powi(2)).sum::<f64>() / samples.len() as f64;
+
+        assert!((sample_mean - mean).abs() < 10.0, "mean {}", sample_mean);
+        assert!((sample_variance.sqrt() - std_dev).abs() < 10.0, "std dev {}", sample_variance.sqrt());
+    }
+
+    #[test]
+    fn gaussian_delays_are_clamped_to_the_floor() {
+        let mut provider = provider((-100_000, 0), false, 2_000, 19);
+        provider.set_distribution(DelayDistribution::Gaussian);
+
+        for _ in 0..DELAY_BUFFER_SIZE {
+            assert!(provider.get_next_delay(Duration::from_millis(10)) >= Duration::from_micros(2_000));
+        }
+    }
+}
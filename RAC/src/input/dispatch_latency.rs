@@ -0,0 +1,57 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy)]
+pub struct DispatchLatencySnapshot {
+    pub samples: u64,
+    pub min_micros: u64,
+    pub avg_micros: u64,
+    pub max_micros: u64,
+}
+
+pub struct DispatchLatency {
+    samples: AtomicU64,
+    total_micros: AtomicU64,
+    min_micros: AtomicU64,
+    max_micros: AtomicU64,
+}
+
+impl DispatchLatency {
+    pub fn new() -> Self {
+        Self {
+            samples: AtomicU64::new(0),
+            total_micros: AtomicU64::new(0),
+            min_micros: AtomicU64::new(u64::MAX),
+            max_micros: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record(&self, elapsed: Duration) {
+        let micros = elapsed.as_micros() as u64;
+        self.samples.fetch_add(1, Ordering::Relaxed);
+        self.total_micros.fetch_add(micros, Ordering::Relaxed);
+        self.min_micros.fetch_min(micros, Ordering::Relaxed);
+        self.max_micros.fetch_max(micros, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> Option<DispatchLatencySnapshot> {
+        let samples = self.samples.load(Ordering::Relaxed);
+        if samples == 0 {
+            return None;
+        }
+
+        Some(DispatchLatencySnapshot {
+            samples,
+            min_micros: self.min_micros.load(Ordering::Relaxed),
+            avg_micros: self.total_micros.load(Ordering::Relaxed) / samples,
+            max_micros: self.max_micros.load(Ordering::Relaxed),
+        })
+    }
+
+    pub fn reset(&self) {
+        self.samples.store(0, Ordering::Relaxed);
+        self.total_micros.store(0, Ordering::Relaxed);
+        self.min_micros.store(u64::MAX, Ordering::Relaxed);
+        self.max_micros.store(0, Ordering::Relaxed);
+    }
+}
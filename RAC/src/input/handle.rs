@@ -1,5 +1,5 @@
 use std::ptr::null_mut;
-use winapi::shared::windef::HWND;
+use crate::input::backend::HWND;
 
 pub struct Handle {
     handle: HWND,
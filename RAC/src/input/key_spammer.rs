@@ -0,0 +1,141 @@
+use crate::config::constants::limits;
+use crate::config::settings::Settings;
+use crate::input::backend::{backend, HWND};
+use crate::input::delay_provider::DelayProvider;
+use crate::input::thread_controller::{Sleeper, ThreadController};
+use crate::logger::logger::log_info;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU8, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+// Repeats one keyboard key into the target window. It runs on its own thread next to the click
+// executors rather than through them, so it shares none of their hold gating, pending release or
+// session stats and can run while mouse clicking is on.
+pub struct KeySpammer {
+    enabled: AtomicBool,
+    spam_key: AtomicI32,
+    toggle_key: AtomicI32,
+    max_cps: AtomicU8,
+    hold_mode: AtomicBool,
+    // Follows ClickService start/stop so spam never outlives a session.
+    active: AtomicBool,
+    // Window and key of a posted key-down still waiting for its key-up.
+    key_down: Mutex<Option<(usize, i32)>>,
+    delay_provider: Mutex<DelayProvider>,
+    thread_controller: ThreadController,
+}
+
+impl KeySpammer {
+    pub fn new(settings: &Settings) -> Self {
+        let thread_controller = ThreadController::new(settings.adaptive_cpu_mode);
+        thread_controller.set_spin_threshold_micros(settings.spin_threshold_micros);
+
+        Self {
+            enabled: AtomicBool::new(settings.key_spam_enabled),
+            spam_key: AtomicI32::new(settings.key_spam_key),
+            toggle_key: AtomicI32::new(settings.key_spam_toggle_key),
+            max_cps: AtomicU8::new(settings.key_spam_cps.clamp(limits::MIN_CPS, limits::MAX_CPS)),
+            hold_mode: AtomicBool::new(settings.key_spam_hold_mode),
+            active: AtomicBool::new(false),
+            key_down: Mutex::new(None),
+            delay_provider: Mutex::new(DelayProvider::from_settings(settings, None)),
+            thread_controller,
+        }
+    }
+
+    pub fn configure(&self, settings: &Settings) {
+        let spam_key = self.spam_key.load(Ordering::SeqCst);
+        if spam_key != settings.key_spam_key {
+            self.release_key();
+        }
+
+        self.enabled.store(settings.key_spam_enabled, Ordering::SeqCst);
+        self.spam_key.store(settings.key_spam_key, Ordering::SeqCst);
+        self.toggle_key.store(settings.key_spam_toggle_key, Ordering::SeqCst);
+        self.max_cps.store(settings.key_spam_cps.clamp(limits::MIN_CPS, limits::MAX_CPS), Ordering::SeqCst);
+        self.hold_mode.store(settings.key_spam_hold_mode, Ordering::SeqCst);
+        if let Ok(mut delay_provider) = self.delay_provider.lock() {
+            *delay_provider = DelayProvider::from_settings(settings, None);
+        }
+
+        log_info(&format!("Key spam {}: key=0x{:02X}, toggle=0x{:02X}, {} CPS, {} mode",
+                          if settings.key_spam_enabled { "enabled" } else { "disabled" },
+                          settings.key_spam_key, settings.key_spam_toggle_key, settings.key_spam_cps,
+                          if settings.key_spam_hold_mode { "hold" } else { "toggle" }),
+                 "KeySpammer::configure");
+    }
+
+    // Both keys have to be set before there is anything to spam or anything to arm it with.
+    pub fn is_configured(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+            && self.spam_key.load(Ordering::SeqCst) != 0
+            && self.toggle_key.load(Ordering::SeqCst) != 0
+    }
+
+    pub fn toggle_key(&self) -> i32 {
+        self.toggle_key.load(Ordering::SeqCst)
+    }
+
+    pub fn is_hold_mode(&self) -> bool {
+        self.hold_mode.load(Ordering::SeqCst)
+    }
+
+    pub fn set_active(&self, active: bool) {
+        self.active.store(active, Ordering::SeqCst);
+        if !active {
+            self.release_key();
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    pub fn thread_controller(&self) -> &ThreadController {
+        &self.thread_controller
+    }
+
+    // Key-down, a short hold, key-up. Returns whether both were delivered.
+    pub fn press_once(&self, hwnd: HWND) -> bool {
+        let spam_key = self.spam_key.load(Ordering::SeqCst);
+        if hwnd.is_null() || spam_key == 0 {
+            return false;
+        }
+
+        if let Ok(mut key_down) = self.key_down.lock() {
+            *key_down = Some((hwnd as usize, spam_key));
+        }
+        let pressed = backend().post_key(hwnd, spam_key, true);
+        self.thread_controller.sleep(Duration::from_micros(1));
+        let released = backend().post_key(hwnd, spam_key, false);
+        if released {
+            if let Ok(mut key_down) = self.key_down.lock() {
+                *key_down = None;
+            }
+        }
+
+        pressed && released
+    }
+
+    pub fn release_key(&self) {
+        let pending = match self.key_down.lock() {
+            Ok(mut key_down) => key_down.take(),
+            Err(_) => return,
+        };
+
+        if let Some((hwnd, spam_key)) = pending {
+            backend().post_key(hwnd as HWND, spam_key, false);
+            log_info(&format!("Sent balancing key-up for 0x{:02X}", spam_key), "KeySpammer::release_key");
+        }
+    }
+
+    // Same path as a click: the rate sets the base interval, the delay provider adds deviation and the floor.
+    pub fn next_delay(&self) -> Duration {
+        let max_cps = self.max_cps.load(Ordering::SeqCst).max(1);
+        let base_delay = Duration::from_micros(1_000_000 / max_cps as u64);
+        match self.delay_provider.lock() {
+            Ok(mut delay_provider) => delay_provider.get_next_delay(base_delay),
+            Err(_) => base_delay,
+        }
+    }
+}
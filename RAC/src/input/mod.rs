@@ -1,10 +1,23 @@
+pub(crate) mod backend;
+pub(crate) mod benchmark;
+mod backpressure;
 pub(crate) mod click_executor;
+pub(crate) mod click_profile;
+pub(crate) mod click_region;
 pub(crate) mod click_service;
+pub(crate) mod click_strategy;
+mod cps_drift;
 mod delay_provider;
+pub(crate) mod dispatch_latency;
 mod handle;
+mod key_spammer;
+#[cfg_attr(not(windows), path = "raw_input_stub.rs")]
+pub(crate) mod raw_input;
+pub(crate) mod session_stats;
 mod sync_controller;
-mod thread_controller;
-mod window_finder;
+pub(crate) mod thread_controller;
+pub(crate) mod toggle_state;
+pub(crate) mod window_finder;
 
 
 
@@ -0,0 +1,200 @@
+use crate::logger::logger::{log_error, log_info};
+use lazy_static::lazy_static;
+use std::mem::{size_of, zeroed};
+use std::ptr::{null, null_mut};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use winapi::shared::minwindef::{LPARAM, LRESULT, UINT, WPARAM};
+use winapi::shared::windef::HWND;
+use winapi::um::winuser::{
+    CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, GetRawInputData, RegisterClassW,
+    RegisterRawInputDevices, TranslateMessage, HRAWINPUT, HWND_MESSAGE, MSG, RAWINPUT, RAWINPUTDEVICE,
+    RAWINPUTHEADER, RIDEV_INPUTSINK, RID_INPUT, RIM_TYPEKEYBOARD, RIM_TYPEMOUSE, RI_KEY_BREAK,
+    RI_MOUSE_BUTTON_4_DOWN, RI_MOUSE_BUTTON_4_UP, RI_MOUSE_BUTTON_5_DOWN, RI_MOUSE_BUTTON_5_UP,
+    RI_MOUSE_LEFT_BUTTON_DOWN, RI_MOUSE_LEFT_BUTTON_UP, RI_MOUSE_MIDDLE_BUTTON_DOWN,
+    RI_MOUSE_MIDDLE_BUTTON_UP, RI_MOUSE_RIGHT_BUTTON_DOWN, RI_MOUSE_RIGHT_BUTTON_UP, WM_INPUT, WNDCLASSW,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RawKeyEvent {
+    pub virtual_key: i32,
+    pub pressed: bool,
+}
+
+lazy_static! {
+    static ref RAW_INPUT_SENDER: Mutex<Option<Sender<RawKeyEvent>>> = Mutex::new(None);
+}
+
+const MOUSE_BUTTON_FLAGS: [(u16, u16, i32); 5] = [
+    (RI_MOUSE_LEFT_BUTTON_DOWN, RI_MOUSE_LEFT_BUTTON_UP, 0x01),
+    (RI_MOUSE_RIGHT_BUTTON_DOWN, RI_MOUSE_RIGHT_BUTTON_UP, 0x02),
+    (RI_MOUSE_MIDDLE_BUTTON_DOWN, RI_MOUSE_MIDDLE_BUTTON_UP, 0x04),
+    (RI_MOUSE_BUTTON_4_DOWN, RI_MOUSE_BUTTON_4_UP, 0x05),
+    (RI_MOUSE_BUTTON_5_DOWN, RI_MOUSE_BUTTON_5_UP, 0x06),
+];
+
+fn send_event(event: RawKeyEvent) {
+    if let Ok(sender) = RAW_INPUT_SENDER.lock() {
+        if let Some(sender) = sender.as_ref() {
+            let _ = sender.send(event);
+        }
+    }
+}
+
+unsafe fn handle_raw_input(lparam: LPARAM) {
+    let mut raw: RAWINPUT = zeroed();
+    let mut size = size_of::<RAWINPUT>() as UINT;
+
+    let read = GetRawInputData(
+        lparam as HRAWINPUT,
+        RID_INPUT,
+        &mut raw as *mut _ as *mut _,
+        &mut size,
+        size_of::<RAWINPUTHEADER>() as UINT,
+    );
+
+    if read == u32::MAX || read == 0 {
+        return;
+    }
+
+    match raw.header.dwType {
+        RIM_TYPEKEYBOARD => {
+            let keyboard = raw.data.keyboard();
+            send_event(RawKeyEvent {
+                virtual_key: keyboard.VKey as i32,
+                pressed: (keyboard.Flags as u32 & RI_KEY_BREAK as u32) == 0,
+            });
+        }
+        RIM_TYPEMOUSE => {
+            let button_flags = raw.data.mouse().usButtonFlags;
+            for (down, up, virtual_key) in MOUSE_BUTTON_FLAGS {
+                if button_flags & down != 0 {
+                    send_event(RawKeyEvent { virtual_key, pressed: true });
+                }
+                if button_flags & up != 0 {
+                    send_event(RawKeyEvent { virtual_key, pressed: false });
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+unsafe extern "system" fn raw_input_window_proc(hwnd: HWND, msg: UINT, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if msg == WM_INPUT {
+        handle_raw_input(lparam);
+    }
+    DefWindowProcW(hwnd, msg, wparam, lparam)
+}
+
+fn wide(value: &str) -> Vec<u16> {
+    value.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+unsafe fn create_listener_window() -> Result<HWND, String> {
+    let class_name = wide("RACRawInputListener");
+
+    let mut window_class: WNDCLASSW = zeroed();
+    window_class.lpfnWndProc = Some(raw_input_window_proc);
+    window_class.lpszClassName = class_name.as_ptr();
+
+    if RegisterClassW(&window_class) == 0 {
+        return Err("Failed to register raw input window class".to_string());
+    }
+
+    let hwnd = CreateWindowExW(
+        0,
+        class_name.as_ptr(),
+        null(),
+        0,
+        0,
+        0,
+        0,
+        0,
+        HWND_MESSAGE,
+        null_mut(),
+        null_mut(),
+        null_mut(),
+    );
+
+    if hwnd.is_null() {
+        return Err("Failed to create raw input message window".to_string());
+    }
+
+    let devices = [
+        RAWINPUTDEVICE { usUsagePage: 0x01, usUsage: 0x02, dwFlags: RIDEV_INPUTSINK, hwndTarget: hwnd },
+        RAWINPUTDEVICE { usUsagePage: 0x01, usUsage: 0x06, dwFlags: RIDEV_INPUTSINK, hwndTarget: hwnd },
+    ];
+
+    if RegisterRawInputDevices(devices.as_ptr(), devices.len() as UINT, size_of::<RAWINPUTDEVICE>() as UINT) == 0 {
+        return Err("Failed to register raw input devices".to_string());
+    }
+
+    Ok(hwnd)
+}
+
+pub struct RawInputListener {
+    receiver: Receiver<RawKeyEvent>,
+}
+
+impl RawInputListener {
+    pub fn start() -> Option<Self> {
+        let context = "RawInputListener::start";
+
+        let (sender, receiver) = mpsc::channel();
+        match RAW_INPUT_SENDER.lock() {
+            Ok(mut global_sender) => *global_sender = Some(sender),
+            Err(e) => {
+                log_error(&format!("Failed to lock raw input sender: {}", e), context);
+                return None;
+            }
+        }
+
+        let (ready_sender, ready_receiver) = mpsc::channel();
+        let spawn_result = thread::Builder::new()
+            .name("RawInputThread".to_string())
+            .spawn(move || unsafe {
+                match create_listener_window() {
+                    Ok(_) => {
+                        let _ = ready_sender.send(Ok(()));
+                    }
+                    Err(e) => {
+                        let _ = ready_sender.send(Err(e));
+                        return;
+                    }
+                }
+
+                let mut msg: MSG = zeroed();
+                while GetMessageW(&mut msg, null_mut(), 0, 0) > 0 {
+                    TranslateMessage(&msg);
+                    DispatchMessageW(&msg);
+                }
+            });
+
+        if let Err(e) = spawn_result {
+            log_error(&format!("Failed to spawn raw input thread: {}", e), context);
+            return None;
+        }
+
+        match ready_receiver.recv_timeout(Duration::from_secs(2)) {
+            Ok(Ok(())) => {
+                log_info("Raw input listener started", context);
+                Some(Self { receiver })
+            }
+            Ok(Err(e)) => {
+                log_error(&e, context);
+                None
+            }
+            Err(e) => {
+                log_error(&format!("Raw input listener did not start: {}", e), context);
+                None
+            }
+        }
+    }
+
+    pub fn next_event(&self, timeout: Duration) -> Option<RawKeyEvent> {
+        self.receiver.recv_timeout(timeout).ok()
+    }
+}
@@ -0,0 +1,25 @@
+use crate::logger::logger::log_warn;
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RawKeyEvent {
+    pub virtual_key: i32,
+    pub pressed: bool,
+}
+
+// Raw input is a Win32 facility; callers fall back to polling key state when start() returns None.
+pub struct RawInputListener {
+    receiver: Receiver<RawKeyEvent>,
+}
+
+impl RawInputListener {
+    pub fn start() -> Option<Self> {
+        log_warn("Raw input is only available on Windows, falling back to key state polling", "RawInputListener::start");
+        None
+    }
+
+    pub fn next_event(&self, timeout: Duration) -> Option<RawKeyEvent> {
+        self.receiver.recv_timeout(timeout).ok()
+    }
+}
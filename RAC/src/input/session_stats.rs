@@ -0,0 +1,93 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Instant;
+
+// Gaps longer than this are the user letting go of the button, not click cadence.
+const MAX_TRACKED_GAP_MICROS: u64 = 1_000_000;
+
+#[derive(Debug, Clone, Copy)]
+pub struct SessionStatsSnapshot {
+    pub clicks: usize,
+    pub failures: usize,
+    pub min_delay_micros: u64,
+    pub mean_delay_micros: u64,
+    pub max_delay_micros: u64,
+}
+
+pub struct SessionStats {
+    epoch: Instant,
+    clicks: AtomicUsize,
+    failures: AtomicUsize,
+    last_click_micros: AtomicU64,
+    delay_samples: AtomicU64,
+    total_delay_micros: AtomicU64,
+    min_delay_micros: AtomicU64,
+    max_delay_micros: AtomicU64,
+}
+
+impl SessionStats {
+    pub fn new() -> Self {
+        Self {
+            epoch: Instant::now(),
+            clicks: AtomicUsize::new(0),
+            failures: AtomicUsize::new(0),
+            last_click_micros: AtomicU64::new(0),
+            delay_samples: AtomicU64::new(0),
+            total_delay_micros: AtomicU64::new(0),
+            min_delay_micros: AtomicU64::new(u64::MAX),
+            max_delay_micros: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record_click(&self) {
+        self.clicks.fetch_add(1, Ordering::Relaxed);
+
+        // Offset by one so zero can mean "no previous click".
+        let now = self.epoch.elapsed().as_micros() as u64 + 1;
+        let previous = self.last_click_micros.swap(now, Ordering::Relaxed);
+        if previous == 0 {
+            return;
+        }
+
+        let gap = now.saturating_sub(previous);
+        if gap > MAX_TRACKED_GAP_MICROS {
+            return;
+        }
+
+        self.delay_samples.fetch_add(1, Ordering::Relaxed);
+        self.total_delay_micros.fetch_add(gap, Ordering::Relaxed);
+        self.min_delay_micros.fetch_min(gap, Ordering::Relaxed);
+        self.max_delay_micros.fetch_max(gap, Ordering::Relaxed);
+    }
+
+    pub fn record_failure(&self) {
+        self.failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn clicks(&self) -> usize {
+        self.clicks.load(Ordering::Relaxed)
+    }
+
+    pub fn snapshot(&self) -> SessionStatsSnapshot {
+        let samples = self.delay_samples.load(Ordering::Relaxed);
+        let min_delay_micros = if samples == 0 { 0 } else { self.min_delay_micros.load(Ordering::Relaxed) };
+        let mean_delay_micros = self.total_delay_micros.load(Ordering::Relaxed).checked_div(samples).unwrap_or(0);
+
+        SessionStatsSnapshot {
+            clicks: self.clicks(),
+            failures: self.failures.load(Ordering::Relaxed),
+            min_delay_micros,
+            mean_delay_micros,
+            max_delay_micros: self.max_delay_micros.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn reset(&self) {
+        self.clicks.store(0, Ordering::Relaxed);
+        self.failures.store(0, Ordering::Relaxed);
+        self.last_click_micros.store(0, Ordering::Relaxed);
+        self.delay_samples.store(0, Ordering::Relaxed);
+        self.total_delay_micros.store(0, Ordering::Relaxed);
+        self.min_delay_micros.store(u64::MAX, Ordering::Relaxed);
+        self.max_delay_micros.store(0, Ordering::Relaxed);
+    }
+}
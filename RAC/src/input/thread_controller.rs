@@ -1,64 +1,127 @@
-use crate::logger::logger::log_error;
+use crate::input::backend::{backend, ThreadPriority};
+use crate::logger::logger::{log_error, log_info, log_warn};
 use std::time::Duration;
-use windows::Win32::System::Threading::{GetCurrentThread, SetThreadPriority};
-use windows::Win32::System::Threading::{THREAD_PRIORITY_BELOW_NORMAL, THREAD_PRIORITY_NORMAL, THREAD_PRIORITY_TIME_CRITICAL};
 use std::time::Instant;
 use std::thread;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::panic::RefUnwindSafe;
+use crate::config::constants::defaults;
+
+// 1ms system timer resolution lets thread::sleep wake within about a millisecond instead of the
+// default ~15.6ms tick. The tradeoff is a system-wide timer interrupt rate increase, which costs
+// some battery on laptops, so it is only held while the click service is running.
+const TIMER_RESOLUTION_MS: u32 = 1;
+static TIMER_RESOLUTION_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+#[cfg(debug_assertions)]
+const SLEEP_ACCURACY_SAMPLE_SIZE: u64 = 1000;
+#[cfg(debug_assertions)]
+static SLEEP_SAMPLES: AtomicU64 = AtomicU64::new(0);
+#[cfg(debug_assertions)]
+static SLEEP_REQUESTED_MICROS: AtomicU64 = AtomicU64::new(0);
+#[cfg(debug_assertions)]
+static SLEEP_ACTUAL_MICROS: AtomicU64 = AtomicU64::new(0);
 
 pub struct ThreadController {
-    adaptive_mode: bool,
+    adaptive_mode: AtomicBool,
+    spin_threshold_micros: AtomicU64,
 }
 
 impl ThreadController {
     pub(crate) fn clone(&self) -> ThreadController {
         ThreadController {
-            adaptive_mode: self.adaptive_mode,
+            adaptive_mode: AtomicBool::new(self.adaptive_mode.load(Ordering::SeqCst)),
+            spin_threshold_micros: AtomicU64::new(self.spin_threshold_micros.load(Ordering::SeqCst)),
         }
     }
 }
 
+// Waits inside a click, e.g. between button-down and button-up.
+pub trait Sleeper: Send + Sync + RefUnwindSafe {
+    fn sleep(&self, duration: Duration);
+}
+
+impl Sleeper for ThreadController {
+    fn sleep(&self, duration: Duration) {
+        self.smart_sleep(duration);
+    }
+}
+
 impl ThreadController {
     pub fn new(adaptive_mode: bool) -> Self {
-        Self { adaptive_mode }
+        Self {
+            adaptive_mode: AtomicBool::new(adaptive_mode),
+            spin_threshold_micros: AtomicU64::new(defaults::SPIN_THRESHOLD_MICROS),
+        }
+    }
+
+    // Sleeps shorter than the threshold busy-wait for accuracy; longer ones yield the CPU.
+    pub fn set_spin_threshold_micros(&self, spin_threshold_micros: u64) {
+        self.spin_threshold_micros.store(spin_threshold_micros, Ordering::SeqCst);
+    }
+
+    // Normal-priority (adaptive) threads spin for at most a quarter of the threshold to save CPU.
+    fn effective_spin_threshold_micros(&self) -> u128 {
+        let threshold = self.spin_threshold_micros.load(Ordering::SeqCst);
+        let threshold = if self.adaptive_mode.load(Ordering::SeqCst) { threshold / 4 } else { threshold };
+        threshold as u128
     }
 
     pub fn set_adaptive_mode(&self, adaptive_mode: bool) {
-        unsafe {
-            let self_ptr = self as *const ThreadController as *mut ThreadController;
-            (*self_ptr).adaptive_mode = adaptive_mode;
-        }
+        self.adaptive_mode.store(adaptive_mode, Ordering::SeqCst);
     }
 
     pub fn set_active_priority(&self) {
         let context = "ThreadController::set_active_priority";
-        unsafe {
-            let priority = if self.adaptive_mode {
-                THREAD_PRIORITY_NORMAL
-            } else {
-                THREAD_PRIORITY_TIME_CRITICAL
-            };
-
-            if let Err(e) = SetThreadPriority(GetCurrentThread(), priority) {
-                log_error(&format!("Failed to set active thread priority: {:?}", e), context);
-            }
+        let priority = if self.adaptive_mode.load(Ordering::SeqCst) {
+            ThreadPriority::Normal
+        } else {
+            ThreadPriority::TimeCritical
+        };
+
+        if !backend().set_thread_priority(priority) {
+            log_error(&format!("Failed to set active thread priority to {:?}", priority), context);
+        }
+    }
+
+    // Keeps the calling thread on one logical core so the scheduler can't migrate it mid-interval.
+    // A negative core selects the last one. Does nothing on single-core systems.
+    pub fn pin_current_thread(core: i32) {
+        let context = "ThreadController::pin_current_thread";
+        let core_count = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        if core_count < 2 {
+            log_info("Single-core system, skipping click thread affinity", context);
+            return;
+        }
+
+        let max_cores = (usize::BITS as usize).min(core_count);
+        let core = if core < 0 {
+            max_cores - 1
+        } else if (core as usize) < max_cores {
+            core as usize
+        } else {
+            log_warn(&format!("Core {} is out of range (0-{}), using the last core", core, max_cores - 1), context);
+            max_cores - 1
+        };
+
+        if !backend().pin_current_thread(core) {
+            log_error(&format!("Failed to pin {} to core {}", thread::current().name().unwrap_or("thread"), core), context);
+        } else {
+            log_info(&format!("Pinned {} to core {}", thread::current().name().unwrap_or("thread"), core), context);
         }
     }
 
     pub fn set_normal_priority(&self) {
         let context = "ThreadController::set_normal_priority";
-        unsafe {
-            if let Err(e) = SetThreadPriority(GetCurrentThread(), THREAD_PRIORITY_NORMAL) {
-                log_error(&format!("Failed to set normal thread priority: {:?}", e), context);
-            }
+        if !backend().set_thread_priority(ThreadPriority::Normal) {
+            log_error("Failed to set normal thread priority", context);
         }
     }
 
     pub fn set_idle_priority(&self) {
         let context = "ThreadController::set_idle_priority";
-        unsafe {
-            if let Err(e) = SetThreadPriority(GetCurrentThread(), THREAD_PRIORITY_BELOW_NORMAL) {
-                log_error(&format!("Failed to set idle thread priority: {:?}", e), context);
-            }
+        if !backend().set_thread_priority(ThreadPriority::Idle) {
+            log_error("Failed to set idle thread priority", context);
         }
     }
 
@@ -67,12 +130,54 @@ impl ThreadController {
             return;
         }
 
-        if duration.as_micros() < 1000 {
+        if duration.as_micros() < self.effective_spin_threshold_micros() {
             let start = Instant::now();
             while start.elapsed() < duration {}
             return;
         }
 
+        #[cfg(debug_assertions)]
+        let start = Instant::now();
+
         thread::sleep(duration);
+
+        #[cfg(debug_assertions)]
+        Self::record_sleep_accuracy(duration, start.elapsed());
+    }
+
+    pub fn begin_timer_resolution() {
+        if TIMER_RESOLUTION_ACTIVE.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        if backend().begin_timer_resolution(TIMER_RESOLUTION_MS) {
+            log_info(&format!("System timer resolution set to {}ms", TIMER_RESOLUTION_MS), "ThreadController::begin_timer_resolution");
+        } else {
+            TIMER_RESOLUTION_ACTIVE.store(false, Ordering::SeqCst);
+            log_warn("Failed to raise system timer resolution, sleeps may overshoot", "ThreadController::begin_timer_resolution");
+        }
+    }
+
+    pub fn end_timer_resolution() {
+        if TIMER_RESOLUTION_ACTIVE.swap(false, Ordering::SeqCst) {
+            backend().end_timer_resolution(TIMER_RESOLUTION_MS);
+            log_info("System timer resolution restored", "ThreadController::end_timer_resolution");
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    fn record_sleep_accuracy(requested: Duration, actual: Duration) {
+        let requested_total = SLEEP_REQUESTED_MICROS.fetch_add(requested.as_micros() as u64, Ordering::Relaxed) + requested.as_micros() as u64;
+        let actual_total = SLEEP_ACTUAL_MICROS.fetch_add(actual.as_micros() as u64, Ordering::Relaxed) + actual.as_micros() as u64;
+        let samples = SLEEP_SAMPLES.fetch_add(1, Ordering::Relaxed) + 1;
+
+        if samples >= SLEEP_ACCURACY_SAMPLE_SIZE {
+            SLEEP_SAMPLES.store(0, Ordering::Relaxed);
+            SLEEP_REQUESTED_MICROS.store(0, Ordering::Relaxed);
+            SLEEP_ACTUAL_MICROS.store(0, Ordering::Relaxed);
+            log_info(&format!("Sleep accuracy over {} sleeps: requested avg {}us, actual avg {}us",
+                              samples, requested_total / samples, actual_total / samples),
+                     "ThreadController::smart_sleep");
+        }
     }
 }
\ No newline at end of file
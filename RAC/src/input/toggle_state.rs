@@ -0,0 +1,212 @@
+use crate::input::backend::backend;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+pub trait KeyStateSource {
+    fn is_down(&self, vk: i32) -> bool;
+}
+
+// Reads the live keyboard/mouse state through the platform input backend.
+pub struct SystemKeyState;
+
+impl KeyStateSource for SystemKeyState {
+    fn is_down(&self, vk: i32) -> bool {
+        backend().is_key_pressed(vk)
+    }
+}
+
+// Key state built from press/release events, for detectors that don't poll.
+#[derive(Debug, Default)]
+pub struct PressedKeys {
+    down: HashSet<i32>,
+}
+
+impl PressedKeys {
+    pub fn record(&mut self, vk: i32, pressed: bool) {
+        if pressed {
+            self.down.insert(vk);
+        } else {
+            self.down.remove(&vk);
+        }
+    }
+}
+
+impl KeyStateSource for PressedKeys {
+    fn is_down(&self, vk: i32) -> bool {
+        self.down.contains(&vk)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToggleMode {
+    MouseHold,
+    KeyboardHold,
+}
+
+impl ToggleMode {
+    pub fn from_keyboard_hold(keyboard_hold_mode: bool) -> Self {
+        if keyboard_hold_mode { ToggleMode::KeyboardHold } else { ToggleMode::MouseHold }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToggleTransition {
+    Unchanged,
+    Activated,
+    Deactivated,
+    // Double-press gesture; the caller resets click state and re-applies is_active().
+    Reset,
+    ScheduleEnded,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ToggleInput {
+    pub mode: ToggleMode,
+    pub in_schedule: bool,
+    pub reset_window: Duration,
+    pub now: Instant,
+}
+
+#[derive(Debug, Default)]
+pub struct ToggleState {
+    was_pressed: bool,
+    is_active: bool,
+    last_press: Option<Instant>,
+}
+
+impl ToggleState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    pub fn poll(&mut self, keys: &dyn KeyStateSource, toggle_key: i32, input: ToggleInput) -> ToggleTransition {
+        self.step(keys.is_down(toggle_key), input)
+    }
+
+    pub fn step(&mut self, is_pressed: bool, input: ToggleInput) -> ToggleTransition {
+        let just_pressed = is_pressed && !self.was_pressed;
+        self.was_pressed = is_pressed;
+
+        if just_pressed {
+            let reset_fired = self.last_press.is_some_and(|last| input.now.duration_since(last) <= input.reset_window);
+            self.last_press = if reset_fired { None } else { Some(input.now) };
+
+            if reset_fired {
+                // Keyboard hold re-arms on its own while the key is held; mouse hold comes back armed.
+                self.is_active = input.mode == ToggleMode::MouseHold && input.in_schedule;
+                return ToggleTransition::Reset;
+            }
+        }
+
+        let was_active = self.is_active;
+        match input.mode {
+            ToggleMode::MouseHold => {
+                if just_pressed && (self.is_active || input.in_schedule) {
+                    self.is_active = !self.is_active;
+                }
+            },
+            ToggleMode::KeyboardHold => {
+                self.is_active = is_pressed && input.in_schedule;
+            }
+        }
+
+        if self.is_active && !input.in_schedule {
+            self.is_active = false;
+            return ToggleTransition::ScheduleEnded;
+        }
+
+        match (was_active, self.is_active) {
+            (false, true) => ToggleTransition::Activated,
+            (true, false) => ToggleTransition::Deactivated,
+            _ => ToggleTransition::Unchanged,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOGGLE_KEY: i32 = 0x05;
+
+    fn input(mode: ToggleMode, now: Instant) -> ToggleInput {
+        ToggleInput { mode, in_schedule: true, reset_window: Duration::from_millis(300), now }
+    }
+
+    // Presses and releases the toggle key once, a second apart so no double press fires.
+    fn tap(state: &mut ToggleState, keys: &mut PressedKeys, mode: ToggleMode, now: &mut Instant) -> ToggleTransition {
+        *now += Duration::from_secs(1);
+        keys.record(TOGGLE_KEY, true);
+        let transition = state.poll(keys, TOGGLE_KEY, input(mode, *now));
+        keys.record(TOGGLE_KEY, false);
+        assert_eq!(state.poll(keys, TOGGLE_KEY, input(mode, *now)), ToggleTransition::Unchanged);
+        transition
+    }
+
+    #[test]
+    fn mouse_hold_toggles_on_and_off_with_each_press() {
+        let (mut state, mut keys, mut now) = (ToggleState::new(), PressedKeys::default(), Instant::now());
+
+        assert_eq!(tap(&mut state, &mut keys, ToggleMode::MouseHold, &mut now), ToggleTransition::Activated);
+        assert!(state.is_active());
+        assert_eq!(tap(&mut state, &mut keys, ToggleMode::MouseHold, &mut now), ToggleTransition::Deactivated);
+        assert!(!state.is_active());
+        assert_eq!(tap(&mut state, &mut keys, ToggleMode::MouseHold, &mut now), ToggleTransition::Activated);
+    }
+
+    #[test]
+    fn mouse_hold_stays_armed_while_the_key_is_held() {
+        let (mut state, mut keys, now) = (ToggleState::new(), PressedKeys::default(), Instant::now());
+
+        keys.record(TOGGLE_KEY, true);
+        assert_eq!(state.poll(&keys, TOGGLE_KEY, input(ToggleMode::MouseHold, now)), ToggleTransition::Activated);
+        for _ in 0..3 {
+            assert_eq!(state.poll(&keys, TOGGLE_KEY, input(ToggleMode::MouseHold, now)), ToggleTransition::Unchanged);
+        }
+        assert!(state.is_active());
+    }
+
+    #[test]
+    fn keyboard_hold_is_active_only_while_the_key_is_down() {
+        let (mut state, mut keys, now) = (ToggleState::new(), PressedKeys::default(), Instant::now());
+
+        keys.record(TOGGLE_KEY, true);
+        assert_eq!(state.poll(&keys, TOGGLE_KEY, input(ToggleMode::KeyboardHold, now)), ToggleTransition::Activated);
+        assert_eq!(state.poll(&keys, TOGGLE_KEY, input(ToggleMode::KeyboardHold, now)), ToggleTransition::Unchanged);
+        assert!(state.is_active());
+
+        keys.record(TOGGLE_KEY, false);
+        assert_eq!(state.poll(&keys, TOGGLE_KEY, input(ToggleMode::KeyboardHold, now)), ToggleTransition::Deactivated);
+        assert!(!state.is_active());
+    }
+
+    #[test]
+    fn a_double_press_within_the_window_resets() {
+        let (mut state, now) = (ToggleState::new(), Instant::now());
+        let mode = ToggleMode::MouseHold;
+
+        assert_eq!(state.step(true, input(mode, now)), ToggleTransition::Activated);
+        state.step(false, input(mode, now));
+        assert_eq!(state.step(true, input(mode, now + Duration::from_millis(100))), ToggleTransition::Reset);
+        assert!(state.is_active());
+    }
+
+    #[test]
+    fn leaving_the_schedule_disarms() {
+        let (mut state, now) = (ToggleState::new(), Instant::now());
+
+        state.step(true, input(ToggleMode::MouseHold, now));
+        let outside = ToggleInput { in_schedule: false, ..input(ToggleMode::MouseHold, now) };
+        assert_eq!(state.step(true, outside), ToggleTransition::ScheduleEnded);
+        assert!(!state.is_active());
+
+        // A press outside the schedule can't arm either.
+        state.step(false, outside);
+        assert_eq!(state.step(true, ToggleInput { now: now + Duration::from_secs(1), ..outside }), ToggleTransition::Unchanged);
+        assert!(!state.is_active());
+    }
+}
@@ -1,69 +1,245 @@
+use crate::config::settings::Settings;
+use crate::input::backend::{backend, HWND};
 use crate::input::handle::Handle;
 use crate::logger::logger::{log_info};
 use std::ptr::null_mut;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use sysinfo::{ProcessesToUpdate, System};
-use winapi::{
-    shared::{minwindef::{DWORD, LPARAM}, windef::HWND},
-    um::winuser::{EnumWindows, GetWindowThreadProcessId, IsWindowVisible},
-};
-use winapi::um::winuser::GetWindowTextW;
 
-struct FindWindowData {
-    pid: DWORD,
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TargetMode {
+    Process,
+    // Skips the window finder; every click goes to the current foreground window.
+    Foreground,
+}
+
+impl TargetMode {
+    pub fn from_setting(value: &str) -> Self {
+        match value {
+            "Foreground" => TargetMode::Foreground,
+            _ => TargetMode::Process,
+        }
+    }
+
+    pub fn as_setting(&self) -> &'static str {
+        match self {
+            TargetMode::Process => "Process",
+            TargetMode::Foreground => "Foreground",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProcessMatchMode {
+    Exact,
+    Contains,
+    StartsWith,
+}
+
+impl ProcessMatchMode {
+    pub fn from_setting(value: &str) -> Self {
+        match value {
+            "Contains" => ProcessMatchMode::Contains,
+            "StartsWith" => ProcessMatchMode::StartsWith,
+            _ => ProcessMatchMode::Exact,
+        }
+    }
+
+    fn matches(&self, process_name: &str, target: &str) -> bool {
+        let process_name = process_name.to_lowercase();
+        let target = target.to_lowercase();
+        match self {
+            ProcessMatchMode::Exact => process_name == target,
+            ProcessMatchMode::Contains => process_name.contains(&target),
+            ProcessMatchMode::StartsWith => process_name.starts_with(&target),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WindowSelectionPolicy {
+    First,
+    Largest,
+    Titled,
+    Foreground,
+    LargestTitled,
+}
+
+impl WindowSelectionPolicy {
+    pub fn from_setting(value: &str) -> Self {
+        match value {
+            "First" => WindowSelectionPolicy::First,
+            "Largest" => WindowSelectionPolicy::Largest,
+            "Titled" => WindowSelectionPolicy::Titled,
+            "Foreground" => WindowSelectionPolicy::Foreground,
+            _ => WindowSelectionPolicy::LargestTitled,
+        }
+    }
+
+    // Candidates arrive topmost first (EnumWindows order on Windows), so ties go to the topmost window.
+    fn select(&self, candidates: &[WindowCandidate]) -> Option<(WindowCandidate, &'static str)> {
+        let first = candidates.first()?;
+        let largest = |windows: &mut dyn Iterator<Item = &WindowCandidate>| {
+            windows.fold(None, |best: Option<&WindowCandidate>, candidate| match best {
+                Some(best) if best.area >= candidate.area => Some(best),
+                _ => Some(candidate),
+            }).copied()
+        };
+
+        match self {
+            WindowSelectionPolicy::First => Some((*first, "first enumerated")),
+            WindowSelectionPolicy::Largest => largest(&mut candidates.iter()).map(|c| (c, "largest client area")),
+            WindowSelectionPolicy::Titled => candidates.iter()
+                .find(|c| c.has_title)
+                .map(|c| (*c, "first with a title"))
+                .or(Some((*first, "no titled window, using first enumerated"))),
+            WindowSelectionPolicy::Foreground => {
+                let foreground = backend().foreground_window();
+                candidates.iter()
+                    .find(|c| c.hwnd == foreground)
+                    .map(|c| (*c, "foreground window"))
+                    .or(Some((*first, "not in foreground, using topmost")))
+            },
+            WindowSelectionPolicy::LargestTitled => largest(&mut candidates.iter().filter(|c| c.has_title))
+                .map(|c| (c, "largest with a title"))
+                .or_else(|| largest(&mut candidates.iter()).map(|c| (c, "no titled window, using largest"))),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct WindowCandidate {
     hwnd: HWND,
+    area: i64,
+    has_title: bool,
+}
+
+struct FindWindowData {
+    pid: u32,
+    candidates: Vec<WindowCandidate>,
     window_count: u32,
     require_visibility: bool,
+    title_filter: String,
 }
 
-unsafe extern "system" fn enum_windows_callback(hwnd: HWND, lparam: LPARAM) -> i32 {
-    let data = &mut *(lparam as *mut FindWindowData);
-    let mut process_id: DWORD = 0;
-    GetWindowThreadProcessId(hwnd, &mut process_id);
+pub struct TargetWindow {
+    pub hwnd: HWND,
+    pub pid: u32,
+    pub process: String,
+    pub title: String,
+}
 
-    if process_id == data.pid {
-        let is_visible = IsWindowVisible(hwnd) != 0;
+fn collect_window_candidates(data: &mut FindWindowData) {
+    let backend = backend();
+    for hwnd in backend.top_level_windows() {
+        if backend.window_pid(hwnd) != data.pid {
+            continue;
+        }
 
-        let mut title: [u16; 512] = [0; 512];
-        let title_len = GetWindowTextW(hwnd, title.as_mut_ptr(), title.len() as i32);
-        let window_title = if title_len > 0 {
-            String::from_utf16_lossy(&title[0..title_len as usize])
-        } else {
-            String::from("[No Title]")
-        };
+        let is_visible = backend.is_window_visible(hwnd);
+
+        let title = backend.window_title(hwnd);
+        let title_len = title.len();
+        let window_title = if title_len > 0 { title } else { String::from("[No Title]") };
 
         log_info(&format!("Found window for PID {}: HWND={:?}, Visible={}, Title='{}'",
                            data.pid, hwnd, is_visible, window_title),
-                  "enum_windows_callback");
+                  "collect_window_candidates");
+
+        data.window_count += 1;
+
+        let title_matches = data.title_filter.is_empty()
+            || (title_len > 0 && window_title.to_lowercase().contains(&data.title_filter));
+
+        if (!data.require_visibility || is_visible) && title_matches {
+            let area = backend.client_size(hwnd)
+                .map(|(width, height)| width as i64 * height as i64)
+                .unwrap_or(0);
 
-        if !data.require_visibility || is_visible {
-            data.hwnd = hwnd;
-            data.window_count += 1;
-            return 1;
+            data.candidates.push(WindowCandidate { hwnd, area, has_title: title_len > 0 });
         }
     }
-    1
 }
 
 pub struct WindowFinder {
-    target_process: String,
+    target_processes: Mutex<Vec<String>>,
     system: Arc<Mutex<System>>,
-    last_found_pid: Option<DWORD>,
-    require_visibility: bool,
+    // 0 means no cached PID; the idle process is never a click target.
+    last_found_pid: AtomicU32,
+    require_visibility: AtomicBool,
+    match_mode: Mutex<ProcessMatchMode>,
+    selection_policy: Mutex<WindowSelectionPolicy>,
+    title_filter: Mutex<String>,
+    child_class: Mutex<String>,
+    pinned_hwnd: AtomicUsize,
 }
 
 impl WindowFinder {
-    pub fn new(target_process: &str) -> Self {
+    pub fn new(target_process: &str, match_mode: ProcessMatchMode, selection_policy: WindowSelectionPolicy, title_filter: &str, child_class: &str, require_visibility: bool) -> Self {
         Self {
-            target_process: target_process.to_string(),
+            target_processes: Mutex::new(Settings::parse_target_processes(target_process)),
             system: Arc::new(Mutex::new(System::new_all())),
-            last_found_pid: None,
-            require_visibility: true,
+            last_found_pid: AtomicU32::new(0),
+            require_visibility: AtomicBool::new(require_visibility),
+            match_mode: Mutex::new(match_mode),
+            selection_policy: Mutex::new(selection_policy),
+            title_filter: Mutex::new(title_filter.trim().to_lowercase()),
+            child_class: Mutex::new(child_class.trim().to_lowercase()),
+            pinned_hwnd: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn pin_window(&self, hwnd: HWND) {
+        self.pinned_hwnd.store(hwnd as usize, Ordering::SeqCst);
+        log_info(&format!("Pinned target window HWND={:?} for this session", hwnd), "WindowFinder::pin_window");
+    }
+
+    pub fn pinned_window(&self) -> Option<HWND> {
+        match self.pinned_hwnd.load(Ordering::SeqCst) {
+            0 => None,
+            hwnd => Some(hwnd as HWND),
+        }
+    }
+
+    pub fn update_title_filter(&self, title_filter: &str) {
+        let title_filter = title_filter.trim().to_lowercase();
+        if let Ok(mut current) = self.title_filter.lock() {
+            *current = title_filter.clone();
+        }
+
+        log_info(&format!("Window title filter set to: '{}'", title_filter), "WindowFinder::update_title_filter");
+    }
+
+    pub fn update_child_class(&self, child_class: &str) {
+        let child_class = child_class.trim().to_lowercase();
+        if let Ok(mut current) = self.child_class.lock() {
+            *current = child_class.clone();
+        }
+
+        log_info(&format!("Child window class filter set to: '{}'", child_class), "WindowFinder::update_child_class");
+    }
+
+    pub fn update_selection_policy(&self, selection_policy: WindowSelectionPolicy) {
+        if let Ok(mut current) = self.selection_policy.lock() {
+            *current = selection_policy;
         }
+
+        log_info(&format!("Window selection policy set to: {:?}", selection_policy), "WindowFinder::update_selection_policy");
+    }
+
+    pub fn update_match_mode(&self, match_mode: ProcessMatchMode) {
+        if let Ok(mut current) = self.match_mode.lock() {
+            *current = match_mode;
+        }
+
+        log_info(&format!("Process match mode set to: {:?}", match_mode), "WindowFinder::update_match_mode");
     }
 
-    pub fn set_require_visibility(&mut self, require: bool) {
-        self.require_visibility = require;
+    // Borderless and exclusive fullscreen games can report their window as not visible; turning this
+    // off accepts them.
+    pub fn set_require_visibility(&self, require: bool) {
+        self.require_visibility.store(require, Ordering::SeqCst);
 
         log_info(&format!("Window visibility requirement set to: {}", require),
                  "WindowFinder::set_require_visibility");
@@ -72,94 +248,193 @@ impl WindowFinder {
 
     pub fn update_target_process(&self, new_target_process: &str) -> bool {
         let context = "WindowFinder::update_target_process";
-        if self.target_process == new_target_process {
+        let new_target_processes = Settings::parse_target_processes(new_target_process);
+        let mut target_processes = self.target_processes.lock().unwrap();
+        if *target_processes == new_target_processes {
             return false;
         }
 
-        unsafe {
-            let self_ptr = self as *const WindowFinder as *mut WindowFinder;
-            (*self_ptr).target_process = new_target_process.to_string();
-            (*self_ptr).last_found_pid = None;
-        }
+        *target_processes = new_target_processes;
+        self.last_found_pid.store(0, Ordering::SeqCst);
 
-        log_info(&format!("Updated target process to: {}", new_target_process), context);
+        log_info(&format!("Updated target processes to: {}", target_processes.join(", ")), context);
         true
     }
 
     pub fn find_target_window(&self, hwnd_handle: &Arc<Mutex<Handle>>) -> Option<HWND> {
         let context = "WindowFinder::find_target_window";
 
-        if let Some(pid) = self.last_found_pid {
-            if let Some(hwnd) = self.find_window_for_pid(pid) {
+        if let Some(pinned) = self.pinned_window() {
+            if backend().is_window(pinned) && (!self.require_visibility.load(Ordering::SeqCst) || backend().is_window_visible(pinned)) {
+                hwnd_handle.lock().unwrap().set(pinned);
+                return Some(pinned);
+            }
+
+            log_info(&format!("Pinned window HWND={:?} is gone, falling back to normal search", pinned), context);
+            self.pinned_hwnd.store(0, Ordering::SeqCst);
+        }
+
+        let last_found_pid = self.last_found_pid.load(Ordering::SeqCst);
+        if last_found_pid != 0 {
+            if let Some(hwnd) = self.find_window_for_pid(last_found_pid) {
                 let mut hwnd_guard = hwnd_handle.lock().unwrap();
                 hwnd_guard.set(hwnd);
                 return Some(hwnd);
             }
         }
 
+        let match_mode = *self.match_mode.lock().unwrap();
+        let target_processes = self.target_processes.lock().unwrap().clone();
+        let mut first_windowless: Option<(&str, u32)> = None;
+
+        for (index, target) in target_processes.iter().enumerate() {
+            let matching_pids = self.find_matching_pids(target, match_mode);
+
+            if matching_pids.len() > 1 {
+                log_info(&format!("{} processes match '{}' ({:?}), preferring one with a window",
+                                  matching_pids.len(), target, match_mode), context);
+            }
+
+            for &pid in &matching_pids {
+                if let Some(hwnd) = self.find_window_for_pid(pid) {
+                    self.last_found_pid.store(pid, Ordering::SeqCst);
+
+                    if target_processes.len() > 1 {
+                        log_info(&format!("Matched target process '{}' (PID: {}), entry {} of {} in the target list",
+                                          target, pid, index + 1, target_processes.len()), context);
+                    }
+
+                    let mut hwnd_guard = hwnd_handle.lock().unwrap();
+                    hwnd_guard.set(hwnd);
+                    return Some(hwnd);
+                }
+            }
+
+            if first_windowless.is_none() {
+                first_windowless = matching_pids.first().map(|&pid| (target.as_str(), pid));
+            }
+        }
+
+        match first_windowless {
+            Some((target, pid)) => {
+                self.last_found_pid.store(pid, Ordering::SeqCst);
+
+                log_info(&format!("Found process '{}' (PID: {}) but it has no visible windows",
+                                  target, pid), context);
+            },
+            None => {
+                log_info(&format!("Process '{}' not found", target_processes.join(", ")), context);
+            }
+        }
+
+        let mut hwnd_guard = hwnd_handle.lock().unwrap();
+        hwnd_guard.set(null_mut());
+        None
+    }
+
+    fn find_matching_pids(&self, target: &str, match_mode: ProcessMatchMode) -> Vec<u32> {
         let mut sys = self.system.lock().unwrap();
         sys.refresh_processes(ProcessesToUpdate::All, false);
 
-        let mut target_pid: Option<DWORD> = None;
+        let mut matching_pids: Vec<u32> = Vec::new();
         for (pid, process) in sys.processes() {
             let name = process.name().to_string_lossy();
-            if name.to_lowercase() == self.target_process.to_lowercase() {
-                target_pid = Some(pid.as_u32());
-                break;
+            if match_mode.matches(&name, target) {
+                matching_pids.push(pid.as_u32());
+                if match_mode == ProcessMatchMode::Exact {
+                    break;
+                }
             }
         }
 
-        drop(sys);
+        matching_pids
+    }
+
+    pub fn list_windows_for_pid(&self, pid: u32) -> Vec<(HWND, String)> {
+        let mut data = FindWindowData {
+            pid,
+            candidates: Vec::new(),
+            window_count: 0,
+            require_visibility: self.require_visibility.load(Ordering::SeqCst),
+            title_filter: self.title_filter.lock().map(|filter| filter.clone()).unwrap_or_default(),
+        };
 
-        if let Some(pid) = target_pid {
-            unsafe {
-                let self_ptr = self as *const WindowFinder as *mut WindowFinder;
-                (*self_ptr).last_found_pid = Some(pid);
+        collect_window_candidates(&mut data);
+
+        data.candidates.iter()
+            .map(|candidate| (candidate.hwnd, backend().window_title(candidate.hwnd)))
+            .collect()
+    }
+
+    pub fn list_target_windows(&self) -> Vec<TargetWindow> {
+        let match_mode = *self.match_mode.lock().unwrap();
+        let mut windows = Vec::new();
+
+        let target_processes = self.target_processes.lock().unwrap().clone();
+        for target in &target_processes {
+            for pid in self.find_matching_pids(target, match_mode) {
+                for (hwnd, title) in self.list_windows_for_pid(pid) {
+                    windows.push(TargetWindow { hwnd, pid, process: target.clone(), title });
+                }
             }
+        }
 
-            if let Some(hwnd) = self.find_window_for_pid(pid) {
-                let mut hwnd_guard = hwnd_handle.lock().unwrap();
-                hwnd_guard.set(hwnd);
-                return Some(hwnd);
-            } else {
-                log_info(&format!("Found process '{}' (PID: {}) but it has no visible windows",
-                                  self.target_process, pid), context);
+        windows
+    }
+
+    // Games and browsers often take input on a render child rather than the frame. With a class filter
+    // set, the first descendant whose class contains it wins; otherwise the top-level window is kept.
+    fn descend_to_child(&self, hwnd: HWND) -> HWND {
+        let context = "WindowFinder::descend_to_child";
+        let backend = backend();
+
+        let child_class = self.child_class.lock().map(|filter| filter.clone()).unwrap_or_default();
+        if child_class.is_empty() {
+            log_info(&format!("Using top-level HWND={:?} (class '{}')", hwnd, backend.window_class(hwnd)), context);
+            return hwnd;
+        }
+
+        let children = backend.child_windows(hwnd);
+        for child in children.iter().copied() {
+            let class = backend.window_class(child);
+            if class.to_lowercase().contains(&child_class) {
+                log_info(&format!("Using child HWND={:?} (class '{}') under top-level HWND={:?}", child, class, hwnd), context);
+                return child;
             }
-        } else {
-            log_info(&format!("Process '{}' not found", self.target_process), context);
         }
 
-        let mut hwnd_guard = hwnd_handle.lock().unwrap();
-        hwnd_guard.set(null_mut());
-        None
+        log_info(&format!("None of the {} child window(s) under HWND={:?} has a class containing '{}', using the top-level window (class '{}')",
+                          children.len(), hwnd, child_class, backend.window_class(hwnd)), context);
+        hwnd
     }
 
-    fn find_window_for_pid(&self, pid: DWORD) -> Option<HWND> {
+    fn find_window_for_pid(&self, pid: u32) -> Option<HWND> {
         let context = "WindowFinder::find_window_for_pid";
 
         log_info(&format!("Looking for {} windows for process PID: {}",
-                          if self.require_visibility { "visible" } else { "any" }, pid), context);
+                          if self.require_visibility.load(Ordering::SeqCst) { "visible" } else { "any" }, pid), context);
 
         let mut data = FindWindowData {
             pid,
-            hwnd: null_mut(),
+            candidates: Vec::new(),
             window_count: 0,
-            require_visibility: self.require_visibility,
+            require_visibility: self.require_visibility.load(Ordering::SeqCst),
+            title_filter: self.title_filter.lock().map(|filter| filter.clone()).unwrap_or_default(),
         };
 
-        unsafe {
-            EnumWindows(Some(enum_windows_callback), &mut data as *mut _ as LPARAM);
-
-            if !data.hwnd.is_null() {
-                log_info(&format!("Found {} window(s) for process PID: {}",
-                                  data.window_count, pid), context);
-                return Some(data.hwnd);
-            } else if data.window_count > 0 {
-                log_info(&format!("Found {} windows for PID: {} but none matched visibility requirements",
-                                  data.window_count, pid), context);
-            } else {
-                log_info(&format!("No windows found for PID: {}", pid), context);
-            }
+        collect_window_candidates(&mut data);
+
+        let policy = *self.selection_policy.lock().unwrap();
+        if let Some((selected, reason)) = policy.select(&data.candidates) {
+            log_info(&format!("Selected HWND={:?} (area={}, titled={}) from {} candidate window(s) for PID {}: {} ({:?})",
+                              selected.hwnd, selected.area, selected.has_title,
+                              data.candidates.len(), pid, reason, policy), context);
+            return Some(self.descend_to_child(selected.hwnd));
+        } else if data.window_count > 0 {
+            log_info(&format!("Found {} windows for PID: {} but none matched visibility or title filter requirements",
+                              data.window_count, pid), context);
+        } else {
+            log_info(&format!("No windows found for PID: {}", pid), context);
         }
 
         None
@@ -1,9 +1,15 @@
+use crate::config::data_dir;
+use crate::config::constants::defaults;
 use chrono::Utc;
 use lazy_static::lazy_static;
+use std::env;
 use std::fs::{self, OpenOptions};
 use std::io::Write;
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub enum LogLevel {
@@ -13,6 +19,23 @@ pub enum LogLevel {
 }
 
 impl LogLevel {
+    fn from_setting(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "info" => Some(LogLevel::Info),
+            "warn" | "warning" => Some(LogLevel::Warning),
+            "error" => Some(LogLevel::Error),
+            _ => None,
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            LogLevel::Info => 0,
+            LogLevel::Warning => 1,
+            LogLevel::Error => 2
+        }
+    }
+
     fn as_str(&self) -> &'static str {
         match self {
             LogLevel::Info => "INFO",
@@ -22,68 +45,258 @@ impl LogLevel {
     }
 }
 
+const LOG_LEVEL_ENV: &str = "RAC_LOG_LEVEL";
+const LOG_BATCH_SIZE: usize = 256;
+const LOG_FLUSH_TIMEOUT: Duration = Duration::from_millis(500);
+
+enum LogCommand {
+    Write(String),
+    Flush(Sender<()>),
+}
+
 lazy_static! {
-    static ref LOGGER: Mutex<Logger> = Mutex::new(Logger::new());
+    static ref LOGGER: Logger = Logger::new();
 }
 
+static LOG_SIZE_EXCEEDED_BYTES: AtomicU64 = AtomicU64::new(0);
+static MIN_LOG_LEVEL: AtomicU8 = AtomicU8::new(0);
+static LOG_LEVEL_FROM_ENV: AtomicBool = AtomicBool::new(false);
+static LOG_MAX_SIZE_BYTES: AtomicU64 = AtomicU64::new(defaults::LOG_MAX_SIZE_MB * 1024 * 1024);
+static LOG_BACKUP_COUNT: AtomicU32 = AtomicU32::new(defaults::LOG_BACKUP_COUNT);
+
+// Callers only format and enqueue; a background thread owns the file so click threads never wait on disk I/O.
 pub struct Logger {
     log_file: PathBuf,
+    sender: Option<Sender<LogCommand>>,
 }
 
 impl Logger {
     fn new() -> Self {
-        let log_path = dirs::data_local_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("RAC")
+        let log_path = data_dir::data_dir()
+            .unwrap_or_else(|e| {
+                eprintln!("Failed to create log directory: {}", e);
+                data_dir::base_dir().join("RAC")
+            })
             .join("logs.txt");
 
-        if let Some(parent) = log_path.parent() {
-            fs::create_dir_all(parent).unwrap_or_else(|e| {
-                eprintln!("Failed to create log directory: {}", e);
-            });
+        if let Some(level) = env::var(LOG_LEVEL_ENV).ok().and_then(|value| LogLevel::from_setting(&value)) {
+            MIN_LOG_LEVEL.store(level.rank(), Ordering::SeqCst);
+            LOG_LEVEL_FROM_ENV.store(true, Ordering::SeqCst);
         }
 
-        Self { log_file: log_path }
+        let (sender, receiver) = mpsc::channel();
+        let writer_path = log_path.clone();
+        let sender = match thread::Builder::new()
+            .name("LoggerThread".to_string())
+            .spawn(move || Self::writer_loop(writer_path, receiver)) {
+            Ok(_) => Some(sender),
+            Err(e) => {
+                eprintln!("Failed to spawn logger thread, writing logs synchronously: {}", e);
+                None
+            }
+        };
+
+        Self { log_file: log_path, sender }
+    }
+
+    fn writer_loop(log_file: PathBuf, receiver: Receiver<LogCommand>) {
+        while let Ok(command) = receiver.recv() {
+            let mut batch = String::new();
+            let mut batched_entries = 0;
+            let mut flush_requests = Vec::new();
+
+            // Drain whatever else is already queued so a burst of messages costs one open/write.
+            let mut next = Some(command);
+            while let Some(command) = next {
+                match command {
+                    LogCommand::Write(entry) => {
+                        batch.push_str(&entry);
+                        batched_entries += 1;
+                    }
+                    LogCommand::Flush(ack) => flush_requests.push(ack),
+                }
+                next = if batched_entries < LOG_BATCH_SIZE { receiver.try_recv().ok() } else { None };
+            }
+
+            if !batch.is_empty() {
+                Self::rotate_if_needed(&log_file, batch.len() as u64);
+                Self::append(&log_file, &batch);
+            }
+            for ack in flush_requests {
+                let _ = ack.send(());
+            }
+        }
+    }
+
+    fn backup_path(log_file: &Path, index: u32) -> PathBuf {
+        let stem = log_file.file_stem().and_then(|stem| stem.to_str()).unwrap_or("logs");
+        log_file.with_file_name(format!("{}.{}.txt", stem, index))
+    }
+
+    // Only the writer thread touches logs.txt, so renaming it here can't race a pending write.
+    fn rotate_if_needed(log_file: &Path, incoming_bytes: u64) {
+        let max_size = LOG_MAX_SIZE_BYTES.load(Ordering::Relaxed);
+        if max_size == 0 {
+            return;
+        }
+
+        let current_size = fs::metadata(log_file).map(|metadata| metadata.len()).unwrap_or(0);
+        if current_size == 0 || current_size + incoming_bytes <= max_size {
+            return;
+        }
+
+        let backups = LOG_BACKUP_COUNT.load(Ordering::Relaxed);
+        let result = if backups == 0 {
+            fs::remove_file(log_file)
+        } else {
+            let _ = fs::remove_file(Self::backup_path(log_file, backups));
+            for index in (1..backups).rev() {
+                let from = Self::backup_path(log_file, index);
+                if from.exists() {
+                    let _ = fs::rename(&from, Self::backup_path(log_file, index + 1));
+                }
+            }
+            fs::rename(log_file, Self::backup_path(log_file, 1))
+        };
+
+        if let Err(e) = result {
+            eprintln!("Failed to rotate log file: {}", e);
+        }
+    }
+
+    fn append(log_file: &Path, contents: &str) {
+        match OpenOptions::new().create(true).append(true).open(log_file) {
+            Ok(mut file) => {
+                if let Err(e) = file.write_all(contents.as_bytes()) {
+                    eprintln!("Failed to write log: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Failed to open log file: {}", e),
+        }
+    }
+
+    fn total_size(&self) -> u64 {
+        let (Some(dir), Some(stem)) = (
+            self.log_file.parent(),
+            self.log_file.file_stem().and_then(|stem| stem.to_str()),
+        ) else {
+            return 0;
+        };
+
+        let Ok(entries) = fs::read_dir(dir) else {
+            return 0;
+        };
+
+        // Counts logs.txt together with any rotated backups sitting next to it.
+        entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with(stem))
+            .filter_map(|entry| entry.metadata().ok())
+            .filter(|metadata| metadata.is_file())
+            .map(|metadata| metadata.len())
+            .sum()
+    }
+
+    fn format_entry(level: &LogLevel, message: &str, context: &str) -> String {
+        let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S");
+        format!(
+            "[{}] [{}] {} in {}\n{}\n{}\n",
+            timestamp,
+            level.as_str(),
+            message,
+            context,
+            "-".repeat(80),
+            ""
+        )
     }
 
     fn write_log(&self, level: LogLevel, message: &str, context: &str) {
-        if let Ok(mut file) = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&self.log_file)
-        {
-            let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S");
-            let log_entry = format!(
-                "[{}] [{}] {} in {}\n{}\n{}\n",
-                timestamp,
-                level.as_str(),
-                message,
-                context,
-                "-".repeat(80),
-                ""
-            );
-
-            if let Err(e) = file.write_all(log_entry.as_bytes()) {
-                eprintln!("Failed to write log: {}", e);
+        if level.rank() < MIN_LOG_LEVEL.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let entry = Self::format_entry(&level, message, context);
+        match &self.sender {
+            Some(sender) => {
+                if let Err(mpsc::SendError(LogCommand::Write(entry))) = sender.send(LogCommand::Write(entry)) {
+                    Self::append(&self.log_file, &entry);
+                }
+            }
+            None => Self::append(&self.log_file, &entry),
+        }
+    }
+
+    // Blocks until everything queued so far is on disk, or the timeout passes.
+    fn flush(&self) {
+        if let Some(sender) = &self.sender {
+            let (ack_sender, ack_receiver) = mpsc::channel();
+            if sender.send(LogCommand::Flush(ack_sender)).is_ok() {
+                let _ = ack_receiver.recv_timeout(LOG_FLUSH_TIMEOUT);
             }
         }
     }
 }
 
 pub fn log_error(error: &str, context: &str) {
-    if let Ok(logger) = LOGGER.lock() {
-        logger.write_log(LogLevel::Error, error, context);
-    }
+    LOGGER.write_log(LogLevel::Error, error, context);
 }
 
 pub fn log_info(message: &str, context: &str) {
-    if let Ok(logger) = LOGGER.lock() {
-        logger.write_log(LogLevel::Info, message, context);
-    }
+    LOGGER.write_log(LogLevel::Info, message, context);
 }
 
 pub fn log_warn(message: &str, context: &str) {
-    if let Ok(logger) = LOGGER.lock() {
-        logger.write_log(LogLevel::Warning, message, context);
+    LOGGER.write_log(LogLevel::Warning, message, context);
+}
+
+// Written synchronously so the message survives even if the process dies right after the hook.
+pub fn log_panic(message: &str, context: &str) {
+    LOGGER.flush();
+    Logger::append(&LOGGER.log_file, &Logger::format_entry(&LogLevel::Error, message, context));
+}
+
+pub fn flush_logs() {
+    LOGGER.flush();
+}
+
+// RAC_LOG_LEVEL takes precedence over the settings value when it is set.
+pub fn set_log_level(level: &str) {
+    if LOG_LEVEL_FROM_ENV.load(Ordering::SeqCst) {
+        return;
+    }
+
+    match LogLevel::from_setting(level) {
+        Some(level) => MIN_LOG_LEVEL.store(level.rank(), Ordering::SeqCst),
+        None => log_warn(&format!("Unknown log level '{}', keeping current level", level), "Logger::set_log_level"),
+    }
+}
+
+pub fn set_log_rotation(max_size_mb: u64, backup_count: u32) {
+    LOG_MAX_SIZE_BYTES.store(max_size_mb.saturating_mul(1024 * 1024), Ordering::SeqCst);
+    LOG_BACKUP_COUNT.store(backup_count, Ordering::SeqCst);
+}
+
+pub fn check_log_size(threshold_mb: u64) {
+    if threshold_mb == 0 {
+        return;
+    }
+
+    let total = LOGGER.total_size();
+
+    if total <= threshold_mb * 1024 * 1024 {
+        return;
+    }
+
+    if LOG_SIZE_EXCEEDED_BYTES.swap(total, Ordering::SeqCst) == 0 {
+        log_warn(&format!("Log files are using {:.1} MB, above the {} MB warning threshold",
+                          total as f64 / (1024.0 * 1024.0), threshold_mb),
+                 "Logger::check_log_size");
+    }
+}
+
+pub fn log_size_warning() -> Option<u64> {
+    match LOG_SIZE_EXCEEDED_BYTES.load(Ordering::SeqCst) {
+        0 => None,
+        total => Some(total),
     }
 }
\ No newline at end of file
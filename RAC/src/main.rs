@@ -1,3 +1,5 @@
+use crate::config::data_dir;
+use crate::config::settings::Settings;
 use crate::input::click_service::{ClickService, ClickServiceConfig};
 use crate::menu::Menu;
 use crate::validation::system_validator::SystemValidator;
@@ -5,13 +7,21 @@ use crate::validation::system_validator::SystemValidator;
 #[cfg(not(debug_assertions))]
 use debugoff;
 use std::error::Error;
+use crate::logger::logger::{flush_logs, log_error, log_info, log_panic};
 use std::io;
-use std::sync::Arc;
-use tokio;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+#[cfg(windows)]
 use windows::core::{w, BOOL, PCSTR};
-use windows::Win32::Foundation::{GetLastError, ERROR_ALREADY_EXISTS};
+#[cfg(windows)]
+use windows::Win32::Foundation::{CloseHandle, GetLastError, ERROR_ALREADY_EXISTS, HANDLE};
+#[cfg(windows)]
+use windows::Win32::System::Console::{SetConsoleCtrlHandler, CTRL_BREAK_EVENT, CTRL_CLOSE_EVENT, CTRL_C_EVENT, CTRL_LOGOFF_EVENT, CTRL_SHUTDOWN_EVENT};
+#[cfg(windows)]
 use windows::Win32::System::Diagnostics::Debug::{CheckRemoteDebuggerPresent, IsDebuggerPresent};
+#[cfg(windows)]
 use windows::Win32::System::Threading::{CreateMutexW, GetCurrentProcess};
+#[cfg(windows)]
 use windows::Win32::UI::WindowsAndMessaging::FindWindowA;
 use crate::input::click_executor::ClickExecutor;
 
@@ -20,46 +30,302 @@ pub mod input;
 pub mod menu;
 pub mod validation;
 mod logger;
+#[cfg(windows)]
 mod auth;
 
-pub struct ClickServiceMenu {
-    click_service: Arc<ClickService>,
-    click_executor: Arc<ClickExecutor>,
-}
+static PANIC_CLICK_SERVICE: OnceLock<Arc<ClickService>> = OnceLock::new();
+// Holds the named mutex handle on Windows and the lock file descriptor + 1 elsewhere; 0 means none.
+static INSTANCE_MUTEX: AtomicUsize = AtomicUsize::new(0);
+static IN_PANIC_HOOK: AtomicBool = AtomicBool::new(false);
 
-impl ClickServiceMenu {
-    pub fn new(click_service: Arc<ClickService>, click_executor: Arc<ClickExecutor>) -> Self {
-        Self {
-            click_service,
-            click_executor,
-        }
+fn initialize_services() -> Result<(), String> {
+    log_info(&format!("Using data directory {}", data_dir::describe()), "initialize_services");
+
+    if let Err(e) = Settings::upgrade_file() {
+        log_error(&format!("Failed to upgrade settings file: {}", e), "initialize_services");
     }
-}
 
-fn initialize_services() -> Result<(), String> {
     let validator = SystemValidator::new();
     let validation_result = validator.validate_system();
     if !validation_result.is_valid {
+        if let Some(error) = &validation_result.error {
+            log_error(&format!("System validation failed: {}", error), "initialize_services");
+        }
         return Err(validation_result.message.unwrap_or_else(|| "Unknown validation error".to_string()));
     }
 
     Ok(())
 }
 
+const USAGE: &str = "Usage: RAC [--hwnd <handle>] [--profile <name>] [--cps <n>] [--click-mode left|right|both] [--toggle-key <vk>] [--start] [--no-color]
+
+  --hwnd <handle>        Target a specific window handle (decimal or 0x hex)
+  --profile <name>       Switch to a saved profile before starting
+  --cps <n>              Max CPS for both buttons (1-50)
+  --click-mode <mode>    left, right or both
+  --toggle-key <vk>      Toggle key virtual-key code (decimal or 0x hex)
+  --start                Skip the menu and start clicking immediately
+  --no-color             Plain menu output (also set by a non-empty NO_COLOR)
+
+--profile, --cps, --click-mode and --toggle-key require --start and are saved to settings.
+With no arguments the interactive menu is shown.";
+
+#[derive(Default)]
+struct CliOptions {
+    target_hwnd: Option<usize>,
+    profile: Option<String>,
+    cps: Option<u8>,
+    click_mode: Option<String>,
+    toggle_key: Option<i32>,
+    start: bool,
+    no_color: bool,
+}
+
+impl CliOptions {
+    fn has_setting_overrides(&self) -> bool {
+        self.profile.is_some() || self.cps.is_some() || self.click_mode.is_some() || self.toggle_key.is_some()
+    }
+}
+
+fn parse_number(value: &str) -> Option<usize> {
+    match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => value.parse::<usize>().ok(),
+    }
+}
+
+fn parse_cli_args(args: &[String]) -> Result<CliOptions, String> {
+    let mut options = CliOptions::default();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().cloned().ok_or_else(|| format!("Missing value for {}", arg));
+
+        match arg.as_str() {
+            "--hwnd" => {
+                let value = value()?;
+                match parse_number(&value) {
+                    Some(0) | None => return Err(format!("Invalid --hwnd value: {}", value)),
+                    Some(hwnd) => options.target_hwnd = Some(hwnd),
+                }
+            },
+            "--profile" => {
+                let value = value()?;
+                if value.trim().is_empty() {
+                    return Err("Profile name cannot be empty".to_string());
+                }
+                options.profile = Some(value);
+            },
+            "--cps" => {
+                let value = value()?;
+                match value.parse::<u8>() {
+                    Ok(cps) if ClickExecutor::is_valid_cps(cps) => options.cps = Some(cps),
+                    _ => return Err(format!("Invalid --cps value: {}", value)),
+                }
+            },
+            "--click-mode" => {
+                let value = value()?;
+                let click_mode = match value.to_lowercase().as_str() {
+                    "left" => "LeftClick",
+                    "right" => "RightClick",
+                    "both" => "Both",
+                    _ => return Err(format!("Invalid --click-mode value: {}", value)),
+                };
+                options.click_mode = Some(click_mode.to_string());
+            },
+            "--toggle-key" => {
+                let value = value()?;
+                match parse_number(&value) {
+                    Some(vk) if vk > 0 && vk <= 0xFE => options.toggle_key = Some(vk as i32),
+                    _ => return Err(format!("Invalid --toggle-key value: {}", value)),
+                }
+            },
+            "--start" => options.start = true,
+            "--no-color" => options.no_color = true,
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+
+    if options.has_setting_overrides() && !options.start {
+        return Err("--profile, --cps, --click-mode and --toggle-key can only be used with --start".to_string());
+    }
+
+    Ok(options)
+}
+
+fn apply_cli_settings(options: &CliOptions) -> Result<(), String> {
+    let mut settings = match &options.profile {
+        Some(name) => Settings::load_profile(name).map_err(|e| format!("Failed to load profile '{}': {}", name, e))?,
+        None => Settings::load().unwrap_or_default(),
+    };
+
+    if let Some(cps) = options.cps {
+        settings.left_max_cps = cps;
+        settings.right_max_cps = cps;
+    }
+    if let Some(click_mode) = &options.click_mode {
+        settings.click_mode = click_mode.clone();
+    }
+    if let Some(toggle_key) = options.toggle_key {
+        settings.toggle_key = toggle_key;
+    }
+
+    if settings.toggle_key == 0 {
+        return Err("No toggle key configured; pass --toggle-key or set one in the menu first".to_string());
+    }
+
+    settings.save().map_err(|e| format!("Failed to save settings: {}", e))
+}
+
+#[cfg(windows)]
 fn check_single_instance() -> bool {
     unsafe {
         let mutex_name = w!("Global\\RACApplicationMutex");
-        CreateMutexW(None, true, mutex_name).expect("TODO: panic message");
+        let handle = CreateMutexW(None, true, mutex_name).expect("TODO: panic message");
+        INSTANCE_MUTEX.store(handle.0 as usize, Ordering::SeqCst);
         GetLastError() != ERROR_ALREADY_EXISTS
     }
 }
 
+// An flock on a file in the data directory stands in for the named mutex; the kernel drops it if we crash.
+#[cfg(not(windows))]
+fn check_single_instance() -> bool {
+    use std::os::unix::io::IntoRawFd;
+
+    let Ok(data_dir) = Settings::get_data_dir() else {
+        return true;
+    };
+    let file = match std::fs::OpenOptions::new().create(true).truncate(false).write(true).open(data_dir.join("rac.lock")) {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("Failed to open instance lock file: {}", e);
+            return true;
+        }
+    };
+
+    let fd = file.into_raw_fd();
+    INSTANCE_MUTEX.store(fd as usize + 1, Ordering::SeqCst);
+    unsafe { libc::flock(fd, libc::LOCK_EX | libc::LOCK_NB) == 0 }
+}
+
+#[cfg(windows)]
+fn release_instance_mutex() {
+    let handle = INSTANCE_MUTEX.swap(0, Ordering::SeqCst);
+    if handle != 0 {
+        unsafe {
+            let _ = CloseHandle(HANDLE(handle as *mut _));
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn release_instance_mutex() {
+    let fd = INSTANCE_MUTEX.swap(0, Ordering::SeqCst);
+    if fd != 0 {
+        unsafe {
+            libc::close((fd - 1) as i32);
+        }
+    }
+}
+
+fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        if IN_PANIC_HOOK.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let _ = std::panic::catch_unwind(|| {
+            if let Some(click_service) = PANIC_CLICK_SERVICE.get() {
+                click_service.emergency_stop();
+            }
+        });
+
+        let location = info
+            .location()
+            .map(|location| format!("{}:{}", location.file(), location.line()))
+            .unwrap_or_else(|| "unknown location".to_string());
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "non-string panic payload".to_string());
+        let thread_name = std::thread::current().name().unwrap_or("unnamed").to_string();
+
+        log_panic(&format!("Panic in thread '{}' at {}: {}", thread_name, location, message), "panic_hook");
+
+        if thread_name == "main" {
+            release_instance_mutex();
+        }
+
+        default_hook(info);
+        IN_PANIC_HOOK.store(false, Ordering::SeqCst);
+    }));
+}
+
+fn shutdown_after_console_event(event: &str) -> ! {
+    if let Some(click_service) = PANIC_CLICK_SERVICE.get() {
+        click_service.emergency_stop();
+        click_service.release_all_holds();
+    }
+    log_info(&format!("Console control event {} received, clicking disabled before exit", event), "console_ctrl_handler");
+    flush_logs();
+    release_instance_mutex();
+    std::process::exit(0);
+}
+
+// Runs on a system thread when the console gets Ctrl+C/Ctrl+Break or is closed. Raw mode in the
+// run loop turns Ctrl+C into a key event instead, which the run loop handles like Ctrl+Q.
+#[cfg(windows)]
+unsafe extern "system" fn console_ctrl_handler(ctrl_type: u32) -> BOOL {
+    match ctrl_type {
+        CTRL_C_EVENT | CTRL_BREAK_EVENT | CTRL_CLOSE_EVENT | CTRL_LOGOFF_EVENT | CTRL_SHUTDOWN_EVENT => {
+            shutdown_after_console_event(&ctrl_type.to_string())
+        }
+        _ => BOOL(0),
+    }
+}
+
+#[cfg(windows)]
+fn install_console_ctrl_handler() {
+    if let Err(e) = unsafe { SetConsoleCtrlHandler(Some(console_ctrl_handler), true) } {
+        eprintln!("Failed to install console control handler: {}", e);
+    }
+}
+
+// SIGHUP is what a closing terminal sends, the counterpart of CTRL_CLOSE_EVENT.
+#[cfg(not(windows))]
+fn install_console_ctrl_handler() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let signals = [
+        ("SIGINT", SignalKind::interrupt()),
+        ("SIGTERM", SignalKind::terminate()),
+        ("SIGHUP", SignalKind::hangup()),
+    ];
+    for (name, kind) in signals {
+        match signal(kind) {
+            Ok(mut stream) => {
+                tokio::spawn(async move {
+                    if stream.recv().await.is_some() {
+                        shutdown_after_console_event(name);
+                    }
+                });
+            }
+            Err(e) => eprintln!("Failed to install {} handler: {}", name, e),
+        }
+    }
+}
+
 #[cfg(target_os = "windows")]
 fn check_debugger() -> bool {
     use windows::Win32::System::Diagnostics::Debug::IsDebuggerPresent;
     unsafe { IsDebuggerPresent().as_bool() }
 }
 
+#[cfg(windows)]
 pub fn check_debugger_presence() -> bool {
     unsafe {
         if IsDebuggerPresent().as_bool() {
@@ -106,6 +372,23 @@ pub fn check_debugger_presence() -> bool {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
+    install_panic_hook();
+    install_console_ctrl_handler();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    let cli_options = match parse_cli_args(&args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+    menu::style::init(cli_options.no_color);
+
     if !check_single_instance() {
         eprintln!("Application is already running!");
         println!("\nPress Enter to exit...");
@@ -118,20 +401,42 @@ async fn main() -> Result<(), Box<dyn Error>> {
     #[cfg(not(debug_assertions))]
     debugoff::multi_ptraceme_or_die();
 
+    #[cfg(windows)]
     if check_debugger_presence() {
         std::process::exit(1);
     }
 
+    #[cfg(target_os = "windows")]
     if check_debugger() {
         std::process::exit(1);
     }
 
     match initialize_services() {
         Ok(()) => {
-            let click_service = Arc::new(ClickService::new(ClickServiceConfig::default()));
+            let mut config = ClickServiceConfig::default();
+            if let Some(target_hwnd) = cli_options.target_hwnd {
+                config.target_hwnd = Some(target_hwnd);
+            }
+
+            if cli_options.start {
+                if let Err(e) = apply_cli_settings(&cli_options) {
+                    eprintln!("{}\n\n{}", e, USAGE);
+                    release_instance_mutex();
+                    std::process::exit(2);
+                }
+            }
+
+            let click_service = Arc::new(ClickService::new(config));
+            let _ = PANIC_CLICK_SERVICE.set(Arc::clone(&*click_service));
             let click_executor = Arc::clone(&click_service.click_executor);
             let mut menu = Menu::new(Arc::clone(&click_service), click_executor);
-            menu.show_main_menu();
+            if cli_options.start {
+                menu.run_unattended();
+            } else {
+                menu.show_main_menu();
+            }
+            release_instance_mutex();
+            flush_logs();
         }
         Err(error_message) => {
             eprintln!("System validation failed: {}", error_message);
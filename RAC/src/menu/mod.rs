@@ -1,29 +1,135 @@
+pub(crate) mod style;
+
+#[cfg(windows)]
+use crate::auth::license_error::LicenseError;
+#[cfg(windows)]
+use crate::auth::license_keys::{PROTECTED_ENCRYPTION, PROTECTED_PUBLIC, XOR_KEY};
+#[cfg(windows)]
+use crate::auth::license_validator::LicenseValidator;
+use crate::config::constants::limits;
 use crate::config::settings::Settings;
+use crate::input::backend::backend;
+use crate::input::benchmark;
 use crate::input::click_service::ClickService;
-use crate::input::click_executor::{ClickExecutor, GameMode, MouseButton};
-use crate::logger::logger::{log_error, log_info};
+use crate::input::click_executor::{ClickExecutor, GameMode, MouseButton, TimingMode};
+use crate::input::click_profile::{ClickProfile, MIN_CLICK_PROFILE_SAMPLES};
+use crate::input::raw_input::RawInputListener;
+use crate::input::window_finder::TargetMode;
+use crate::input::toggle_state::{KeyStateSource, PressedKeys, SystemKeyState, ToggleInput, ToggleMode, ToggleState, ToggleTransition};
+use crate::logger::logger::{check_log_size, log_error, log_info, log_size_warning, log_warn};
+use crate::validation::anti_cheat_detector::AntiCheatDetector;
+use std::collections::VecDeque;
 use std::io::{self, Write};
+use std::path::Path;
 use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
-use windows::core::PCSTR;
-use windows::Win32::System::Console::SetConsoleTitleA;
-use windows::Win32::UI::Input::KeyboardAndMouse::GetAsyncKeyState;
 use crossterm::event::{self, Event, KeyCode, KeyEvent};
-use crossterm::terminal::{enable_raw_mode, disable_raw_mode, Clear, ClearType};
+use crossterm::terminal::{enable_raw_mode, disable_raw_mode, Clear, ClearType, SetTitle};
 use crossterm::execute;
+#[cfg(windows)]
+use chrono::DateTime;
+use chrono::Utc;
 
-#[derive(Clone, Copy, PartialEq)]
-enum ToggleMode {
-    MouseHold,
-    KeyboardHold,
-}
+const CLICK_PROFILE_RECORD_SECS: u64 = 30;
+const BENCHMARK_DURATION: Duration = Duration::from_secs(10);
+const TEST_CLICK_COUNT: u32 = 5;
+const MAX_TEST_CLICK_COUNT: u32 = 50;
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum ClickMode {
     LeftClick,
     RightClick,
-    Both
+    Both,
+    // Middle and side buttons are clicked by the left executor.
+    ExtraButton(MouseButton),
+}
+
+impl ClickMode {
+    fn from_setting(value: &str) -> Self {
+        match value {
+            "RightClick" => ClickMode::RightClick,
+            "Both" => ClickMode::Both,
+            "MiddleClick" => ClickMode::ExtraButton(MouseButton::Middle),
+            "X1Click" => ClickMode::ExtraButton(MouseButton::X1),
+            "X2Click" => ClickMode::ExtraButton(MouseButton::X2),
+            _ => ClickMode::LeftClick,
+        }
+    }
+
+    fn buttons(&self) -> Vec<MouseButton> {
+        match self {
+            ClickMode::LeftClick => vec![MouseButton::Left],
+            ClickMode::RightClick => vec![MouseButton::Right],
+            ClickMode::Both => vec![MouseButton::Left, MouseButton::Right],
+            ClickMode::ExtraButton(button) => vec![*button],
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            ClickMode::LeftClick => "LEFT CLICK",
+            ClickMode::RightClick => "RIGHT CLICK",
+            ClickMode::Both => "BOTH BUTTONS",
+            ClickMode::ExtraButton(MouseButton::Middle) => "MIDDLE CLICK",
+            ClickMode::ExtraButton(MouseButton::X1) => "SIDE BUTTON 1 (X1)",
+            ClickMode::ExtraButton(MouseButton::X2) => "SIDE BUTTON 2 (X2)",
+            ClickMode::ExtraButton(_) => "LEFT CLICK",
+        }
+    }
+}
+
+// Both mode with distinct per-button keys arms each button on its own; everything else listens to one key.
+#[derive(Clone, Copy, PartialEq)]
+enum ToggleKeys {
+    Shared(i32),
+    Split { left: i32, right: i32 },
+}
+
+impl ToggleKeys {
+    fn for_mode(click_mode: ClickMode, settings: &Settings) -> Self {
+        let left = settings.effective_left_toggle_key();
+        let right = settings.effective_right_toggle_key();
+        match click_mode {
+            ClickMode::Both if left != right => ToggleKeys::Split { left, right },
+            ClickMode::RightClick => ToggleKeys::Shared(right),
+            _ => ToggleKeys::Shared(left),
+        }
+    }
+
+    fn is_configured(&self) -> bool {
+        match *self {
+            ToggleKeys::Shared(key) => key != 0,
+            ToggleKeys::Split { left, right } => left != 0 && right != 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum HotkeyTarget {
+    Shared,
+    Left,
+    Right,
+    Panic,
+    CpsUp,
+    CpsDown,
+    SpamKey,
+    SpamToggle,
+}
+
+impl HotkeyTarget {
+    fn label(&self) -> &'static str {
+        match self {
+            HotkeyTarget::Shared => "Hotkey",
+            HotkeyTarget::Left => "Left toggle",
+            HotkeyTarget::Right => "Right toggle",
+            HotkeyTarget::Panic => "Panic key",
+            HotkeyTarget::CpsUp => "CPS up key",
+            HotkeyTarget::CpsDown => "CPS down key",
+            HotkeyTarget::SpamKey => "Key to spam",
+            HotkeyTarget::SpamToggle => "Key spam activation key",
+        }
+    }
 }
 
 pub struct Menu {
@@ -33,6 +139,55 @@ pub struct Menu {
     toggle_mode: ToggleMode,
     click_mode: ClickMode,
     settings: Settings,
+    anti_cheat_acknowledged: bool,
+}
+
+// Clicks counted over a trailing one second window, sampled from the executor's running total.
+struct ClickRate {
+    samples: VecDeque<(Instant, usize)>,
+}
+
+impl ClickRate {
+    fn new() -> Self {
+        Self { samples: VecDeque::new() }
+    }
+
+    fn sample(&mut self, total: usize) -> f64 {
+        let now = Instant::now();
+        self.samples.push_back((now, total));
+        while self.samples.len() > 1 && now.duration_since(self.samples[0].0) > Duration::from_secs(1) {
+            self.samples.pop_front();
+        }
+
+        let (oldest_at, oldest_total) = self.samples[0];
+        let elapsed = now.duration_since(oldest_at).as_secs_f64();
+        if elapsed <= 0.0 {
+            return 0.0;
+        }
+        total.saturating_sub(oldest_total) as f64 / elapsed
+    }
+}
+
+// Stops a run once it has gone on too long or clicked too often. Zero disables either limit.
+struct SessionLimit {
+    max_secs: u64,
+    max_clicks: u64,
+}
+
+impl SessionLimit {
+    fn from_settings(settings: &Settings) -> Self {
+        Self { max_secs: settings.max_session_secs, max_clicks: settings.max_session_clicks }
+    }
+
+    fn exceeded(&self, elapsed: Duration, clicks: usize) -> Option<String> {
+        if self.max_secs > 0 && elapsed.as_secs() >= self.max_secs {
+            return Some(format!("session time limit of {}s reached", self.max_secs));
+        }
+        if self.max_clicks > 0 && clicks as u64 >= self.max_clicks {
+            return Some(format!("session click limit of {} reached", self.max_clicks));
+        }
+        None
+    }
 }
 
 impl Menu {
@@ -44,17 +199,11 @@ impl Menu {
                 log_info("Loaded existing configuration", context);
 
                 let left_executor = click_service.get_left_click_executor();
-                let left_mode = match s.left_game_mode.as_str() {
-                    "Combo" => GameMode::Combo,
-                    _ => GameMode::Default, 
-                };
+                let left_mode = GameMode::from_setting(&s.left_game_mode);
                 left_executor.set_game_mode(left_mode);
                 
                 let right_executor = click_service.get_right_click_executor();
-                let right_mode = match s.right_game_mode.as_str() {
-                    "Combo" => GameMode::Combo,
-                    _ => GameMode::Default,
-                };
+                let right_mode = GameMode::from_setting(&s.right_game_mode);
                 right_executor.set_game_mode(right_mode);
 
                 s
@@ -69,11 +218,14 @@ impl Menu {
             click_service,
             click_executor,
             toggle_key: settings.toggle_key,
-            toggle_mode: if settings.keyboard_hold_mode { ToggleMode::KeyboardHold } else { ToggleMode::MouseHold },
+            toggle_mode: ToggleMode::from_keyboard_hold(settings.keyboard_hold_mode),
             click_mode: ClickMode::LeftClick,
             settings,
+            anti_cheat_acknowledged: false,
         };
 
+        check_log_size(menu.settings.log_size_warning_mb);
+
         menu.start_toggle_monitor();
 
         log_info("Menu initialized successfully", context);
@@ -81,7 +233,7 @@ impl Menu {
     }
 
     fn clear_console(&self) {
-        if let Err(_) = execute!(io::stdout(), Clear(ClearType::All)) {
+        if execute!(io::stdout(), Clear(ClearType::All)).is_err() {
             print!("\x1B[2J\x1B[3J\x1B[1;1H");
         }
         
@@ -99,11 +251,14 @@ impl Menu {
         let context = "Menu::configure_toggle_mode";
 
         self.clear_console();
-        println!("=== Toggle Mode Configuration ===");
+        style::header("Toggle Mode Configuration");
         println!("Select how you want to activate clicking:");
         println!("1. Mouse Hold Mode (Default) - Press toggle key to enable, then HOLD LEFT MOUSE BUTTON to click");
         println!("2. Keyboard Hold Mode - HOLD TOGGLE KEY to click");
-        println!("3. Back to Main Menu");
+        println!("3. Anti-AFK Mode - Press toggle key, then RAC clicks once every {}s (currently: {})",
+                 self.settings.antiafk_interval_secs,
+                 style::state(self.settings.antiafk_enabled));
+        println!("4. Back to Main Menu");
         print!("\nSelect option: ");
 
         if let Err(e) = io::stdout().flush() {
@@ -120,21 +275,12 @@ impl Menu {
         match choice.trim() {
             "1" => {
                 self.toggle_mode = ToggleMode::MouseHold;
-                let settings = match Settings::load() {
-                    Ok(mut s) => {
-                        s.keyboard_hold_mode = false;
-                        s
-                    },
-                    Err(_) => {
-                        let mut s = Settings::default();
-                        s.keyboard_hold_mode = false;
-                        s
-                    }
-                };
+                let mut settings = Settings::load().unwrap_or_default();
+                settings.keyboard_hold_mode = false;
 
                 if let Err(e) = settings.save() {
                     log_error(&format!("Failed to save settings: {}", e), context);
-                    println!("Failed to save settings! Press Enter to continue...");
+                    style::error("Failed to save settings! Press Enter to continue...");
                 } else {
                     println!("Mouse Hold Mode enabled! Press Enter to continue...");
                 }
@@ -143,31 +289,52 @@ impl Menu {
             },
             "2" => {
                 self.toggle_mode = ToggleMode::KeyboardHold;
-                let settings = match Settings::load() {
-                    Ok(mut s) => {
-                        s.keyboard_hold_mode = true;
-                        s
-                    },
-                    Err(_) => {
-                        let mut s = Settings::default();
-                        s.keyboard_hold_mode = true;
-                        s
-                    }
-                };
+                let mut settings = Settings::load().unwrap_or_default();
+                settings.keyboard_hold_mode = true;
 
                 if let Err(e) = settings.save() {
                     log_error(&format!("Failed to save settings: {}", e), context);
-                    println!("Failed to save settings! Press Enter to continue...");
+                    style::error("Failed to save settings! Press Enter to continue...");
                 } else {
                     println!("Keyboard Hold Mode enabled! Press Enter to continue...");
                 }
                 let mut _input = String::new();
                 let _ = io::stdin().read_line(&mut _input);
             },
-            "3" => return,
+            "3" => {
+                let mut settings = Settings::load().unwrap_or_default();
+                if settings.antiafk_enabled {
+                    settings.antiafk_enabled = false;
+                } else {
+                    let input = Self::prompt_line(&format!("Click interval in seconds (current: {}, randomized by +/-20%): ", settings.antiafk_interval_secs));
+                    if !input.is_empty() {
+                        match input.parse::<u64>() {
+                            Ok(secs) if secs > 0 => settings.antiafk_interval_secs = secs,
+                            _ => style::error(&format!("Invalid interval, keeping {}s", settings.antiafk_interval_secs)),
+                        }
+                    }
+                    settings.antiafk_enabled = true;
+                }
+
+                self.settings.antiafk_enabled = settings.antiafk_enabled;
+                self.settings.antiafk_interval_secs = settings.antiafk_interval_secs;
+                self.click_service.set_antiafk(settings.antiafk_enabled, settings.antiafk_interval_secs);
+
+                if let Err(e) = settings.save() {
+                    log_error(&format!("Failed to save settings: {}", e), context);
+                    style::error("Failed to save settings! Press Enter to continue...");
+                } else if settings.antiafk_enabled {
+                    println!("Anti-AFK Mode enabled! Press Enter to continue...");
+                } else {
+                    println!("Anti-AFK Mode disabled! Press Enter to continue...");
+                }
+                let mut _input = String::new();
+                let _ = io::stdin().read_line(&mut _input);
+            },
+            "4" => {}
             _ => {
                 log_error("Invalid toggle mode option selected", context);
-                println!("\nInvalid option! Press Enter to continue...");
+                style::error("\nInvalid option! Press Enter to continue...");
                 let mut _input = String::new();
                 let _ = io::stdin().read_line(&mut _input);
             }
@@ -178,11 +345,19 @@ impl Menu {
         let context = "Menu::configure_click_mode";
 
         self.clear_console();
-        println!("=== Click Mode Configuration ===");
+        style::header("Click Mode Configuration");
         println!("1. Left Click Mode");
         println!("2. Right Click Mode");
         println!("3. Both (Left and Right)");
-        println!("4. Back to Main Menu");
+        println!("4. Middle Click Mode");
+        println!("5. Side Button 1 (X1) Mode");
+        println!("6. Side Button 2 (X2) Mode");
+        println!("7. Hold Mode (hold the button down instead of clicking): {}", style::state(self.settings.hold_mode));
+        println!("8. Double Click (send a double-click each activation, max {} CPS): {}", self.settings.double_click_max_cps,
+                 style::state(self.settings.double_click));
+        println!("9. Independent Button Hold (in Both mode each button clicks only while its own toggle key is held): {}",
+                 style::state(self.settings.independent_button_hold));
+        println!("10. Back to Main Menu");
         print!("\nSelect option: ");
 
         if let Err(e) = io::stdout().flush() {
@@ -201,16 +376,13 @@ impl Menu {
                 self.click_mode = ClickMode::LeftClick;
                 self.click_executor.set_mouse_button(MouseButton::Left);
 
-                let mut settings = match Settings::load() {
-                    Ok(s) => s,
-                    Err(_) => Settings::default(),
-                };
+                let mut settings = Settings::load().unwrap_or_default();
 
                 settings.click_mode = "LeftClick".to_string();
 
                 if let Err(e) = settings.save() {
                     log_error(&format!("Failed to save settings: {}", e), context);
-                    println!("Failed to save settings! Press Enter to continue...");
+                    style::error("Failed to save settings! Press Enter to continue...");
                 } else {
                     println!("Left Click Mode enabled! Press Enter to continue...");
                 }
@@ -222,16 +394,13 @@ impl Menu {
                 self.click_mode = ClickMode::RightClick;
                 self.click_executor.set_mouse_button(MouseButton::Right);
 
-                let mut settings = match Settings::load() {
-                    Ok(s) => s,
-                    Err(_) => Settings::default(),
-                };
+                let mut settings = Settings::load().unwrap_or_default();
 
                 settings.click_mode = "RightClick".to_string();
 
                 if let Err(e) = settings.save() {
                     log_error(&format!("Failed to save settings: {}", e), context);
-                    println!("Failed to save settings! Press Enter to continue...");
+                    style::error("Failed to save settings! Press Enter to continue...");
                 } else {
                     println!("Right Click Mode enabled! Press Enter to continue...");
                 }
@@ -244,16 +413,13 @@ impl Menu {
                 self.click_executor.set_mouse_button(MouseButton::Left);
                 self.click_executor.set_mouse_button(MouseButton::Right);
 
-                let mut settings = match Settings::load() {
-                    Ok(s) => s,
-                    Err(_) => Settings::default(),
-                };
+                let mut settings = Settings::load().unwrap_or_default();
 
                 settings.click_mode = "Both".to_string();
 
                 if let Err(e) = settings.save() {
                     log_error(&format!("Failed to save settings: {}", e), context);
-                    println!("Failed to save settings! Press Enter to continue...");
+                    style::error("Failed to save settings! Press Enter to continue...");
                 } else {
                     println!("Both Click Mode enabled! Press Enter to continue...");
                 }
@@ -261,31 +427,129 @@ impl Menu {
                 let mut _input = String::new();
                 let _ = io::stdin().read_line(&mut _input);
             },
-            "4" => return,
+            "4" => self.select_extra_button_mode(MouseButton::Middle, "MiddleClick"),
+            "5" => self.select_extra_button_mode(MouseButton::X1, "X1Click"),
+            "6" => self.select_extra_button_mode(MouseButton::X2, "X2Click"),
+            "7" => {
+                let mut settings = Settings::load().unwrap_or_default();
+
+                settings.hold_mode = !settings.hold_mode;
+                self.settings.hold_mode = settings.hold_mode;
+                self.click_service.get_left_click_executor().set_hold_mode(settings.hold_mode);
+                self.click_service.get_right_click_executor().set_hold_mode(settings.hold_mode);
+
+                if let Err(e) = settings.save() {
+                    log_error(&format!("Failed to save settings: {}", e), context);
+                    style::error("Failed to save settings! Press Enter to continue...");
+                } else {
+                    println!("Hold Mode {}! Press Enter to continue...", if settings.hold_mode { "enabled" } else { "disabled" });
+                }
+
+                let mut _input = String::new();
+                let _ = io::stdin().read_line(&mut _input);
+            },
+            "8" => {
+                let mut settings = Settings::load().unwrap_or_default();
+
+                settings.double_click = !settings.double_click;
+                if settings.double_click {
+                    if let Some(max_cps) = Self::prompt_max_cps("Double Click", settings.double_click_max_cps) {
+                        settings.double_click_max_cps = max_cps;
+                    }
+                }
+                self.settings.double_click = settings.double_click;
+                self.settings.double_click_max_cps = settings.double_click_max_cps;
+                for executor in [self.click_service.get_left_click_executor(), self.click_service.get_right_click_executor()] {
+                    executor.set_double_click(settings.double_click, settings.double_click_max_cps);
+                }
+
+                if let Err(e) = settings.save() {
+                    log_error(&format!("Failed to save settings: {}", e), context);
+                    style::error("Failed to save settings! Press Enter to continue...");
+                } else {
+                    println!("Double Click {}! Press Enter to continue...", if settings.double_click { "enabled" } else { "disabled" });
+                }
+
+                let mut _input = String::new();
+                let _ = io::stdin().read_line(&mut _input);
+            },
+            "9" => {
+                let mut settings = Settings::load().unwrap_or_default();
+
+                settings.independent_button_hold = !settings.independent_button_hold;
+                self.settings.independent_button_hold = settings.independent_button_hold;
+                self.click_service.set_coupled_button_hold(settings.couples_button_hold());
+
+                if let Err(e) = settings.save() {
+                    log_error(&format!("Failed to save settings: {}", e), context);
+                    style::error("Failed to save settings! Press Enter to continue...");
+                } else {
+                    println!("Independent Button Hold {}! Press Enter to continue...",
+                             if settings.independent_button_hold { "enabled" } else { "disabled" });
+                }
+
+                let mut _input = String::new();
+                let _ = io::stdin().read_line(&mut _input);
+            },
+            "10" => {},
             _ => {
                 log_error("Invalid click mode option selected", context);
-                println!("\nInvalid option! Press Enter to continue...");
+                style::error("\nInvalid option! Press Enter to continue...");
             }
         }
     }
 
+    fn select_extra_button_mode(&mut self, button: MouseButton, setting: &str) {
+        let context = "Menu::select_extra_button_mode";
+
+        self.click_mode = ClickMode::ExtraButton(button);
+        self.click_executor.set_mouse_button(button);
+
+        let mut settings = Settings::load().unwrap_or_default();
+
+        settings.click_mode = setting.to_string();
+
+        if let Err(e) = settings.save() {
+            log_error(&format!("Failed to save settings: {}", e), context);
+            style::error("Failed to save settings! Press Enter to continue...");
+        } else {
+            println!("{} mode enabled (uses left click timing)! Press Enter to continue...", self.click_mode.description());
+        }
+
+        let mut _input = String::new();
+        let _ = io::stdin().read_line(&mut _input);
+    }
+
     pub fn show_main_menu(&mut self) {
         let context = "Menu::show_main_menu";
         loop {
-            unsafe {
-                SetConsoleTitleA(PCSTR::from_raw("RAC Menu\0".as_ptr())).expect("TODO: panic message");
-            }
+            Self::set_console_title(&self.build_console_title());
 
             self.clear_console();
 
-            println!("=== RAC Menu ===");
+            check_log_size(self.settings.log_size_warning_mb);
+
+            style::header("RAC Menu");
+            if self.click_service.take_window_lost_notice() {
+                style::warning("Notice: clicking was stopped because the target window closed or was hidden. Start RAC again to resume.");
+            }
+            if let Some(total) = log_size_warning() {
+                style::warning(&format!("Warning: log files are using {:.1} MB (threshold {} MB)",
+                                        total as f64 / (1024.0 * 1024.0), self.settings.log_size_warning_mb));
+            }
             println!("1. Configure Hotkey");
             println!("2. Start RAC");
             println!("3. Show Current Settings");
             println!("4. Configure Advanced Settings");
             println!("5. Configure Toggle Mode");
             println!("6. Configure Click Mode");
-            println!("7. Exit");
+            println!("7. Select Target Window");
+            println!("8. Manage Profiles");
+            println!("9. License Info");
+            println!("10. Benchmark Click Rate");
+            println!("11. Key Spam");
+            println!("12. Send Test Clicks");
+            println!("13. Exit");
             print!("\nSelect option: ");
 
             if let Err(e) = io::stdout().flush() {
@@ -307,10 +571,19 @@ impl Menu {
                 "4" => self.configure_advanced_settings(),
                 "5" => self.configure_toggle_mode(),
                 "6" => self.configure_click_mode(),
-                "7" => self.perform_clean_exit(),
+                "7" => self.select_target_window(),
+                "8" => self.manage_profiles(),
+                "9" => self.show_license_info(),
+                "10" => self.run_benchmark(),
+                "11" => self.configure_key_spam(),
+                "12" => self.send_test_clicks(),
+                "13" => {
+                    self.perform_clean_exit();
+                    return;
+                },
                 _ => {
                     log_error("Invalid menu option selected", context);
-                    println!("\nInvalid option! Press Enter to continue...");
+                    style::error("\nInvalid option! Press Enter to continue...");
                     let mut _input = String::new();
                     let _ = io::stdin().read_line(&mut _input);
                 }
@@ -318,115 +591,158 @@ impl Menu {
         }
     }
 
-    fn perform_clean_exit(&self) {
-        let context = "Menu::perform_clean_exit";
-        log_info("Performing clean exit...", context);
-
-        if self.click_service.is_enabled() {
-            log_info("Disabling active click service before exit", context);
-            self.click_service.toggle();
-
-            thread::sleep(Duration::from_millis(100));
-        }
-
-        log_info("Clean exit completed, terminating process", context);
-
-        std::process::exit(0);
-    }
-
-    fn configure_hotkey(&mut self) {
-        let context = "Menu::configure_hotkey";
-
-        self.clear_console();
-        println!("=== Hotkey Configuration ===");
-        println!("1. Configure Mouse Button");
-        println!("2. Configure Keyboard Key");
-        println!("3. Back to Main Menu");
-        print!("\nSelect option: ");
+    fn configure_key_spam(&mut self) {
+        let context = "Menu::configure_key_spam";
 
-        if let Err(e) = io::stdout().flush() {
-            log_error(&format!("Failed to flush stdout: {}", e), context);
-            return;
-        }
+        loop {
+            self.clear_console();
+            style::header("Key Spam");
+            println!("Repeats a keyboard key into the target window while its activation key arms it.");
+            println!("It runs alongside mouse clicking, and only while RAC is started.\n");
+
+            let mut settings = Settings::load().unwrap_or_else(|_| Settings::default_with_toggle_key(self.toggle_key));
+            println!("1. Key Spam (currently: {})", style::state(settings.key_spam_enabled));
+            println!("2. Key to Spam (currently: {})", Self::optional_key_name(settings.key_spam_key));
+            println!("3. Spam Rate (currently: {} CPS)", settings.key_spam_cps);
+            println!("4. Activation Key (currently: {})", Self::optional_key_name(settings.key_spam_toggle_key));
+            println!("5. Activation Mode (currently: {})", if settings.key_spam_hold_mode { "hold to spam" } else { "press to toggle" });
+            println!("6. Back to Main Menu");
+
+            match Self::prompt_line("\nSelect option: ").as_str() {
+                "1" => {
+                    settings.key_spam_enabled = !settings.key_spam_enabled;
+                    if settings.key_spam_enabled && (settings.key_spam_key == 0 || settings.key_spam_toggle_key == 0) {
+                        style::warning("Set both the key to spam and an activation key before key spam can run.");
+                    }
+                },
+                "2" => {
+                    self.configure_keyboard_hotkey(HotkeyTarget::SpamKey);
+                    continue;
+                },
+                "3" => match Self::prompt_max_cps("Key Spam", settings.key_spam_cps) {
+                    Some(cps) => settings.key_spam_cps = cps,
+                    None => continue,
+                },
+                "4" => {
+                    match Self::prompt_line("1. Mouse Button\n2. Keyboard Key\nSelect option: ").as_str() {
+                        "1" => self.configure_mouse_hotkey(HotkeyTarget::SpamToggle),
+                        "2" => self.configure_keyboard_hotkey(HotkeyTarget::SpamToggle),
+                        _ => {}
+                    }
 
-        let mut choice = String::new();
-        if let Err(e) = io::stdin().read_line(&mut choice) {
-            log_error(&format!("Failed to read user input: {}", e), context);
-            return;
-        }
+                    // Sharing a key with a click toggle would arm both at once.
+                    let settings = Settings::load().unwrap_or_default();
+                    let spam_toggle = settings.key_spam_toggle_key;
+                    if spam_toggle != 0 && [settings.toggle_key, settings.left_toggle_key, settings.right_toggle_key, settings.panic_key].contains(&spam_toggle) {
+                        style::warning(&format!("{} is also a click or panic hotkey, so it will trigger both. Press Enter to continue...",
+                                                Self::get_key_name(spam_toggle)));
+                        let mut _input = String::new();
+                        let _ = io::stdin().read_line(&mut _input);
+                    }
+                    continue;
+                },
+                "5" => settings.key_spam_hold_mode = !settings.key_spam_hold_mode,
+                "6" => return,
+                _ => {
+                    style::error("Invalid option. Press Enter to continue...");
+                    let mut _input = String::new();
+                    let _ = io::stdin().read_line(&mut _input);
+                    continue;
+                }
+            }
 
-        match choice.trim() {
-            "1" => self.configure_mouse_hotkey(),
-            "2" => self.configure_keyboard_hotkey(),
-            "3" => return,
-            _ => {
-                log_error("Invalid hotkey configuration option selected", context);
-                println!("\nInvalid option! Press Enter to continue...");
+            if let Err(e) = settings.save() {
+                log_error(&format!("Failed to save settings: {}", e), context);
+                style::error("Failed to save settings! Press Enter to continue...");
                 let mut _input = String::new();
                 let _ = io::stdin().read_line(&mut _input);
             }
         }
     }
 
-    fn configure_keyboard_hotkey(&mut self) {
-        let context = "Menu::configure_keyboard_hotkey";
-
+    fn send_test_clicks(&self) {
         self.clear_console();
-        println!("=== Keyboard Hotkey Configuration ===");
-        println!("\nPress any key (A-Z) to set as hotkey...");
-
-        if let Err(e) = io::stdout().flush() {
-            log_error(&format!("Failed to flush stdout: {}", e), context);
-            return;
-        }
+        style::header("Send Test Clicks");
 
-        if let Err(e) = enable_raw_mode() {
-            log_error(&format!("Failed to enable raw mode: {}", e), context);
+        let settings = Settings::load().unwrap_or_default();
+        if TargetMode::from_setting(&settings.target_mode) == TargetMode::Foreground {
+            style::warning("Target Window is set to the foreground window, which right now is this console.");
+            style::warning("Switch it back to the target process in Advanced Settings to test clicks.");
+            println!("\nPress Enter to continue...");
+            let mut _input = String::new();
+            let _ = io::stdin().read_line(&mut _input);
             return;
         }
 
-        let start_time = Instant::now();
-        let timeout = Duration::from_secs(30);
-        let mut input_received = false;
-
-        while start_time.elapsed() < timeout && !input_received {
-            if event::poll(Duration::from_millis(100)).unwrap_or(false) {
-                if let Ok(Event::Key(KeyEvent { code, .. })) = event::read() {
-                    if let KeyCode::Char(c) = code {
-                        if c.is_ascii_alphabetic() {
-                            let virtual_key = c.to_ascii_uppercase() as i32;
-
-                            self.toggle_key = virtual_key;
-                            let settings = match Settings::load() {
-                                Ok(mut s) => {
-                                    s.toggle_key = self.toggle_key;
-                                    s
-                                },
-                                Err(_) => Settings::default_with_toggle_key(self.toggle_key),
-                            };
+        println!("Sends a few real clicks to '{}' to check RAC can reach it.", settings.target_process);
+        println!("They land wherever your Click Region points, so make sure that's somewhere harmless.\n");
+        let input = Self::prompt_line(&format!("Clicks per button (1-{}, Enter for {}): ", MAX_TEST_CLICK_COUNT, TEST_CLICK_COUNT));
+        let count = if input.is_empty() {
+            TEST_CLICK_COUNT
+        } else {
+            match input.parse::<u32>() {
+                Ok(count) if (1..=MAX_TEST_CLICK_COUNT).contains(&count) => count,
+                _ => {
+                    style::error(&format!("Enter a whole number from 1 to {}. Press Enter to continue...", MAX_TEST_CLICK_COUNT));
+                    let mut _input = String::new();
+                    let _ = io::stdin().read_line(&mut _input);
+                    return;
+                }
+            }
+        };
 
-                            if let Err(e) = settings.save() {
-                                log_error(&format!("Failed to save settings: {}", e), context);
-                            } else {
-                                println!("\nHotkey successfully set to: {}", Self::get_key_name(virtual_key));
-                                println!("To change the hotkey, return to the main menu and configure again.");
-                            }
-                            input_received = true;
-                        } else {
-                            println!("\nInvalid key! Please press a letter key (A-Z)...");
-                            thread::sleep(Duration::from_secs(2));
-                            disable_raw_mode().unwrap_or(());
-                            return;
-                        }
+        for button in self.click_mode.buttons() {
+            match self.click_service.send_test_clicks(button, count) {
+                None => {
+                    style::error(&format!("\nNo window found for '{}'.", settings.target_process));
+                    println!("Check that the game is running, the target process name is right and, if set, the window title filter matches.");
+                    break;
+                },
+                Some((hwnd, delivered)) => {
+                    let title = backend().window_title(hwnd);
+                    let summary = format!("{:?}: {}/{} clicks delivered to {} (HWND {:?})",
+                                          button, delivered, count, if title.is_empty() { "[No Title]" } else { &title }, hwnd);
+                    if delivered == count {
+                        style::success(&summary);
+                    } else if delivered == 0 {
+                        style::error(&summary);
+                        println!("The window refused every click. Try another Click Method in Advanced Settings.");
+                    } else {
+                        style::warning(&summary);
                     }
                 }
             }
         }
 
-        let _ = disable_raw_mode();
+        println!("\nPress Enter to continue...");
+        let mut _input = String::new();
+        let _ = io::stdin().read_line(&mut _input);
+    }
 
-        if !input_received {
-            println!("\nTimeout reached! No key was pressed within {} seconds.", timeout.as_secs());
+    fn run_benchmark(&self) {
+        self.clear_console();
+        style::header("Click Rate Benchmark");
+        println!("Clicks a stand-in target for {}s per button with your current settings.", BENCHMARK_DURATION.as_secs());
+        println!("Nothing is sent to the game, so it doesn't need to be running.\n");
+
+        let settings = Settings::load().unwrap_or_default();
+        for button in self.click_mode.buttons() {
+            println!("Benchmarking {:?}...", button);
+            let _ = io::stdout().flush();
+
+            let result = benchmark::run(&settings, button, BENCHMARK_DURATION);
+            let floor_cps = 1_000_000.0 / result.min_click_delay_micros.max(1) as f64;
+            println!("  Target:   {:.1} CPS", result.target_cps);
+            println!("  Achieved: {:.2} CPS ({} clicks in {:.1}s)", result.achieved_cps(), result.stats.clicks, result.elapsed.as_secs_f64());
+            println!("  Interval: min {:.2}ms, mean {:.2}ms, max {:.2}ms",
+                     result.stats.min_delay_micros as f64 / 1000.0,
+                     result.stats.mean_delay_micros as f64 / 1000.0,
+                     result.stats.max_delay_micros as f64 / 1000.0);
+            if floor_cps < result.target_cps {
+                println!("  Note: the {:.1}ms minimum click delay caps this button at {:.1} CPS.",
+                         result.min_click_delay_micros as f64 / 1000.0, floor_cps);
+            }
+            println!();
         }
 
         println!("Press Enter to continue...");
@@ -434,37 +750,520 @@ impl Menu {
         let _ = io::stdin().read_line(&mut _input);
     }
 
-    fn configure_mouse_hotkey(&mut self) {
-        let context = "Menu::configure_mouse_hotkey";
+    fn select_target_window(&mut self) {
+        let context = "Menu::select_target_window";
+
         self.clear_console();
-        println!("=== Mouse Hotkey Configuration ===");
-        println!("\nPress any mouse button to set as hotkey...");
+        style::header("Select Target Window");
+
+        let windows = self.click_service.list_target_windows();
+        if windows.is_empty() {
+            println!("No windows found for '{}'. Press Enter to continue...", self.settings.target_process);
+            let mut _input = String::new();
+            let _ = io::stdin().read_line(&mut _input);
+            return;
+        }
+
+        for (index, window) in windows.iter().enumerate() {
+            let title = if window.title.is_empty() { "[No Title]" } else { &window.title };
+            println!("{}. {} (PID {}, HWND {:?}) - {}", index + 1, window.process, window.pid, window.hwnd, title);
+        }
+        println!("{}. Back to Main Menu", windows.len() + 1);
+        print!("\nSelect window: ");
 
         if let Err(e) = io::stdout().flush() {
             log_error(&format!("Failed to flush stdout: {}", e), context);
             return;
         }
 
-        let mut mouse_key = 0;
-        let button_codes = [
-            0x01, 0x02, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C,
-            0xA0, 0xA1, 0xA2, 0xA3, 0xA4, 0xA5, 0xA6, 0xA7,
-            0xA8, 0xA9, 0xAA, 0xAB,
-            0xAD, 0xAE, 0xAF, 0xB0, 0xB1, 0xB2, 0xB3
-        ];
-
-        let start_time = Instant::now();
-        let timeout = Duration::from_secs(30);
+        let mut choice = String::new();
+        if let Err(e) = io::stdin().read_line(&mut choice) {
+            log_error(&format!("Failed to read user input: {}", e), context);
+            return;
+        }
+
+        match choice.trim().parse::<usize>() {
+            Ok(index) if (1..=windows.len()).contains(&index) => {
+                let window = &windows[index - 1];
+                self.click_service.pin_target_window(window.hwnd);
+                println!("Pinned HWND {:?} for this session. Press Enter to continue...", window.hwnd);
+            },
+            Ok(index) if index == windows.len() + 1 => return,
+            _ => style::error("Invalid option! Press Enter to continue..."),
+        }
+
+        let mut _input = String::new();
+        let _ = io::stdin().read_line(&mut _input);
+    }
+
+    fn manage_profiles(&mut self) {
+        let context = "Menu::manage_profiles";
+
+        loop {
+            self.clear_console();
+            style::header("Profiles");
+
+            let active = if self.settings.active_profile.is_empty() { "(none)" } else { &self.settings.active_profile };
+            println!("Active profile: {}", active);
+
+            let profiles = Settings::list_profiles().unwrap_or_else(|e| {
+                log_error(&format!("Failed to list profiles: {}", e), context);
+                Vec::new()
+            });
+            if profiles.is_empty() {
+                println!("No saved profiles");
+            } else {
+                for (index, name) in profiles.iter().enumerate() {
+                    println!("  {}. {}", index + 1, name);
+                }
+            }
+
+            println!("\n1. Create Profile From Current Settings");
+            println!("2. Switch Profile");
+            println!("3. Delete Profile");
+            println!("4. Export Settings To File");
+            println!("5. Import Settings From File");
+            println!("6. Back to Main Menu");
+            print!("\nSelect option: ");
+
+            if let Err(e) = io::stdout().flush() {
+                log_error(&format!("Failed to flush stdout: {}", e), context);
+            }
+
+            let mut choice = String::new();
+            if let Err(e) = io::stdin().read_line(&mut choice) {
+                log_error(&format!("Failed to read user input: {}", e), context);
+                continue;
+            }
+
+            match choice.trim() {
+                "1" => {
+                    let name = Self::prompt_line("Profile name: ");
+                    let mut settings = Settings::load().unwrap_or_else(|_| self.settings.clone());
+                    match settings.save_as(&name) {
+                        Ok(()) => {
+                            settings.active_profile = name.trim().to_string();
+                            if let Err(e) = settings.save() {
+                                log_error(&format!("Failed to save settings: {}", e), context);
+                            }
+                            self.settings = settings;
+                            println!("Profile created.");
+                        },
+                        Err(e) => style::error(&format!("Failed to create profile: {}", e)),
+                    }
+                },
+                "2" => {
+                    let name = Self::prompt_profile_name(&profiles);
+                    match Settings::load_profile(&name) {
+                        Ok(settings) => {
+                            if let Err(e) = settings.save() {
+                                log_error(&format!("Failed to save settings: {}", e), context);
+                            }
+                            self.apply_profile(settings);
+                            println!("Switched to profile '{}'.", self.settings.active_profile);
+                        },
+                        Err(e) => style::error(&format!("Failed to switch profile: {}", e)),
+                    }
+                },
+                "3" => {
+                    let name = Self::prompt_profile_name(&profiles);
+                    match Settings::delete_profile(&name) {
+                        Ok(()) => {
+                            if self.settings.active_profile == name.trim() {
+                                let mut settings = Settings::load().unwrap_or_else(|_| self.settings.clone());
+                                settings.active_profile.clear();
+                                if let Err(e) = settings.save() {
+                                    log_error(&format!("Failed to save settings: {}", e), context);
+                                }
+                                self.settings = settings;
+                            }
+                            println!("Profile deleted.");
+                        },
+                        Err(e) => style::error(&format!("Failed to delete profile: {}", e)),
+                    }
+                },
+                "4" => {
+                    let path = Self::prompt_line("Export file path: ");
+                    let path = path.trim_matches('"');
+                    let settings = Settings::load().unwrap_or_else(|_| self.settings.clone());
+                    match settings.export_to(Path::new(path)) {
+                        Ok(()) => println!("Settings exported to {}.", path),
+                        Err(e) => style::error(&format!("Failed to export settings: {}", e)),
+                    }
+                },
+                "5" => {
+                    let path = Self::prompt_line("Import file path: ");
+                    let mut settings = Settings::load().unwrap_or_else(|_| self.settings.clone());
+                    match settings.import_from(Path::new(path.trim_matches('"'))) {
+                        Ok(()) => {
+                            if let Err(e) = settings.save() {
+                                log_error(&format!("Failed to save settings: {}", e), context);
+                            }
+                            self.apply_profile(settings);
+                            println!("Settings imported.");
+                        },
+                        Err(e) => style::error(&format!("Failed to import settings: {}", e)),
+                    }
+                },
+                "6" => return,
+                _ => style::error("Invalid option!"),
+            }
+
+            println!("Press Enter to continue...");
+            let mut _input = String::new();
+            let _ = io::stdin().read_line(&mut _input);
+        }
+    }
+
+    // Re-prompts until the value is within the CPS limits; an empty line keeps the current value.
+    fn prompt_max_cps(label: &str, current: u8) -> Option<u8> {
+        loop {
+            let input = Self::prompt_line(&format!("Enter {} Max CPS ({}-{}) (current: {}): ",
+                                                   label, limits::MIN_CPS, limits::MAX_CPS, current));
+            if input.is_empty() {
+                return None;
+            }
+
+            match input.parse::<u8>() {
+                Ok(value) if ClickExecutor::is_valid_cps(value) => return Some(value),
+                _ => println!("Max CPS must be a whole number from {} to {}.", limits::MIN_CPS, limits::MAX_CPS),
+            }
+        }
+    }
+
+    fn prompt_line(prompt: &str) -> String {
+        print!("{}", prompt);
+        let _ = io::stdout().flush();
+
+        let mut input = String::new();
+        let _ = io::stdin().read_line(&mut input);
+        input.trim().to_string()
+    }
+
+    fn prompt_profile_name(profiles: &[String]) -> String {
+        let input = Self::prompt_line("Profile number or name: ");
+        match input.parse::<usize>() {
+            Ok(index) if (1..=profiles.len()).contains(&index) => profiles[index - 1].clone(),
+            _ => input,
+        }
+    }
+
+    fn apply_profile(&mut self, settings: Settings) {
+        self.click_service.set_left_click_cps(settings.left_max_cps);
+        self.click_service.set_right_click_cps(settings.right_max_cps);
+        self.click_service.get_left_click_executor().set_game_mode(GameMode::from_setting(&settings.left_game_mode));
+        self.click_service.get_right_click_executor().set_game_mode(GameMode::from_setting(&settings.right_game_mode));
+
+        self.toggle_key = settings.toggle_key;
+        self.toggle_mode = ToggleMode::from_keyboard_hold(settings.keyboard_hold_mode);
+        self.click_mode = ClickMode::from_setting(&settings.click_mode);
+        self.settings = settings;
+    }
+
+    fn build_console_title(&self) -> String {
+        if !self.settings.dynamic_console_title {
+            return "RAC Menu".to_string();
+        }
+
+        let snapshot = self.click_service.snapshot();
+
+        if !snapshot.left_active && !snapshot.right_active {
+            return "RAC [idle]".to_string();
+        }
+
+        let left_cps = if snapshot.left_active { snapshot.left_max_cps } else { 0 };
+        let right_cps = if snapshot.right_active { snapshot.right_max_cps } else { 0 };
+
+        format!("RAC [ARMED] L:{} R:{} CPS", left_cps, right_cps)
+    }
+
+    fn set_console_title(title: &str) {
+        if let Err(e) = execute!(io::stdout(), SetTitle(title)) {
+            log_error(&format!("Failed to set console title: {}", e), "Menu::set_console_title");
+        }
+    }
+
+    pub fn run_unattended(&mut self) {
+        log_info("Starting from command line, skipping menu", "Menu::run_unattended");
+        self.start_auto_clicker();
+        self.perform_clean_exit();
+    }
+
+    fn show_license_info(&self) {
+        self.clear_console();
+        style::header("License Info");
+
+        let warning_days = Settings::load().map(|settings| settings.license_warning_days).unwrap_or(self.settings.license_warning_days);
+        Self::print_license_info(warning_days);
+
+        println!("\nPress Enter to continue...");
+        let mut _input = String::new();
+        let _ = io::stdin().read_line(&mut _input);
+    }
+
+    #[cfg(windows)]
+    fn print_license_info(warning_days: u64) {
+        let context = "Menu::print_license_info";
+        let validator = match LicenseValidator::new(XOR_KEY.to_vec(), PROTECTED_PUBLIC.to_vec(), PROTECTED_ENCRYPTION.to_vec()) {
+            Ok(validator) => validator,
+            Err(e) => {
+                log_error(&format!("Failed to initialize license validator: {}", e), context);
+                style::error(&format!("Could not determine this machine's ID: {}", e));
+                return;
+            }
+        };
+
+        let machine_id = validator.get_current_machine_id();
+        println!("Machine ID: {}", machine_id);
+
+        let info = match validator.get_license_info() {
+            Ok(info) => info,
+            Err(LicenseError::NotFound) => {
+                println!("\nNo license file found.");
+                println!("Place {}.license in {} to activate RAC.", machine_id, validator.get_license_dir());
+                return;
+            }
+            Err(e) => {
+                log_error(&format!("Failed to read license info: {}", e), context);
+                style::error(&format!("\nThe license file could not be read: {}", e));
+                return;
+            }
+        };
+
+        if info.machine_id == machine_id {
+            println!("Licensed machine ID: {} (matches this machine)", info.machine_id);
+        } else {
+            style::warning(&format!("Licensed machine ID: {} (does NOT match this machine)", info.machine_id));
+        }
+
+        let expires = DateTime::from_timestamp(info.expires_at, 0)
+            .map(|expires| expires.format("%Y-%m-%d %H:%M UTC").to_string())
+            .unwrap_or_else(|| info.expires_at.to_string());
+        println!("Expires: {}", expires);
+
+        let remaining_secs = info.expires_at - Utc::now().timestamp();
+        if remaining_secs <= 0 {
+            style::warning("Time remaining: expired, RAC will exit at the next license check");
+            return;
+        }
+
+        let remaining = format!("Time remaining: {} day(s) {} hour(s)", remaining_secs / 86_400, (remaining_secs % 86_400) / 3_600);
+        if (remaining_secs as u64) < warning_days.saturating_mul(86_400) {
+            style::warning(&format!("{} - renew soon, less than {} day(s) left", remaining, warning_days));
+        } else {
+            println!("{}", remaining);
+        }
+    }
+
+    #[cfg(not(windows))]
+    fn print_license_info(_warning_days: u64) {
+        println!("Licensing is only enforced on Windows builds; there is nothing to show here.");
+    }
+
+    fn perform_clean_exit(&self) {
+        let context = "Menu::perform_clean_exit";
+        log_info("Performing clean exit...", context);
+
+        if self.click_service.is_enabled() {
+            log_info("Disabling active click service before exit", context);
+            self.click_service.toggle();
+
+            thread::sleep(Duration::from_millis(100));
+        }
+
+        self.click_service.shutdown();
+
+        log_info("Clean exit completed", context);
+    }
+
+    fn configure_hotkey(&mut self) {
+        let context = "Menu::configure_hotkey";
+
+        self.clear_console();
+        style::header("Hotkey Configuration");
+        let settings = Settings::load().unwrap_or_default();
+        println!("1. Configure Mouse Button");
+        println!("2. Configure Keyboard Key");
+        println!("3. Configure Left Toggle (currently: {})", Self::button_toggle_name(settings.left_toggle_key));
+        println!("4. Configure Right Toggle (currently: {})", Self::button_toggle_name(settings.right_toggle_key));
+        println!("5. Configure Panic Key (currently: {})", Self::optional_key_name(settings.panic_key));
+        println!("6. Configure CPS Up Key (currently: {})", Self::optional_key_name(settings.cps_up_key));
+        println!("7. Configure CPS Down Key (currently: {})", Self::optional_key_name(settings.cps_down_key));
+        println!("8. Back to Main Menu");
+        print!("\nSelect option: ");
+
+        if let Err(e) = io::stdout().flush() {
+            log_error(&format!("Failed to flush stdout: {}", e), context);
+            return;
+        }
+
+        let mut choice = String::new();
+        if let Err(e) = io::stdin().read_line(&mut choice) {
+            log_error(&format!("Failed to read user input: {}", e), context);
+            return;
+        }
+
+        match choice.trim() {
+            "1" => self.configure_mouse_hotkey(HotkeyTarget::Shared),
+            "2" => self.configure_keyboard_hotkey(HotkeyTarget::Shared),
+            "3" => self.configure_button_toggle(HotkeyTarget::Left),
+            "4" => self.configure_button_toggle(HotkeyTarget::Right),
+            "5" => self.configure_keyboard_hotkey(HotkeyTarget::Panic),
+            "6" => self.configure_keyboard_hotkey(HotkeyTarget::CpsUp),
+            "7" => self.configure_keyboard_hotkey(HotkeyTarget::CpsDown),
+            "8" => {},
+            _ => {
+                log_error("Invalid hotkey configuration option selected", context);
+                style::error("\nInvalid option! Press Enter to continue...");
+                let mut _input = String::new();
+                let _ = io::stdin().read_line(&mut _input);
+            }
+        }
+    }
+
+    fn configure_button_toggle(&mut self, target: HotkeyTarget) {
+        let context = "Menu::configure_button_toggle";
+
+        self.clear_console();
+        style::header(&format!("{} Configuration", target.label()));
+        println!("An unset per-button key follows the main hotkey. In Both mode, different");
+        println!("left and right keys arm each button independently.");
+        println!("\n1. Configure Mouse Button");
+        println!("2. Configure Keyboard Key");
+        println!("3. Clear (use main hotkey)");
+        println!("4. Back");
+
+        match Self::prompt_line("\nSelect option: ").as_str() {
+            "1" => self.configure_mouse_hotkey(target),
+            "2" => self.configure_keyboard_hotkey(target),
+            "3" => {
+                if let Err(e) = self.save_hotkey(target, 0) {
+                    log_error(&format!("Failed to save settings: {}", e), context);
+                } else {
+                    println!("{} cleared, it now follows the main hotkey.", target.label());
+                }
+                println!("Press Enter to continue...");
+                let mut _input = String::new();
+                let _ = io::stdin().read_line(&mut _input);
+            },
+            _ => {}
+        }
+    }
+
+    fn button_toggle_name(key: i32) -> String {
+        if key == 0 { "Same as hotkey".to_string() } else { Self::get_key_name(key) }
+    }
+
+    fn optional_key_name(key: i32) -> String {
+        if key == 0 { "Disabled".to_string() } else { Self::get_key_name(key) }
+    }
+
+    fn toggle_keys_name(toggle_keys: ToggleKeys) -> String {
+        match toggle_keys {
+            ToggleKeys::Shared(key) => Self::get_key_name(key),
+            ToggleKeys::Split { left, right } => format!("{} (left) / {} (right)", Self::get_key_name(left), Self::get_key_name(right)),
+        }
+    }
+
+    fn save_hotkey(&mut self, target: HotkeyTarget, virtual_key: i32) -> io::Result<()> {
+        let mut settings = Settings::load().unwrap_or_else(|_| Settings::default_with_toggle_key(self.toggle_key));
+        match target {
+            HotkeyTarget::Shared => {
+                self.toggle_key = virtual_key;
+                settings.toggle_key = virtual_key;
+            },
+            HotkeyTarget::Left => settings.left_toggle_key = virtual_key,
+            HotkeyTarget::Right => settings.right_toggle_key = virtual_key,
+            HotkeyTarget::Panic => settings.panic_key = virtual_key,
+            HotkeyTarget::CpsUp => settings.cps_up_key = virtual_key,
+            HotkeyTarget::CpsDown => settings.cps_down_key = virtual_key,
+            HotkeyTarget::SpamKey => settings.key_spam_key = virtual_key,
+            HotkeyTarget::SpamToggle => settings.key_spam_toggle_key = virtual_key,
+        }
+        settings.save()
+    }
+
+    fn configure_keyboard_hotkey(&mut self, target: HotkeyTarget) {
+        let context = "Menu::configure_keyboard_hotkey";
+
+        self.clear_console();
+        style::header("Keyboard Hotkey Configuration");
+        println!("\nPress a letter (A-Z), digit (0-9), function key (F1-F12), arrow, navigation or +/- key to set as {}...", target.label().to_lowercase());
+
+        if let Err(e) = io::stdout().flush() {
+            log_error(&format!("Failed to flush stdout: {}", e), context);
+            return;
+        }
+
+        if let Err(e) = enable_raw_mode() {
+            log_error(&format!("Failed to enable raw mode: {}", e), context);
+            return;
+        }
+
+        let start_time = Instant::now();
+        let timeout = Duration::from_secs(30);
+        let mut input_received = false;
+
+        while start_time.elapsed() < timeout && !input_received {
+            if event::poll(Duration::from_millis(100)).unwrap_or(false) {
+                if let Ok(Event::Key(KeyEvent { code, .. })) = event::read() {
+                    if let Some(virtual_key) = Self::key_code_to_virtual_key(code) {
+                        if let Err(e) = self.save_hotkey(target, virtual_key) {
+                            log_error(&format!("Failed to save settings: {}", e), context);
+                        } else {
+                            style::success(&format!("\n{} successfully set to: {}", target.label(), Self::get_key_name(virtual_key)));
+                            println!("To change the hotkey, return to the main menu and configure again.");
+                        }
+                        input_received = true;
+                    } else {
+                        style::error("\nInvalid key! Please press a letter, digit, F1-F12, arrow, navigation or +/- key...");
+                        thread::sleep(Duration::from_secs(2));
+                        disable_raw_mode().unwrap_or(());
+                        return;
+                    }
+                }
+            }
+        }
+
+        let _ = disable_raw_mode();
+
+        if !input_received {
+            println!("\nTimeout reached! No key was pressed within {} seconds.", timeout.as_secs());
+        }
+
+        println!("Press Enter to continue...");
+        let mut _input = String::new();
+        let _ = io::stdin().read_line(&mut _input);
+    }
+
+    fn configure_mouse_hotkey(&mut self, target: HotkeyTarget) {
+        let context = "Menu::configure_mouse_hotkey";
+        self.clear_console();
+        style::header("Mouse Hotkey Configuration");
+        println!("\nPress any mouse button to set as hotkey...");
+
+        if let Err(e) = io::stdout().flush() {
+            log_error(&format!("Failed to flush stdout: {}", e), context);
+            return;
+        }
+
+        let mut mouse_key = 0;
+        let button_codes = [
+            0x01, 0x02, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C,
+            0xA0, 0xA1, 0xA2, 0xA3, 0xA4, 0xA5, 0xA6, 0xA7,
+            0xA8, 0xA9, 0xAA, 0xAB,
+            0xAD, 0xAE, 0xAF, 0xB0, 0xB1, 0xB2, 0xB3
+        ];
+
+        let start_time = Instant::now();
+        let timeout = Duration::from_secs(30);
 
         'detection: while mouse_key == 0 && start_time.elapsed() < timeout {
             for &key in &button_codes {
-                unsafe {
-                    let state = GetAsyncKeyState(key);
-                    if (state as u16 & 0x8000) != 0 {
-                        mouse_key = key;
-                        thread::sleep(Duration::from_millis(100));
-                        break 'detection;
-                    }
+                if backend().is_key_pressed(key) {
+                    mouse_key = key;
+                    thread::sleep(Duration::from_millis(100));
+                    break 'detection;
                 }
             }
             thread::sleep(Duration::from_millis(10));
@@ -478,20 +1277,11 @@ impl Menu {
             return;
         }
 
-        self.toggle_key = mouse_key;
-        let settings = match Settings::load() {
-            Ok(mut s) => {
-                s.toggle_key = self.toggle_key;
-                s
-            },
-            Err(_) => Settings::default_with_toggle_key(self.toggle_key),
-        };
-
-        if let Err(e) = settings.save() {
+        if let Err(e) = self.save_hotkey(target, mouse_key) {
             log_error(&format!("Failed to save settings: {}", e), context);
         } else {
-            println!("\nHotkey successfully set to: {} (code: 0x{:02X})",
-                     Self::get_key_name(mouse_key), mouse_key);
+            style::success(&format!("\n{} successfully set to: {} (code: 0x{:02X})",
+                                    target.label(), Self::get_key_name(mouse_key), mouse_key));
             println!("To change the hotkey, return to the main menu and configure again.");
             println!("\nPress Enter to continue...");
 
@@ -509,7 +1299,7 @@ impl Menu {
             Ok(s) => s,
             Err(_) => {
                 log_error("Failed to load settings", context);
-                println!("Failed to load settings. Press Enter to continue...");
+                style::error("Failed to load settings. Press Enter to continue...");
                 let mut _input = String::new();
                 let _ = io::stdin().read_line(&mut _input);
                 return;
@@ -517,25 +1307,69 @@ impl Menu {
         };
         
         self.clear_console();
-        println!("=== Current Settings ===\n");
+        style::header("Current Settings");
+        println!();
         
         println!("Toggle Key: {}", Self::get_key_name(settings.toggle_key));
+        println!("Left Toggle Key: {}", Self::button_toggle_name(settings.left_toggle_key));
+        println!("Right Toggle Key: {}", Self::button_toggle_name(settings.right_toggle_key));
+        println!("Panic Key: {}", Self::optional_key_name(settings.panic_key));
+        println!("CPS Up/Down Keys: {} / {}", Self::optional_key_name(settings.cps_up_key), Self::optional_key_name(settings.cps_down_key));
+        if settings.key_spam_enabled {
+            println!("Key Spam: {} at {} CPS, {} with {}", Self::optional_key_name(settings.key_spam_key), settings.key_spam_cps,
+                     if settings.key_spam_hold_mode { "hold" } else { "toggle" }, Self::optional_key_name(settings.key_spam_toggle_key));
+        } else {
+            println!("Key Spam: {}", style::state(false));
+        }
         println!("Toggle Mode: {}", if settings.keyboard_hold_mode { "Keyboard Hold" } else { "Mouse Hold" });
+        println!("Toggle Detector: {}", settings.toggle_detector);
+        println!("Toggle Poll Interval: {}ms armed / {}ms idle", settings.toggle_poll_active_ms, settings.toggle_poll_idle_ms);
         println!("Target Process: {}", settings.target_process);
-        println!("Adaptive CPU Mode: {}", if settings.adaptive_cpu_mode { "Enabled" } else { "Disabled" });
+        println!("Target Window: {}", Self::target_mode_name(&settings.target_mode));
+        println!("Process Match Mode: {}", settings.process_match_mode);
+        println!("Window Selection Policy: {}", settings.window_selection_policy);
+        println!("Window Title Filter: {}", if settings.window_title_filter.is_empty() { "None" } else { &settings.window_title_filter });
+        println!("Require Visible Window: {}", style::state(settings.require_window_visibility));
+        println!("Render Child Window Class: {}", if settings.child_window_class.is_empty() { "None (top-level window)" } else { &settings.child_window_class });
+        println!("Click Method: {}", Self::click_method_name(&settings.click_strategy));
+        if settings.fallback_click_strategy.is_empty() {
+            println!("Fallback Strategy: Disabled");
+        } else {
+            println!("Fallback Strategy: {} (after {} failures)", settings.fallback_click_strategy, settings.fallback_failure_threshold);
+        }
+        println!("Pause When Console Focused: {}", style::state(settings.pause_when_console_focused));
+        println!("Only Click While Target Is Focused: {}", style::state(settings.only_when_foreground));
+        println!("Adaptive CPU Mode: {}", style::state(settings.adaptive_cpu_mode));
+        println!("Timing Mode: {}", Self::timing_mode_name(&settings.timing_mode));
+        println!("Minimum Click Delay: {} microseconds (left {}, right {})", settings.effective_min_click_delay_micros(),
+                 settings.effective_left_min_click_delay_micros(), settings.effective_right_min_click_delay_micros());
+        println!("Mixed Input Ratio: {:.2} (SendInput share, requires game focus)", settings.effective_mixed_input_ratio());
+        println!("CPS Drift: {} (band: +/-{} CPS, every {}s)", style::state(settings.cps_drift_enabled), settings.cps_drift_band, settings.cps_drift_period_secs);
+        println!("Failure Backpressure: {}", if settings.backpressure_aggressiveness > 0.0 { format!("{:.2}", settings.backpressure_aggressiveness) } else { "Disabled".to_string() });
         
         println!("\n=== Left Click Settings ===");
         println!("1. Max CPS: {} (Clicks Per Second)", settings.left_max_cps);
-        println!("2. Randomize Click Delay: {}", if settings.left_game_mode == "Combo" { "Enabled" } else { "Disabled" });
-        println!("3. Click Delay: {} microseconds", settings.left_click_delay_micros);
+        println!("2. Click Pattern: {}", settings.left_game_mode);
+        println!("3. Click Delay: {}", Self::click_delay_description(&settings, settings.left_click_delay_micros));
         println!("4. Random Deviation: {} to {} microseconds", settings.left_random_deviation_min, settings.left_random_deviation_max);
         
         println!("\n=== Right Click Settings ===");
         println!("Max CPS: {}", settings.right_max_cps);
         println!("Executor CPS: {}", self.click_service.get_right_click_executor().get_current_max_cps());
-        println!("Randomize Click Delay: {}", if settings.right_game_mode == "Combo" { "Enabled" } else { "Disabled" });
-        println!("Click Delay: {} microseconds", settings.right_click_delay_micros);
+        println!("Click Pattern: {}", settings.right_game_mode);
+        println!("Click Delay: {}", Self::click_delay_description(&settings, settings.right_click_delay_micros));
         println!("Random Deviation: {} to {} microseconds", settings.right_random_deviation_min, settings.right_random_deviation_max);
+
+        if settings.dispatch_latency_diagnostics {
+            println!("\n=== Dispatch Latency ===");
+            for (label, executor) in [("Left", self.click_service.get_left_click_executor()), ("Right", self.click_service.get_right_click_executor())] {
+                match executor.dispatch_latency() {
+                    Some(latency) => println!("{}: min {}us / avg {}us / max {}us ({} samples)",
+                                              label, latency.min_micros, latency.avg_micros, latency.max_micros, latency.samples),
+                    None => println!("{}: no samples yet", label),
+                }
+            }
+        }
         
         println!("\nPress Enter to continue...");
         let mut _input = String::new();
@@ -545,7 +1379,10 @@ impl Menu {
     fn start_auto_clicker(&mut self) {
         let context = "Menu::start_auto_clicker";
 
-        if self.toggle_key == 0 {
+        let settings = Settings::load().unwrap_or_default();
+        let toggle_keys = ToggleKeys::for_mode(ClickMode::from_setting(&settings.click_mode), &settings);
+
+        if !toggle_keys.is_configured() {
             self.clear_console();
             println!("Please configure hotkey first!");
             println!("\nPress Enter to continue...");
@@ -558,16 +1395,30 @@ impl Menu {
 
         self.clear_console();
 
-        let settings = Settings::load().unwrap_or_default();
+        if !self.confirm_anti_cheat_warning(&settings) {
+            return;
+        }
 
-        self.click_mode = match settings.click_mode.as_str() {
-            "LeftClick" => ClickMode::LeftClick,
-            "RightClick" => ClickMode::RightClick,
-            "Both" => ClickMode::Both,
-            _ => ClickMode::LeftClick,
-        };
+        if !settings.is_within_schedule() {
+            log_warn(&format!("Refusing to start outside the active schedule ({}-{})", settings.active_from, settings.active_until), context);
+            println!("RAC can only be started between {} and {}.", settings.active_from, settings.active_until);
+            println!("\nPress Enter to continue...");
+            let mut _input = String::new();
+            let _ = io::stdin().read_line(&mut _input);
+            return;
+        }
+
+        self.click_mode = ClickMode::from_setting(&settings.click_mode);
 
         self.apply_settings();
+        self.click_service.set_hold_gate(settings.effective_left_toggle_key(),
+                                         settings.effective_right_toggle_key(),
+                                         self.toggle_mode == ToggleMode::KeyboardHold);
+        self.click_service.set_coupled_button_hold(settings.couples_button_hold());
+        self.click_service.set_antiafk(settings.antiafk_enabled, settings.antiafk_interval_secs);
+
+        self.click_service.get_left_click_executor().reset_session_stats();
+        self.click_service.get_right_click_executor().reset_session_stats();
 
         match self.click_mode {
             ClickMode::LeftClick => {
@@ -577,10 +1428,7 @@ impl Menu {
                 left_executor.set_mouse_button(MouseButton::Left);
                 left_executor.set_max_cps(settings.left_max_cps);
                 left_executor.set_active(true);
-                let mode = match self.settings.left_game_mode.as_str() {
-                    "Combo" => GameMode::Combo,
-                    _ => GameMode::Default,
-                };
+                let mode = GameMode::from_setting(&self.settings.left_game_mode);
                 left_executor.set_game_mode(mode);
             },
             ClickMode::RightClick => {
@@ -590,10 +1438,7 @@ impl Menu {
                 right_executor.set_mouse_button(MouseButton::Right);
                 right_executor.set_max_cps(settings.right_max_cps);
                 right_executor.set_active(true);
-                let mode = match self.settings.right_game_mode.as_str() {
-                    "Combo" => GameMode::Combo,
-                    _ => GameMode::Default,
-                };
+                let mode = GameMode::from_setting(&self.settings.right_game_mode);
                 right_executor.set_game_mode(mode);
                 log_info("Right click mode activated", context);
             },
@@ -604,50 +1449,167 @@ impl Menu {
                 left_executor.set_mouse_button(MouseButton::Left);
                 left_executor.set_max_cps(settings.left_max_cps);
                 left_executor.set_active(true);
-                let left_mode = match self.settings.left_game_mode.as_str() {
-                    "Combo" => GameMode::Combo,
-                    _ => GameMode::Default,
-                };
+                let left_mode = GameMode::from_setting(&self.settings.left_game_mode);
                 left_executor.set_game_mode(left_mode);
 
                 let right_executor = self.click_service.get_right_click_executor();
                 right_executor.set_mouse_button(MouseButton::Right);
                 right_executor.set_max_cps(settings.right_max_cps);
                 right_executor.set_active(true);
-                let right_mode = match self.settings.right_game_mode.as_str() {
-                    "Combo" => GameMode::Combo,
-                    _ => GameMode::Default,
-                };
+                let right_mode = GameMode::from_setting(&self.settings.right_game_mode);
                 right_executor.set_game_mode(right_mode);
+            },
+            ClickMode::ExtraButton(button) => {
+                self.click_service.force_enable_left_clicking();
+                self.click_service.force_disable_right_clicking();
+                let left_executor = self.click_service.get_left_click_executor();
+                left_executor.set_mouse_button(button);
+                left_executor.set_max_cps(settings.left_max_cps);
+                left_executor.set_active(true);
+                let mode = GameMode::from_setting(&self.settings.left_game_mode);
+                left_executor.set_game_mode(mode);
+                log_info(&format!("{:?} click mode activated", button), context);
+            }
+        }
+
+        match self.toggle_mode {
+            ToggleMode::MouseHold => {
+                println!("RAC Started! Press {} to enable/disable.", Self::toggle_keys_name(toggle_keys));
+                println!("When enabled, hold mouse button to activate clicking.");
+                println!("Click Mode: {}", self.click_mode.description());
+                println!("Press Ctrl+Q to return to menu.");
+                println!("Note: If clicking stops, press the toggle key twice quickly to reset.");
+            },
+            ToggleMode::KeyboardHold => {
+                println!("RAC Started!");
+                println!("Hold {} to activate clicking.", Self::toggle_keys_name(toggle_keys));
+                println!("Click Mode: {}", self.click_mode.description());
+                println!("Press Ctrl+Q to return to menu.");
+                println!("Note: If clicking stops, press the toggle key twice quickly to reset.");
+            }
+        }
+        if settings.antiafk_enabled {
+            println!("Anti-AFK: clicking once every ~{}s while enabled, no hold needed.", settings.antiafk_interval_secs);
+        }
+
+        self.run_main_loop();
+
+        self.offer_stats_export(&settings);
+    }
+
+    fn offer_stats_export(&self, settings: &Settings) {
+        let context = "Menu::offer_stats_export";
+
+        let mut rows = Vec::new();
+        let left_executor = self.click_service.get_left_click_executor();
+        let right_executor = self.click_service.get_right_click_executor();
+        let left_stats = left_executor.session_stats();
+        let right_stats = right_executor.session_stats();
+        if left_stats.clicks > 0 || left_stats.failures > 0 {
+            rows.push((format!("{:?}", left_executor.current_button()), settings.left_max_cps, left_stats));
+        }
+        if right_stats.clicks > 0 || right_stats.failures > 0 {
+            rows.push(("Right".to_string(), settings.right_max_cps, right_stats));
+        }
+        if rows.is_empty() {
+            return;
+        }
+
+        print!("\nAppend session statistics to stats.csv? (y/N): ");
+        let _ = io::stdout().flush();
+        let mut answer = String::new();
+        if io::stdin().read_line(&mut answer).is_err() || !answer.trim().eq_ignore_ascii_case("y") {
+            return;
+        }
+
+        let stats_path = match Settings::get_data_dir() {
+            Ok(dir) => dir.join("stats.csv"),
+            Err(e) => {
+                log_error(&format!("Failed to get data directory: {}", e), context);
+                return;
             }
+        };
+
+        let csv_field = |value: &str| format!("\"{}\"", value.replace('"', "\"\""));
+        let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let mut lines = String::new();
+        if !stats_path.exists() {
+            lines.push_str("timestamp,profile,target_process,click_mode,button,target_cps,clicks,failures,min_delay_ms,mean_delay_ms,max_delay_ms\n");
+        }
+        for (button, target_cps, stats) in rows {
+            lines.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{:.3},{:.3},{:.3}\n",
+                timestamp,
+                csv_field(&settings.active_profile),
+                csv_field(&settings.target_process),
+                settings.click_mode,
+                button,
+                target_cps,
+                stats.clicks,
+                stats.failures,
+                stats.min_delay_micros as f64 / 1000.0,
+                stats.mean_delay_micros as f64 / 1000.0,
+                stats.max_delay_micros as f64 / 1000.0,
+            ));
         }
 
-        match self.toggle_mode {
-            ToggleMode::MouseHold => {
-                println!("RAC Started! Press {} to enable/disable.", Self::get_key_name(self.toggle_key));
-                println!("When enabled, hold mouse button to activate clicking.");
-                match self.click_mode {
-                    ClickMode::LeftClick => println!("Click Mode: LEFT CLICK"),
-                    ClickMode::RightClick => println!("Click Mode: RIGHT CLICK"),
-                    ClickMode::Both => println!("Click Mode: BOTH BUTTONS"),
-                }
-                println!("Press Ctrl+Q to return to menu.");
-                println!("Note: If clicking stops, press the toggle key twice quickly to reset.");
+        let result = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&stats_path)
+            .and_then(|mut file| file.write_all(lines.as_bytes()));
+        match result {
+            Ok(()) => {
+                log_info(&format!("Appended session statistics to {}", stats_path.display()), context);
+                println!("Saved to {}", stats_path.display());
             },
-            ToggleMode::KeyboardHold => {
-                println!("RAC Started!");
-                println!("Hold {} to activate clicking.", Self::get_key_name(self.toggle_key));
-                match self.click_mode {
-                    ClickMode::LeftClick => println!("Click Mode: LEFT CLICK"),
-                    ClickMode::RightClick => println!("Click Mode: RIGHT CLICK"),
-                    ClickMode::Both => println!("Click Mode: BOTH BUTTONS"),
-                }
-                println!("Press Ctrl+Q to return to menu.");
-                println!("Note: If clicking stops, press the toggle key twice quickly to reset.");
+            Err(e) => {
+                log_error(&format!("Failed to write session statistics: {}", e), context);
+                style::error(&format!("Failed to save statistics: {}", e));
             }
         }
+        thread::sleep(Duration::from_millis(800));
+    }
+
+    fn confirm_anti_cheat_warning(&mut self, settings: &Settings) -> bool {
+        let context = "Menu::confirm_anti_cheat_warning";
+
+        if !settings.anti_cheat_warning || self.anti_cheat_acknowledged {
+            return true;
+        }
 
-        self.run_main_loop();
+        let detected = AntiCheatDetector::new(&settings.anti_cheat_processes).detect();
+        if detected.is_empty() {
+            return true;
+        }
+
+        style::header("Anti-Cheat Warning");
+        println!("The following anti-cheat processes are running:");
+        for name in &detected {
+            println!("  - {}", name);
+        }
+        println!("\nAutomated clicking may violate the game's terms of service and can result in a ban.");
+        print!("Type 'yes' to acknowledge and continue: ");
+
+        if let Err(e) = io::stdout().flush() {
+            log_error(&format!("Failed to flush stdout: {}", e), context);
+        }
+
+        let mut input = String::new();
+        if let Err(e) = io::stdin().read_line(&mut input) {
+            log_error(&format!("Failed to read user input: {}", e), context);
+            return false;
+        }
+
+        if input.trim().eq_ignore_ascii_case("yes") {
+            log_warn("User acknowledged anti-cheat warning", context);
+            self.anti_cheat_acknowledged = true;
+            self.clear_console();
+            true
+        } else {
+            log_info("User declined anti-cheat warning, not starting RAC", context);
+            false
+        }
     }
 
     fn run_main_loop(&self) {
@@ -663,7 +1625,7 @@ impl Menu {
         let key_thread = thread::spawn(move || {
             while !quit_requested_clone.load(std::sync::atomic::Ordering::Relaxed) {
                 if event::poll(Duration::from_millis(100)).unwrap_or(false) {
-                    if let Ok(Event::Key(KeyEvent { code: KeyCode::Char('q'), modifiers, .. })) = event::read() {
+                    if let Ok(Event::Key(KeyEvent { code: KeyCode::Char('q') | KeyCode::Char('c'), modifiers, .. })) = event::read() {
                         if modifiers == event::KeyModifiers::CONTROL {
                             quit_requested_clone.store(true, std::sync::atomic::Ordering::Relaxed);
                             break;
@@ -673,15 +1635,98 @@ impl Menu {
             }
         });
 
+        let left_executor = self.click_service.get_left_click_executor();
+        let right_executor = self.click_service.get_right_click_executor();
+        let mut left_rate = ClickRate::new();
+        let mut right_rate = ClickRate::new();
+        let mut last_status = Instant::now() - Duration::from_millis(250);
+        let loop_settings = Settings::load().unwrap_or_default();
+        let session_limit = SessionLimit::from_settings(&loop_settings);
+        let session_started = Instant::now();
+        let mut stop_reason = None;
+        // Held state from the previous poll, so holding a CPS key counts as a single step.
+        let mut cps_keys_held = (false, false);
+        let mut cps_notice: Option<(String, Instant)> = None;
+
+        println!();
+        let mut last_title = String::new();
+        let mut last_log_size_check = Instant::now();
         while !quit_requested.load(std::sync::atomic::Ordering::Relaxed) {
-            thread::sleep(Duration::from_millis(100));
+            if last_status.elapsed() >= Duration::from_millis(250) {
+                let snapshot = self.click_service.snapshot();
+                let left_total = left_executor.click_count();
+                let right_total = right_executor.click_count();
+                let left_cps = left_rate.sample(left_total);
+                let right_cps = right_rate.sample(right_total);
+
+                let mut status = Vec::new();
+                if snapshot.left_active {
+                    status.push(format!("{:?}: {:>4.1} CPS, {} total", left_executor.current_button(), left_cps, left_total));
+                }
+                if snapshot.right_active {
+                    status.push(format!("Right: {:>4.1} CPS, {} total", right_cps, right_total));
+                }
+                if let Some((notice, _)) = cps_notice.as_ref().filter(|(_, shown)| shown.elapsed() < Duration::from_millis(1500)) {
+                    status.push(notice.clone());
+                }
+                let status = if status.is_empty() { "Idle".to_string() } else { status.join(" | ") };
+
+                print!("\r{:<72}", status);
+                let _ = io::stdout().flush();
+                last_status = Instant::now();
+            }
+
+            let up_held = loop_settings.cps_up_key != 0 && backend().is_key_pressed(loop_settings.cps_up_key);
+            let down_held = loop_settings.cps_down_key != 0 && backend().is_key_pressed(loop_settings.cps_down_key);
+            let step = match (up_held && !cps_keys_held.0, down_held && !cps_keys_held.1) {
+                (true, false) => 1,
+                (false, true) => -1,
+                _ => 0,
+            };
+            cps_keys_held = (up_held, down_held);
+            if step != 0 {
+                cps_notice = Some((self.nudge_max_cps(step), Instant::now()));
+                last_status = Instant::now() - Duration::from_millis(250);
+            }
+
+            let title = self.build_console_title();
+            if title != last_title {
+                Self::set_console_title(&title);
+                last_title = title;
+            }
+
+            if last_log_size_check.elapsed() >= Duration::from_secs(60) {
+                check_log_size(self.settings.log_size_warning_mb);
+                last_log_size_check = Instant::now();
+            }
+
+            let session_clicks = left_executor.click_count() + right_executor.click_count();
+            if let Some(reason) = session_limit.exceeded(session_started.elapsed(), session_clicks) {
+                stop_reason = Some(reason);
+                quit_requested.store(true, std::sync::atomic::Ordering::Relaxed);
+                break;
+            }
+
+            thread::sleep(Duration::from_millis(50));
         }
 
-        log_info("Ctrl+Q pressed, stopping RAC", context);
+        print!("\r\n");
+        let _ = io::stdout().flush();
         
         self.click_service.force_disable_clicking();
         self.click_service.force_disable_left_clicking();
         self.click_service.force_disable_right_clicking();
+        left_executor.set_active(false);
+        right_executor.set_active(false);
+
+        match &stop_reason {
+            Some(reason) => {
+                log_info(&format!("Stopping RAC: {}", reason), context);
+                print!("RAC stopped automatically: {}.\r\n", reason);
+                let _ = io::stdout().flush();
+            }
+            None => log_info("Ctrl+Q or Ctrl+C pressed, stopping RAC", context),
+        }
         
         if let Err(e) = key_thread.join() {
             log_error(&format!("Failed to join key thread: {:?}", e), context);
@@ -692,21 +1737,99 @@ impl Menu {
         }
     }
 
+    // Steps Max CPS for the buttons the current click mode drives, both of them in Both mode.
+    fn nudge_max_cps(&self, step: i8) -> String {
+        let context = "Menu::nudge_max_cps";
+        let mut settings = Settings::load().unwrap_or_default();
+        let nudge = |cps: u8| cps.saturating_add_signed(step).clamp(limits::MIN_CPS, limits::MAX_CPS);
+
+        let (adjust_left, adjust_right) = match self.click_mode {
+            ClickMode::RightClick => (false, true),
+            ClickMode::Both => (true, true),
+            ClickMode::LeftClick | ClickMode::ExtraButton(_) => (true, false),
+        };
+
+        let mut changes = Vec::new();
+        if adjust_left {
+            settings.left_max_cps = nudge(settings.left_max_cps);
+            for left_executor in [Arc::clone(&self.click_executor), self.click_service.get_left_click_executor()] {
+                left_executor.set_left_max_cps(settings.left_max_cps);
+            }
+            changes.push(format!("Left max {} CPS", settings.left_max_cps));
+        }
+        if adjust_right {
+            settings.right_max_cps = nudge(settings.right_max_cps);
+            self.click_service.get_right_click_executor().set_max_cps(settings.right_max_cps);
+            changes.push(format!("Right max {} CPS", settings.right_max_cps));
+        }
+
+        let notice = changes.join(", ");
+        if let Err(e) = settings.save() {
+            log_error(&format!("Failed to save Max CPS: {}", e), context);
+        } else {
+            log_info(&format!("Max CPS adjusted from hotkey: {}", notice), context);
+        }
+        notice
+    }
+
+    fn timing_mode_name(setting: &str) -> &'static str {
+        match TimingMode::from_setting(setting) {
+            TimingMode::Cps => "CPS (interval from Max CPS)",
+            TimingMode::Interval => "Interval (Click Delay is authoritative)",
+        }
+    }
+
+    fn click_delay_description(settings: &Settings, click_delay_micros: u64) -> String {
+        match TimingMode::from_setting(&settings.timing_mode) {
+            TimingMode::Interval => format!("{} microseconds (~{:.1} CPS)", click_delay_micros, 1_000_000.0 / click_delay_micros.max(1) as f64),
+            TimingMode::Cps => format!("{} microseconds (used in Interval mode only)", click_delay_micros),
+        }
+    }
+
+    fn target_mode_name(setting: &str) -> &'static str {
+        match TargetMode::from_setting(setting) {
+            TargetMode::Process => "target process",
+            TargetMode::Foreground => "foreground window",
+        }
+    }
+
     fn configure_advanced_settings(&mut self) {
         let context = "Menu::configure_advanced_settings";
-        let mut settings = match Settings::load() {
-            Ok(s) => s,
-            Err(_) => Settings::default(),
-        };
+        let settings = Settings::load().unwrap_or_default();
 
         loop {
             self.clear_console();
-            println!("=== Advanced Settings ===");
+            style::header("Advanced Settings");
             println!("1. Configure Target Process (currently: {})", settings.target_process);
-            println!("2. Toggle Adaptive CPU Mode (currently: {})", if settings.adaptive_cpu_mode { "Enabled" } else { "Disabled" });
+            println!("2. Toggle Adaptive CPU Mode (currently: {})", style::state(settings.adaptive_cpu_mode));
             println!("3. Left Click Advanced Settings");
             println!("4. Right Click Advanced Settings");
-            println!("5. Save and Return to Main Menu");
+            println!("5. Export Timing Share Code");
+            println!("6. Import Timing Share Code");
+            println!("7. Click Method (currently: {})", Self::click_method_name(&self.settings.click_strategy));
+            println!("8. Window Title Filter (currently: {})", if self.settings.window_title_filter.is_empty() { "none" } else { &self.settings.window_title_filter });
+            println!("9. CPS Warmup (currently: {})", if self.settings.warmup_ms == 0 { "off".to_string() } else { format!("{}ms", self.settings.warmup_ms) });
+            println!("10. Spin Threshold (currently: {}us)", self.settings.spin_threshold_micros);
+            println!("11. Session Limits (currently: {}, {})",
+                     if self.settings.max_session_secs == 0 { "no time limit".to_string() } else { format!("{}s", self.settings.max_session_secs) },
+                     if self.settings.max_session_clicks == 0 { "no click limit".to_string() } else { format!("{} clicks", self.settings.max_session_clicks) });
+            println!("12. Active Hours (currently: {})",
+                     match self.settings.schedule_window() {
+                         Some(_) => format!("{}-{}", self.settings.active_from, self.settings.active_until),
+                         None => "always".to_string(),
+                     });
+            println!("13. Click Region (currently: {})", Self::click_region_description(&self.settings));
+            println!("14. Simulate Mouse Movement (currently: {})", style::state(self.settings.simulate_movement));
+            println!("15. Burst Pattern (currently: {} clicks, {}% gap, {}% pause)",
+                self.settings.burst_size, self.settings.burst_intra_delay_percent, self.settings.burst_pause_percent);
+            println!("16. Activation Delay (currently: {}-{}ms)", self.settings.activation_delay_min_ms, self.settings.activation_delay_max_ms);
+            println!("17. Target Window (currently: {})", Self::target_mode_name(&self.settings.target_mode));
+            println!("18. Render Child Window (currently: {})", if self.settings.child_window_class.is_empty() { "top-level window" } else { &self.settings.child_window_class });
+            println!("19. Timing Mode (currently: {})", Self::timing_mode_name(&self.settings.timing_mode));
+            println!("20. Require Visible Target Window (currently: {})", style::state(self.settings.require_window_visibility));
+            println!("21. Only Click While Target Is Focused (currently: {})", style::state(self.settings.only_when_foreground));
+            println!("22. Reset to Defaults");
+            println!("23. Save and Return to Main Menu");
             print!("\nSelect option: ");
 
             if let Err(e) = io::stdout().flush() {
@@ -722,7 +1845,7 @@ impl Menu {
 
             match choice.trim() {
                 "1" => {
-                    println!("Enter target process name (current: {}): ", self.settings.target_process);
+                    println!("Enter target process name, or several separated by commas in order of preference (current: {}): ", self.settings.target_process);
                     let mut input = String::new();
                     if let Err(e) = io::stdin().read_line(&mut input) {
                         log_error(&format!("Failed to read input: {}", e), context);
@@ -735,7 +1858,7 @@ impl Menu {
                     }
                 },
                 "2" => {
-                    println!("Toggle Adaptive CPU Mode (currently {})", if self.settings.adaptive_cpu_mode { "Enabled" } else { "Disabled" });
+                    println!("Toggle Adaptive CPU Mode (currently {})", style::state(self.settings.adaptive_cpu_mode));
                     println!("1. Enable");
                     println!("2. Disable");
                     print!("Enter choice: ");
@@ -755,7 +1878,7 @@ impl Menu {
                         "1" => self.settings.adaptive_cpu_mode = true,
                         "2" => self.settings.adaptive_cpu_mode = false,
                         _ => {
-                            println!("Invalid choice. Press Enter to continue...");
+                            style::error("Invalid choice. Press Enter to continue...");
                             let mut _input = String::new();
                             let _ = io::stdin().read_line(&mut _input);
                             self.clear_console();
@@ -769,11 +1892,242 @@ impl Menu {
                     self.configure_right_click_settings();
                 },
                 "5" => {
+                    println!("\nShare code:\n{}", self.settings.to_share_code());
+                    println!("\nPress Enter to continue...");
+                    let mut _input = String::new();
+                    let _ = io::stdin().read_line(&mut _input);
+                },
+                "6" => {
+                    println!("Paste share code: ");
+                    let mut input = String::new();
+                    if let Err(e) = io::stdin().read_line(&mut input) {
+                        log_error(&format!("Failed to read input: {}", e), context);
+                        continue;
+                    }
+
+                    match self.settings.apply_share_code(&input) {
+                        Ok(()) => println!("Share code applied! Save to keep it. Press Enter to continue..."),
+                        Err(e) => style::error(&format!("Invalid share code: {}. Press Enter to continue...", e)),
+                    }
+                    let mut _input = String::new();
+                    let _ = io::stdin().read_line(&mut _input);
+                },
+                "7" => {
+                    println!("Select click method:");
+                    println!("1. Background (PostMessage) - clicks the game window even when it is not focused");
+                    println!("2. Foreground (SendInput) - for games that ignore PostMessage, only clicks while the game is focused");
+                    print!("Enter choice: ");
+
+                    if let Err(e) = io::stdout().flush() {
+                        log_error(&format!("Failed to flush stdout: {}", e), context);
+                        continue;
+                    }
+
+                    let mut input = String::new();
+                    if let Err(e) = io::stdin().read_line(&mut input) {
+                        log_error(&format!("Failed to read input: {}", e), context);
+                        continue;
+                    }
+
+                    match input.trim() {
+                        "1" => self.settings.click_strategy = "PostMessage".to_string(),
+                        "2" => self.settings.click_strategy = "SendInput".to_string(),
+                        _ => {
+                            style::error("Invalid choice. Press Enter to continue...");
+                            let mut _input = String::new();
+                            let _ = io::stdin().read_line(&mut _input);
+                        }
+                    }
+                },
+                "8" => {
+                    println!("Enter text the game window title must contain, or leave empty to accept any title (current: {}): ", self.settings.window_title_filter);
+                    let mut input = String::new();
+                    if let Err(e) = io::stdin().read_line(&mut input) {
+                        log_error(&format!("Failed to read input: {}", e), context);
+                        continue;
+                    }
+
+                    self.settings.window_title_filter = input.trim().to_string();
+                },
+                "9" => {
+                    println!("Clicking starts at a quarter of Max CPS and ramps up to full speed over this time.");
+                    println!("Enter warmup in milliseconds, 0 to start at full speed (current: {}): ", self.settings.warmup_ms);
+                    let mut input = String::new();
+                    if let Err(e) = io::stdin().read_line(&mut input) {
+                        log_error(&format!("Failed to read input: {}", e), context);
+                        continue;
+                    }
+
+                    match input.trim().parse::<u64>() {
+                        Ok(value) => self.settings.warmup_ms = value,
+                        Err(_) => {
+                            style::error("Invalid number. Press Enter to continue...");
+                            let mut _input = String::new();
+                            let _ = io::stdin().read_line(&mut _input);
+                        }
+                    }
+                },
+                "10" => {
+                    println!("Waits shorter than this busy-wait for accuracy, longer ones sleep and free the CPU.");
+                    println!("Raise it for tighter timing, lower it to save CPU and battery. Adaptive CPU mode uses a quarter of it.");
+                    println!("Enter spin threshold in microseconds (current: {}): ", self.settings.spin_threshold_micros);
+                    let mut input = String::new();
+                    if let Err(e) = io::stdin().read_line(&mut input) {
+                        log_error(&format!("Failed to read input: {}", e), context);
+                        continue;
+                    }
+
+                    match input.trim().parse::<u64>() {
+                        Ok(value) => self.settings.spin_threshold_micros = value,
+                        Err(_) => {
+                            style::error("Invalid number. Press Enter to continue...");
+                            let mut _input = String::new();
+                            let _ = io::stdin().read_line(&mut _input);
+                        }
+                    }
+                },
+                "11" => {
+                    println!("RAC stops itself once either limit is reached. Enter 0 for no limit.");
+                    let input = Self::prompt_line(&format!("Maximum session length in seconds (current: {}): ", self.settings.max_session_secs));
+                    if !input.is_empty() {
+                        match input.parse::<u64>() {
+                            Ok(value) => self.settings.max_session_secs = value,
+                            Err(_) => style::error(&format!("Invalid number, keeping {}", self.settings.max_session_secs)),
+                        }
+                    }
+
+                    let input = Self::prompt_line(&format!("Maximum clicks per session (current: {}): ", self.settings.max_session_clicks));
+                    if !input.is_empty() {
+                        match input.parse::<u64>() {
+                            Ok(value) => self.settings.max_session_clicks = value,
+                            Err(_) => style::error(&format!("Invalid number, keeping {}", self.settings.max_session_clicks)),
+                        }
+                    }
+                },
+                "12" => {
+                    println!("RAC can only be armed between these times (local, HH:MM). Leave both empty to allow any time.");
+                    let from = Self::prompt_line("Active from: ");
+                    let until = Self::prompt_line("Active until: ");
+                    if from.is_empty() && until.is_empty() {
+                        self.settings.active_from.clear();
+                        self.settings.active_until.clear();
+                    } else if Settings::parse_schedule_time(&from).is_some() && Settings::parse_schedule_time(&until).is_some() {
+                        self.settings.active_from = from;
+                        self.settings.active_until = until;
+                    } else {
+                        style::error("Invalid time, use HH:MM (e.g. 18:30). Press Enter to continue...");
+                        let mut _input = String::new();
+                        let _ = io::stdin().read_line(&mut _input);
+                    }
+                },
+                "13" => self.configure_click_region(),
+                "14" => self.settings.simulate_movement = !self.settings.simulate_movement,
+                "15" => {
+                    println!("Burst mode clicks in groups: short gaps inside a group, then one longer pause.");
+                    println!("Gap and pause are percentages of the normal delay. Leave empty to keep the current value.");
+                    let input = Self::prompt_line(&format!("Clicks per burst (current: {}): ", self.settings.burst_size));
+                    if !input.is_empty() {
+                        match input.parse::<u32>() {
+                            Ok(value) if value >= 1 => self.settings.burst_size = value,
+                            _ => style::error(&format!("Invalid number, keeping {}", self.settings.burst_size)),
+                        }
+                    }
+
+                    let input = Self::prompt_line(&format!("Gap inside a burst in % (current: {}): ", self.settings.burst_intra_delay_percent));
+                    if !input.is_empty() {
+                        match input.parse::<u32>() {
+                            Ok(value) if value >= 1 => self.settings.burst_intra_delay_percent = value,
+                            _ => style::error(&format!("Invalid number, keeping {}", self.settings.burst_intra_delay_percent)),
+                        }
+                    }
+
+                    let input = Self::prompt_line(&format!("Pause after a burst in % (current: {}): ", self.settings.burst_pause_percent));
+                    if !input.is_empty() {
+                        match input.parse::<u32>() {
+                            Ok(value) if value >= 1 => self.settings.burst_pause_percent = value,
+                            _ => style::error(&format!("Invalid number, keeping {}", self.settings.burst_pause_percent)),
+                        }
+                    }
+                },
+                "16" => {
+                    println!("RAC waits a random time in this range after the toggle arms before the first click.");
+                    println!("Set both to 0 to start clicking immediately.");
+                    let input = Self::prompt_line(&format!("Minimum delay in ms (current: {}): ", self.settings.activation_delay_min_ms));
+                    if !input.is_empty() {
+                        match input.parse::<u64>() {
+                            Ok(value) => self.settings.activation_delay_min_ms = value,
+                            Err(_) => style::error(&format!("Invalid number, keeping {}", self.settings.activation_delay_min_ms)),
+                        }
+                    }
+
+                    let input = Self::prompt_line(&format!("Maximum delay in ms (current: {}): ", self.settings.activation_delay_max_ms));
+                    if !input.is_empty() {
+                        match input.parse::<u64>() {
+                            Ok(value) => self.settings.activation_delay_max_ms = value,
+                            Err(_) => style::error(&format!("Invalid number, keeping {}", self.settings.activation_delay_max_ms)),
+                        }
+                    }
+                },
+                "17" => {
+                    self.settings.target_mode = match TargetMode::from_setting(&self.settings.target_mode) {
+                        TargetMode::Process => TargetMode::Foreground,
+                        TargetMode::Foreground => TargetMode::Process,
+                    }.as_setting().to_string();
+                },
+                "18" => {
+                    println!("Some games and browsers only take input on a child window inside the main one.");
+                    println!("Enter text that child's class name must contain, or leave empty to click the top-level window (current: {}): ", self.settings.child_window_class);
+                    let mut input = String::new();
+                    if let Err(e) = io::stdin().read_line(&mut input) {
+                        log_error(&format!("Failed to read input: {}", e), context);
+                        continue;
+                    }
+
+                    self.settings.child_window_class = input.trim().to_string();
+                },
+                "19" => {
+                    let timing_mode = match TimingMode::from_setting(&self.settings.timing_mode) {
+                        TimingMode::Cps => TimingMode::Interval,
+                        TimingMode::Interval => TimingMode::Cps,
+                    };
+                    if timing_mode == TimingMode::Interval {
+                        // A delay below the floor was never in use; start from the interval Max CPS was giving.
+                        if self.settings.left_click_delay_micros < self.settings.effective_left_min_click_delay_micros() {
+                            self.settings.left_click_delay_micros = 1_000_000 / self.settings.left_max_cps.max(1) as u64;
+                        }
+                        if self.settings.right_click_delay_micros < self.settings.effective_right_min_click_delay_micros() {
+                            self.settings.right_click_delay_micros = 1_000_000 / self.settings.right_max_cps.max(1) as u64;
+                        }
+                        println!("Interval mode: each button clicks every Click Delay microseconds and Max CPS is ignored.");
+                        println!("Left {}, right {}. Press Enter to continue...",
+                                 Self::click_delay_description(&self.settings, self.settings.left_click_delay_micros),
+                                 Self::click_delay_description(&self.settings, self.settings.right_click_delay_micros));
+                        let mut _input = String::new();
+                        let _ = io::stdin().read_line(&mut _input);
+                    }
+                    self.settings.timing_mode = timing_mode.as_setting().to_string();
+                },
+                "20" => {
+                    self.settings.require_window_visibility = !self.settings.require_window_visibility;
+                    if !self.settings.require_window_visibility {
+                        println!("Windows that report as hidden can now be targeted, which some borderless and fullscreen games need.");
+                        println!("Press Enter to continue...");
+                        let mut _input = String::new();
+                        let _ = io::stdin().read_line(&mut _input);
+                    }
+                },
+                "21" => self.settings.only_when_foreground = !self.settings.only_when_foreground,
+                "22" => {
+                    if self.reset_to_defaults() {
+                        return;
+                    }
+                },
+                "23" => {
                     println!("Saving all settings...");
                     
                     let left_executor = self.click_service.get_left_click_executor();
                     left_executor.set_max_cps(self.settings.left_max_cps);
-                    let left_mode = if self.settings.left_game_mode == "Combo" { GameMode::Combo } else { GameMode::Default };
+                    let left_mode = GameMode::from_setting(&self.settings.left_game_mode);
                     left_executor.set_game_mode(left_mode);
                     
                     let right_executor = self.click_service.get_right_click_executor();
@@ -781,16 +2135,16 @@ impl Menu {
                     
                     if let Err(e) = self.settings.save() {
                         log_error(&format!("Failed to save settings: {}", e), context);
-                        println!("Failed to save settings! Press Enter to continue...");
+                        style::error("Failed to save settings! Press Enter to continue...");
                     } else {
-                        println!("All settings saved successfully! Press Enter to continue...");
+                        style::success("All settings saved successfully! Press Enter to continue...");
                     }
                     let mut _input = String::new();
                     let _ = io::stdin().read_line(&mut _input);
                     return;
                 },
                 _ => {
-                    println!("Invalid option. Press Enter to continue...");
+                    style::error("Invalid option. Press Enter to continue...");
                     let mut _input = String::new();
                     let _ = io::stdin().read_line(&mut _input);
                 }
@@ -798,14 +2152,56 @@ impl Menu {
         }
     }
 
+    // Returns true once the reset went through, so the caller doesn't save its stale copy over it.
+    fn reset_to_defaults(&mut self) -> bool {
+        let context = "Menu::reset_to_defaults";
+
+        style::warning("\nThis replaces every setting with its default, including hotkeys and click timing.");
+        let answer = Self::prompt_line("Reset all settings to defaults? (y/N): ");
+        if !answer.eq_ignore_ascii_case("y") && !answer.eq_ignore_ascii_case("yes") {
+            return false;
+        }
+
+        let current_profile = Settings::load().map(|settings| settings.active_profile).unwrap_or_default();
+        let keep_active_profile = !current_profile.is_empty() && {
+            let answer = Self::prompt_line(&format!("Keep '{}' as the active profile? (Y/n): ", current_profile));
+            !answer.eq_ignore_ascii_case("n") && !answer.eq_ignore_ascii_case("no")
+        };
+
+        let reset = match Settings::reset_to_defaults(keep_active_profile) {
+            Ok((settings, backed_up)) => {
+                self.apply_profile(settings);
+                if backed_up {
+                    style::success("Settings reset to defaults. The previous file was kept as settings.bak.json.");
+                } else {
+                    style::success("Settings reset to defaults.");
+                }
+                if self.toggle_key == 0 {
+                    println!("Set a hotkey from the main menu before starting RAC.");
+                }
+                true
+            },
+            Err(e) => {
+                log_error(&format!("Failed to reset settings: {}", e), context);
+                style::error(&format!("Failed to reset settings: {}", e));
+                false
+            }
+        };
+
+        println!("Press Enter to continue...");
+        let mut _input = String::new();
+        let _ = io::stdin().read_line(&mut _input);
+        reset
+    }
+
     fn configure_left_click_settings(&mut self) {
         let context = "Menu::configure_left_click_settings";
         
         loop {
             self.clear_console();
-            println!("=== Left Click Settings ===");
+            style::header("Left Click Settings");
             println!("1. Max CPS: {} (Clicks Per Second)", self.settings.left_max_cps);
-            println!("2. Randomize Click Delay: {}", if self.settings.left_game_mode == "Combo" { "Enabled" } else { "Disabled" });
+            println!("2. Click Pattern: {}", self.settings.left_game_mode);
             println!("3. Click Delay Options");
             println!("4. Back to Advanced Settings");
 
@@ -814,85 +2210,33 @@ impl Menu {
                 return;
             }
 
-            let mut choice = String::new();
-            if let Err(e) = io::stdin().read_line(&mut choice) {
-                log_error(&format!("Failed to read input: {}", e), context);
-                return;
-            }
-
-            match choice.trim() {
-                "1" => {
-                    println!("Enter Left Max CPS (1-20) (current: {}): ", self.settings.left_max_cps);
-                    let mut input = String::new();
-                    if let Err(e) = io::stdin().read_line(&mut input) {
-                        log_error(&format!("Failed to read input: {}", e), context);
-                        continue;
-                    }
-                    
-                    if let Ok(value) = input.trim().parse::<u8>() {
-                        if value > 0 {
-                            self.settings.left_max_cps = value;
-                            let left_executor = self.click_service.get_left_click_executor();
-                            left_executor.set_max_cps(value);
-                            
-                            if let Err(e) = self.settings.save() {
-                                log_error(&format!("Failed to save settings: {}", e), context);
-                            } else {
-                                log_info(&format!("Left click max CPS saved as {}", value), context);
-                            }
-                        }
-                    }
-                },
-                "2" => {
-                    self.clear_console();
-                    println!("=== Randomize Click Delay ===");
-                    println!("Current Status: {}", if self.settings.left_game_mode == "Combo" { "Enabled" } else { "Disabled" });
-                    println!("\nOptions:");
-                    println!("1. Disable (Uses constant speed based on Max CPS)");
-                    println!("2. Enable (Adds random variations for natural clicking)");
-                    
-                    let mut input = String::new();
-                    if let Err(e) = io::stdin().read_line(&mut input) {
-                        log_error(&format!("Failed to read input: {}", e), context);
-                        continue;
-                    }
-                    
-                    match input.trim() {
-                        "1" => {
-                            self.settings.left_game_mode = "Default".to_string();
-                            let left_executor = self.click_service.get_left_click_executor();
-                            left_executor.set_game_mode(GameMode::Default);
-                            if let Err(e) = self.settings.save() {
-                                log_error(&format!("Failed to save settings: {}", e), context);
-                            }
-                            println!("Randomize Click Delay disabled. Press Enter to continue...");
-                            let mut _input = String::new();
-                            let _ = io::stdin().read_line(&mut _input);
-                        },
-                        "2" => {
-                            self.settings.left_game_mode = "Combo".to_string();
-                            let left_executor = self.click_service.get_left_click_executor();
-                            left_executor.set_game_mode(GameMode::Combo);
-                            if let Err(e) = self.settings.save() {
-                                log_error(&format!("Failed to save settings: {}", e), context);
-                            }
-                            println!("Randomize Click Delay enabled. Press Enter to continue...");
-                            let mut _input = String::new();
-                            let _ = io::stdin().read_line(&mut _input);
-                        },
-                        _ => {
-                            println!("Invalid choice. Press Enter to continue...");
-                            let mut _input = String::new();
-                            let _ = io::stdin().read_line(&mut _input);
+            let mut choice = String::new();
+            if let Err(e) = io::stdin().read_line(&mut choice) {
+                log_error(&format!("Failed to read input: {}", e), context);
+                return;
+            }
+
+            match choice.trim() {
+                "1" => {
+                    if let Some(value) = Self::prompt_max_cps("Left", self.settings.left_max_cps) {
+                        self.settings.left_max_cps = value;
+                        let left_executor = self.click_service.get_left_click_executor();
+                        left_executor.set_max_cps(value);
+                        
+                        if let Err(e) = self.settings.save() {
+                            log_error(&format!("Failed to save settings: {}", e), context);
+                        } else {
+                            log_info(&format!("Left click max CPS saved as {}", value), context);
                         }
                     }
                 },
+                "2" => self.configure_game_mode(MouseButton::Left),
                 "3" => {
                     self.configure_left_click_delay_options();
                 },
                 "4" => return,
                 _ => {
-                    println!("Invalid option. Press Enter to continue...");
+                    style::error("Invalid option. Press Enter to continue...");
                     let mut _input = String::new();
                     let _ = io::stdin().read_line(&mut _input);
                     self.clear_console();
@@ -901,15 +2245,140 @@ impl Menu {
         }
     }
 
+    fn click_region_description(settings: &Settings) -> String {
+        match settings.click_region_mode.as_str() {
+            "Origin" => "top-left corner (0,0)".to_string(),
+            "Centered" => format!("{}x{} centered", settings.click_region_width, settings.click_region_height),
+            "Custom" => format!("{}x{} at ({},{})", settings.click_region_width, settings.click_region_height,
+                                settings.click_region_x, settings.click_region_y),
+            _ => "whole client area".to_string(),
+        }
+    }
+
+    fn configure_click_region(&mut self) {
+        self.clear_console();
+        style::header("Click Region");
+        println!("Background clicks land on a random point inside this region of the game window.");
+        println!("1. Whole Client Area");
+        println!("2. Centered Rectangle");
+        println!("3. Custom Rectangle");
+        println!("4. Top-Left Corner (0,0)");
+        println!("5. Back");
+
+        let parse_or = |input: String, current: i32| input.parse::<i32>().map_or(current, |px| px.clamp(0, limits::MAX_CLICK_REGION_PX));
+        match Self::prompt_line("\nSelect option: ").as_str() {
+            "1" => self.settings.click_region_mode = "ClientArea".to_string(),
+            "2" => {
+                self.settings.click_region_width = parse_or(Self::prompt_line(&format!("Width in pixels (current: {}): ", self.settings.click_region_width)), self.settings.click_region_width);
+                self.settings.click_region_height = parse_or(Self::prompt_line(&format!("Height in pixels (current: {}): ", self.settings.click_region_height)), self.settings.click_region_height);
+                self.settings.click_region_mode = "Centered".to_string();
+            },
+            "3" => {
+                self.settings.click_region_x = parse_or(Self::prompt_line(&format!("Left edge in pixels (current: {}): ", self.settings.click_region_x)), self.settings.click_region_x);
+                self.settings.click_region_y = parse_or(Self::prompt_line(&format!("Top edge in pixels (current: {}): ", self.settings.click_region_y)), self.settings.click_region_y);
+                self.settings.click_region_width = parse_or(Self::prompt_line(&format!("Width in pixels (current: {}): ", self.settings.click_region_width)), self.settings.click_region_width);
+                self.settings.click_region_height = parse_or(Self::prompt_line(&format!("Height in pixels (current: {}): ", self.settings.click_region_height)), self.settings.click_region_height);
+                self.settings.click_region_mode = "Custom".to_string();
+            },
+            "4" => self.settings.click_region_mode = "Origin".to_string(),
+            _ => {},
+        }
+    }
+
+    fn configure_click_profile(&mut self, button: MouseButton) {
+        let context = "Menu::configure_click_profile";
+        let current = match button {
+            MouseButton::Right => self.settings.right_click_profile.clone(),
+            _ => self.settings.left_click_profile.clone(),
+        };
+
+        self.clear_console();
+        style::header(&format!("{:?} Click Human Profile", button));
+        println!("A recorded profile replays your own click intervals instead of Max CPS and the synthetic delays.");
+        println!("Current: {}", if current.is_empty() { "Off" } else { &current });
+        println!("1. Record New Profile");
+        println!("2. Use Existing Profile");
+        println!("3. Turn Off");
+        println!("4. Back");
+
+        let selected = match Self::prompt_line("\nSelect option: ").as_str() {
+            "1" => {
+                let name = Self::prompt_line("Profile name: ");
+                if Settings::get_click_profile_path(&name).is_err() {
+                    style::error("Invalid profile name.");
+                    None
+                } else {
+                    println!("Click {:?} at your normal pace for {} seconds. Recording starts in 3 seconds...", button, CLICK_PROFILE_RECORD_SECS);
+                    thread::sleep(Duration::from_secs(3));
+                    println!("Recording...");
+                    let profile = ClickProfile::record(button.virtual_key(), Duration::from_secs(CLICK_PROFILE_RECORD_SECS));
+                    println!("Captured {} intervals (average {:.1} CPS).",
+                             profile.sample_count(),
+                             if profile.mean_micros() == 0 { 0.0 } else { 1_000_000.0 / profile.mean_micros() as f64 });
+                    if !profile.is_usable() {
+                        println!("That is fewer than {} intervals, so synthetic delays will be used until you record again.", MIN_CLICK_PROFILE_SAMPLES);
+                    }
+
+                    match profile.save(&name) {
+                        Ok(()) => Some(name),
+                        Err(e) => {
+                            log_error(&format!("Failed to save click profile: {}", e), context);
+                            style::error(&format!("Failed to save profile: {}", e));
+                            None
+                        }
+                    }
+                }
+            },
+            "2" => {
+                let profiles = Settings::list_click_profiles().unwrap_or_default();
+                if profiles.is_empty() {
+                    println!("No recorded profiles yet.");
+                    None
+                } else {
+                    for (index, name) in profiles.iter().enumerate() {
+                        println!("{}. {}", index + 1, name);
+                    }
+                    let name = Self::prompt_profile_name(&profiles);
+                    if profiles.contains(&name) {
+                        Some(name)
+                    } else {
+                        println!("Profile '{}' not found.", name);
+                        None
+                    }
+                }
+            },
+            "3" => Some(String::new()),
+            _ => return,
+        };
+
+        if let Some(name) = selected {
+            self.click_service.set_click_profile(button, ClickProfile::load_usable(&name));
+            match button {
+                MouseButton::Right => self.settings.right_click_profile = name,
+                _ => self.settings.left_click_profile = name,
+            }
+        }
+
+        println!("Press Enter to continue...");
+        let mut _input = String::new();
+        let _ = io::stdin().read_line(&mut _input);
+    }
+
     fn configure_left_click_delay_options(&mut self) {
         let context = "Menu::configure_left_click_delay_options";
         
         loop {
             self.clear_console();
-            println!("=== Left Click Delay Options ===");
-            println!("1. Click Delay: {} microseconds", self.settings.left_click_delay_micros);
+            style::header("Left Click Delay Options");
+            println!("1. Click Delay: {}", Self::click_delay_description(&self.settings, self.settings.left_click_delay_micros));
             println!("2. Random Deviation: {} to {} microseconds", self.settings.left_random_deviation_min, self.settings.left_random_deviation_max);
-            println!("3. Back to Left Click Settings");
+            println!("3. Delay Distribution: {}", self.settings.left_delay_distribution);
+            println!("4. Minimum Delay: {} microseconds (effective {}, caps rate at {:.1} CPS)",
+                     self.settings.left_min_click_delay_micros,
+                     self.settings.effective_left_min_click_delay_micros(),
+                     1_000_000.0 / self.settings.effective_left_min_click_delay_micros() as f64);
+            println!("5. Human Click Profile: {}", if self.settings.left_click_profile.is_empty() { "Off" } else { &self.settings.left_click_profile });
+            println!("6. Back to Left Click Settings");
             print!("\nSelect option: ");
 
             if let Err(e) = io::stdout().flush() {
@@ -942,7 +2411,7 @@ impl Menu {
                             self.clear_console();
                         }
                     } else {
-                        println!("Invalid number. Press Enter to continue...");
+                        style::error("Invalid number. Press Enter to continue...");
                         let mut _input = String::new();
                         let _ = io::stdin().read_line(&mut _input);
                         self.clear_console();
@@ -959,7 +2428,7 @@ impl Menu {
                     let min_value = if let Ok(value) = min_input.trim().parse::<i32>() {
                         value
                     } else {
-                        println!("Invalid number. Using current value.");
+                        style::error("Invalid number. Using current value.");
                         self.settings.left_random_deviation_min
                     };
                     
@@ -978,7 +2447,7 @@ impl Menu {
                             self.settings.left_random_deviation_max
                         }
                     } else {
-                        println!("Invalid number. Using current value.");
+                        style::error("Invalid number. Using current value.");
                         self.settings.left_random_deviation_max
                     };
                     
@@ -986,9 +2455,38 @@ impl Menu {
                     self.settings.left_random_deviation_max = max_value;
                     self.clear_console();
                 },
-                "3" => return,
+                "3" => {
+                    self.settings.left_delay_distribution = if self.settings.left_delay_distribution == "Gaussian" {
+                        "Uniform".to_string()
+                    } else {
+                        "Gaussian".to_string()
+                    };
+                    self.clear_console();
+                },
+                "4" => {
+                    println!("The effective rate is the lower of Max CPS and 1,000,000 / minimum delay.");
+                    println!("0 uses the global minimum ({} microseconds); lower values are raised to it.", self.settings.effective_min_click_delay_micros());
+                    println!("Enter minimum delay in microseconds (current: {}): ", self.settings.left_min_click_delay_micros);
+                    let mut input = String::new();
+                    if let Err(e) = io::stdin().read_line(&mut input) {
+                        log_error(&format!("Failed to read input: {}", e), context);
+                        continue;
+                    }
+
+                    match input.trim().parse::<u64>() {
+                        Ok(value) => self.settings.left_min_click_delay_micros = value,
+                        Err(_) => {
+                            style::error("Invalid number. Press Enter to continue...");
+                            let mut _input = String::new();
+                            let _ = io::stdin().read_line(&mut _input);
+                        }
+                    }
+                    self.clear_console();
+                },
+                "5" => self.configure_click_profile(MouseButton::Left),
+                "6" => return,
                 _ => {
-                    println!("Invalid option. Press Enter to continue...");
+                    style::error("Invalid option. Press Enter to continue...");
                     let mut _input = String::new();
                     let _ = io::stdin().read_line(&mut _input);
                     self.clear_console();
@@ -997,14 +2495,68 @@ impl Menu {
         }
     }
 
+    fn configure_game_mode(&mut self, button: MouseButton) {
+        let context = "Menu::configure_game_mode";
+
+        self.clear_console();
+        let current = match button {
+            MouseButton::Left | MouseButton::Middle | MouseButton::X1 | MouseButton::X2 => &self.settings.left_game_mode,
+            MouseButton::Right => &self.settings.right_game_mode,
+        };
+        style::header("Click Pattern");
+        println!("Current Pattern: {}", current);
+        println!("\nOptions:");
+        println!("1. Default (Uses constant speed based on Max CPS)");
+        println!("2. Combo (Adds random variations for natural clicking)");
+        println!("3. Butterfly (Tight click pairs followed by a longer gap)");
+        println!("4. Jitter (High-variance delays with occasional micro-stutters)");
+
+        let mut input = String::new();
+        if let Err(e) = io::stdin().read_line(&mut input) {
+            log_error(&format!("Failed to read input: {}", e), context);
+            return;
+        }
+
+        let mode = match input.trim() {
+            "1" => GameMode::Default,
+            "2" => GameMode::Combo,
+            "3" => GameMode::Butterfly,
+            "4" => GameMode::Jitter,
+            _ => {
+                style::error("Invalid choice. Press Enter to continue...");
+                let mut _input = String::new();
+                let _ = io::stdin().read_line(&mut _input);
+                return;
+            }
+        };
+
+        match button {
+            MouseButton::Left | MouseButton::Middle | MouseButton::X1 | MouseButton::X2 => {
+                self.settings.left_game_mode = mode.as_setting().to_string();
+                self.click_service.get_left_click_executor().set_game_mode(mode);
+            },
+            MouseButton::Right => {
+                self.settings.right_game_mode = mode.as_setting().to_string();
+                self.click_service.get_right_click_executor().set_game_mode(mode);
+            }
+        }
+
+        if let Err(e) = self.settings.save() {
+            log_error(&format!("Failed to save settings: {}", e), context);
+        }
+        println!("Click pattern set to {}. Press Enter to continue...", mode.as_setting());
+        let mut _input = String::new();
+        let _ = io::stdin().read_line(&mut _input);
+    }
+
     fn configure_right_click_settings(&mut self) {
         let context = "Menu::configure_right_click_settings";
         
         loop {
             self.clear_console();
-            println!("=== Right Click Settings ===");
+            style::header("Right Click Settings");
             println!("1. Max CPS: {} (Clicks Per Second)", self.settings.right_max_cps);
-            println!("2. Randomize Click Delay: {}", if self.settings.right_game_mode == "Combo" { "Enabled" } else { "Disabled" });
+            println!("2. Click Pattern: {}", self.settings.right_game_mode);
             println!("3. Click Delay Options");
             println!("4. Back to Advanced Settings");
 
@@ -1021,76 +2573,24 @@ impl Menu {
 
             match choice.trim() {
                 "1" => {
-                    println!("Enter new Max CPS (Clicks Per Second): ");
-                    let mut input = String::new();
-                    if let Err(e) = io::stdin().read_line(&mut input) {
-                        log_error(&format!("Failed to read input: {}", e), context);
-                        continue;
-                    }
-                    
-                    if let Ok(value) = input.trim().parse::<u8>() {
-                        if value > 0 {
-                            self.settings.right_max_cps = value;
-                            
-                            let right_executor = self.click_service.get_right_click_executor();
-                            right_executor.set_max_cps(value);
-                            
-                            if let Err(e) = self.settings.save() {
-                                log_error(&format!("Failed to save settings: {}", e), context);
-                            }
-                        }
-                    }
-                },
-                "2" => {
-                    self.clear_console();
-                    println!("=== Randomize Click Delay ===");
-                    println!("Current Status: {}", if self.settings.right_game_mode == "Combo" { "Enabled" } else { "Disabled" });
-                    println!("\nOptions:");
-                    println!("1. Disable (Uses constant speed based on Max CPS)");
-                    println!("2. Enable (Adds random variations for natural clicking)");
-                    
-                    let mut input = String::new();
-                    if let Err(e) = io::stdin().read_line(&mut input) {
-                        log_error(&format!("Failed to read input: {}", e), context);
-                        continue;
-                    }
-                    
-                    match input.trim() {
-                        "1" => {
-                            self.settings.right_game_mode = "Default".to_string();
-                            let right_executor = self.click_service.get_right_click_executor();
-                            right_executor.set_game_mode(GameMode::Default);
-                            if let Err(e) = self.settings.save() {
-                                log_error(&format!("Failed to save settings: {}", e), context);
-                            }
-                            println!("Randomize Click Delay disabled. Press Enter to continue...");
-                            let mut _input = String::new();
-                            let _ = io::stdin().read_line(&mut _input);
-                        },
-                        "2" => {
-                            self.settings.right_game_mode = "Combo".to_string();
-                            let right_executor = self.click_service.get_right_click_executor();
-                            right_executor.set_game_mode(GameMode::Combo);
-                            if let Err(e) = self.settings.save() {
-                                log_error(&format!("Failed to save settings: {}", e), context);
-                            }
-                            println!("Randomize Click Delay enabled. Press Enter to continue...");
-                            let mut _input = String::new();
-                            let _ = io::stdin().read_line(&mut _input);
-                        },
-                        _ => {
-                            println!("Invalid choice. Press Enter to continue...");
-                            let mut _input = String::new();
-                            let _ = io::stdin().read_line(&mut _input);
+                    if let Some(value) = Self::prompt_max_cps("Right", self.settings.right_max_cps) {
+                        self.settings.right_max_cps = value;
+                        
+                        let right_executor = self.click_service.get_right_click_executor();
+                        right_executor.set_max_cps(value);
+                        
+                        if let Err(e) = self.settings.save() {
+                            log_error(&format!("Failed to save settings: {}", e), context);
                         }
                     }
                 },
+                "2" => self.configure_game_mode(MouseButton::Right),
                 "3" => {
                     self.configure_right_click_delay_options();
                 },
                 "4" => return,
                 _ => {
-                    println!("Invalid option. Press Enter to continue...");
+                    style::error("Invalid option. Press Enter to continue...");
                     let mut _input = String::new();
                     let _ = io::stdin().read_line(&mut _input);
                     self.clear_console();
@@ -1104,10 +2604,16 @@ impl Menu {
         
         loop {
             self.clear_console();
-            println!("=== Right Click Delay Options ===");
-            println!("1. Click Delay: {} microseconds", self.settings.right_click_delay_micros);
+            style::header("Right Click Delay Options");
+            println!("1. Click Delay: {}", Self::click_delay_description(&self.settings, self.settings.right_click_delay_micros));
             println!("2. Random Deviation: {} to {} microseconds", self.settings.right_random_deviation_min, self.settings.right_random_deviation_max);
-            println!("3. Back to Right Click Settings");
+            println!("3. Delay Distribution: {}", self.settings.right_delay_distribution);
+            println!("4. Minimum Delay: {} microseconds (effective {}, caps rate at {:.1} CPS)",
+                     self.settings.right_min_click_delay_micros,
+                     self.settings.effective_right_min_click_delay_micros(),
+                     1_000_000.0 / self.settings.effective_right_min_click_delay_micros() as f64);
+            println!("5. Human Click Profile: {}", if self.settings.right_click_profile.is_empty() { "Off" } else { &self.settings.right_click_profile });
+            println!("6. Back to Right Click Settings");
             print!("\nSelect option: ");
 
             if let Err(e) = io::stdout().flush() {
@@ -1140,7 +2646,7 @@ impl Menu {
                             self.clear_console();
                         }
                     } else {
-                        println!("Invalid number. Press Enter to continue...");
+                        style::error("Invalid number. Press Enter to continue...");
                         let mut _input = String::new();
                         let _ = io::stdin().read_line(&mut _input);
                         self.clear_console();
@@ -1157,7 +2663,7 @@ impl Menu {
                     let min_value = if let Ok(value) = min_input.trim().parse::<i32>() {
                         value
                     } else {
-                        println!("Invalid number. Using current value.");
+                        style::error("Invalid number. Using current value.");
                         self.settings.right_random_deviation_min
                     };
                     
@@ -1176,7 +2682,7 @@ impl Menu {
                             self.settings.right_random_deviation_max
                         }
                     } else {
-                        println!("Invalid number. Using current value.");
+                        style::error("Invalid number. Using current value.");
                         self.settings.right_random_deviation_max
                     };
                     
@@ -1184,9 +2690,38 @@ impl Menu {
                     self.settings.right_random_deviation_max = max_value;
                     self.clear_console();
                 },
-                "3" => return,
+                "3" => {
+                    self.settings.right_delay_distribution = if self.settings.right_delay_distribution == "Gaussian" {
+                        "Uniform".to_string()
+                    } else {
+                        "Gaussian".to_string()
+                    };
+                    self.clear_console();
+                },
+                "4" => {
+                    println!("The effective rate is the lower of Max CPS and 1,000,000 / minimum delay.");
+                    println!("0 uses the global minimum ({} microseconds); lower values are raised to it.", self.settings.effective_min_click_delay_micros());
+                    println!("Enter minimum delay in microseconds (current: {}): ", self.settings.right_min_click_delay_micros);
+                    let mut input = String::new();
+                    if let Err(e) = io::stdin().read_line(&mut input) {
+                        log_error(&format!("Failed to read input: {}", e), context);
+                        continue;
+                    }
+
+                    match input.trim().parse::<u64>() {
+                        Ok(value) => self.settings.right_min_click_delay_micros = value,
+                        Err(_) => {
+                            style::error("Invalid number. Press Enter to continue...");
+                            let mut _input = String::new();
+                            let _ = io::stdin().read_line(&mut _input);
+                        }
+                    }
+                    self.clear_console();
+                },
+                "5" => self.configure_click_profile(MouseButton::Right),
+                "6" => return,
                 _ => {
-                    println!("Invalid option. Press Enter to continue...");
+                    style::error("Invalid option. Press Enter to continue...");
                     let mut _input = String::new();
                     let _ = io::stdin().read_line(&mut _input);
                     self.clear_console();
@@ -1195,6 +2730,35 @@ impl Menu {
         }
     }
 
+    fn click_method_name(click_strategy: &str) -> &'static str {
+        match click_strategy {
+            "SendInput" => "Foreground (SendInput)",
+            _ => "Background (PostMessage)",
+        }
+    }
+
+    fn key_code_to_virtual_key(code: KeyCode) -> Option<i32> {
+        match code {
+            KeyCode::Char(c) if c.is_ascii_alphabetic() => Some(c.to_ascii_uppercase() as i32),
+            KeyCode::Char(c) if c.is_ascii_digit() => Some(c as i32),
+            KeyCode::F(n) if (1..=12).contains(&n) => Some(0x70 + n as i32 - 1),
+            KeyCode::Left => Some(0x25),
+            KeyCode::Up => Some(0x26),
+            KeyCode::Right => Some(0x27),
+            KeyCode::Down => Some(0x28),
+            KeyCode::Pause => Some(0x13),
+            KeyCode::PageUp => Some(0x21),
+            KeyCode::PageDown => Some(0x22),
+            KeyCode::End => Some(0x23),
+            KeyCode::Home => Some(0x24),
+            KeyCode::Insert => Some(0x2D),
+            KeyCode::Delete => Some(0x2E),
+            KeyCode::Char('+') | KeyCode::Char('=') => Some(0xBB),
+            KeyCode::Char('-') | KeyCode::Char('_') => Some(0xBD),
+            _ => None,
+        }
+    }
+
     fn get_key_name(key: i32) -> String {
         match key {
             0x01 => "Left Mouse Button".to_string(),
@@ -1209,178 +2773,348 @@ impl Menu {
             0x0B => "Mouse Button 11".to_string(),
             0x0C => "Mouse Button 12".to_string(),
 
+            0x13 => "Pause".to_string(),
+            0x21 => "Page Up".to_string(),
+            0x22 => "Page Down".to_string(),
+            0x23 => "End".to_string(),
+            0x24 => "Home".to_string(),
+            0x2D => "Insert".to_string(),
+            0x2E => "Delete".to_string(),
+            0x25 => "Left Arrow".to_string(),
+            0x26 => "Up Arrow".to_string(),
+            0x27 => "Right Arrow".to_string(),
+            0x28 => "Down Arrow".to_string(),
+            0x70..=0x7B => format!("Key F{}", key - 0x70 + 1),
+            0x6B => "Numpad +".to_string(),
+            0x6D => "Numpad -".to_string(),
+            0xBB => "Key +/=".to_string(),
+            0xBD => "Key -".to_string(),
+
             0xA0..=0xB3 => format!("Special Button (0x{:02X})", key),
-            0x41..=0x5A => format!("Key {}", key as u8 as char),
+            0x30..=0x39 | 0x41..=0x5A => format!("Key {}", key as u8 as char),
             _ => format!("Button Code 0x{:02X}", key),
         }
     }
 
-    fn apply_settings(&mut self) {
-        let mut settings = match Settings::load() {
-            Ok(s) => s,
-            Err(_) => Settings::default(),
-        };
-        
-        if let Some(left_executor) = Arc::get_mut(&mut self.click_executor) {
+    fn apply_settings(&self) {
+        let settings = Settings::load().unwrap_or_default();
+        self.apply_to_executors(&settings);
+
+        if let Err(e) = settings.save() {
+            log_error(&format!("Failed to save settings: {}", e), "Menu::apply_settings");
+        }
+    }
+
+    fn apply_to_executors(&self, settings: &Settings) {
+        let left_mode = GameMode::from_setting(&settings.left_game_mode);
+        for left_executor in [Arc::clone(&self.click_executor), self.click_service.get_left_click_executor()] {
             left_executor.set_max_cps(settings.left_max_cps);
-            
-            let mode = match settings.left_game_mode.as_str() {
-                "Combo" => GameMode::Combo,
-                _ => GameMode::Default,
-            };
-            left_executor.set_game_mode(mode);
-            
-            settings.left_game_mode = settings.left_game_mode.clone();
+            left_executor.set_game_mode(left_mode);
         }
-        
+
+        let right_executor = self.click_service.get_right_click_executor();
+        right_executor.set_max_cps(settings.right_max_cps);
+        right_executor.set_game_mode(GameMode::from_setting(&settings.right_game_mode));
+
         if let Ok(mut delay_provider) = self.click_service.delay_provider.lock() {
             if delay_provider.burst_mode != settings.burst_mode {
                 delay_provider.toggle_burst_mode();
             }
         }
-        
-        if let Err(e) = settings.save() {
-            log_error(&format!("Failed to save settings: {}", e), "Menu::apply_settings");
-        }
     }
 
-    fn toggle_service(&self) {
-        static mut IS_ACTIVE: bool = false;
-        
-        unsafe {
-            IS_ACTIVE = !IS_ACTIVE;
-            
-            if IS_ACTIVE {
-                log_info("AutoClicker Enabled", "Menu::toggle_service");
-                self.click_executor.set_active(true);
-                
-                if self.click_mode == ClickMode::Both || self.click_mode == ClickMode::RightClick {
-                    self.click_service.get_right_click_executor().set_active(true);
-                }
-                
-                if self.click_mode == ClickMode::Both || self.click_mode == ClickMode::LeftClick {
-                    self.click_service.get_left_click_executor().set_active(true);
-                }
-            } else {
-                log_info("AutoClicker Disabled", "Menu::toggle_service");
-                self.click_executor.set_active(false);
-                self.click_service.get_left_click_executor().set_active(false);
-                self.click_service.get_right_click_executor().set_active(false);
+    fn apply_click_mode(click_mode: ClickMode, active: bool, left_executor: &ClickExecutor, right_executor: &ClickExecutor) {
+        if !active {
+            left_executor.set_active(false);
+            right_executor.set_active(false);
+            return;
+        }
+
+        match click_mode {
+            ClickMode::LeftClick => {
+                left_executor.set_active(true);
+                left_executor.set_mouse_button(MouseButton::Left);
+                right_executor.set_active(false);
+            },
+            ClickMode::RightClick => {
+                right_executor.set_active(true);
+                right_executor.set_mouse_button(MouseButton::Right);
+                left_executor.set_active(false);
+            },
+            ClickMode::Both => {
+                left_executor.set_active(true);
+                left_executor.set_mouse_button(MouseButton::Left);
+                right_executor.set_active(true);
+                right_executor.set_mouse_button(MouseButton::Right);
+            },
+            ClickMode::ExtraButton(button) => {
+                left_executor.set_active(true);
+                left_executor.set_mouse_button(button);
+                right_executor.set_active(false);
             }
         }
     }
 
+    fn apply_button(executor: &ClickExecutor, button: MouseButton, active: bool) {
+        executor.set_active(active);
+        if active {
+            executor.set_mouse_button(button);
+        }
+    }
+
     fn start_toggle_monitor(&self) {
-        let toggle_key = self.toggle_key;
         let left_executor = Arc::clone(&self.click_service.get_left_click_executor());
         let right_executor = Arc::clone(&self.click_service.get_right_click_executor());
+        let click_service = Arc::clone(&self.click_service);
 
         thread::spawn(move || {
-            let mut was_pressed = false;
-            let mut is_active = false;
+            let mut shared_state = ToggleState::new();
+            let mut left_state = ToggleState::new();
+            let mut right_state = ToggleState::new();
+            let mut was_split = false;
+            let mut panic_was_down = false;
+            let mut pressed_keys = PressedKeys::default();
+
+            let raw_input = if Settings::load().unwrap_or_default().toggle_detector == "RawInput" {
+                let listener = RawInputListener::start();
+                if listener.is_none() {
+                    log_error("Raw input toggle detector unavailable, falling back to polling", "Menu::start_toggle_monitor");
+                }
+                listener
+            } else {
+                None
+            };
 
             loop {
-                let settings = Settings::load().unwrap_or_default();
-                let click_mode = match settings.click_mode.as_str() {
-                    "LeftClick" => ClickMode::LeftClick,
-                    "RightClick" => ClickMode::RightClick,
-                    "Both" => ClickMode::Both,
-                    _ => ClickMode::LeftClick,
+                let settings = click_service.current_settings();
+                let click_mode = ClickMode::from_setting(&settings.click_mode);
+                let toggle_keys = ToggleKeys::for_mode(click_mode, &settings);
+                let input = ToggleInput {
+                    mode: ToggleMode::from_keyboard_hold(settings.keyboard_hold_mode),
+                    in_schedule: settings.is_within_schedule(),
+                    reset_window: Duration::from_millis(settings.double_press_reset_window_ms),
+                    now: Instant::now(),
                 };
 
-                let toggle_mode = if settings.keyboard_hold_mode {
-                    ToggleMode::KeyboardHold
-                } else {
-                    ToggleMode::MouseHold
+                // Switching between one shared key and per-button keys starts both buttons disarmed.
+                let is_split = matches!(toggle_keys, ToggleKeys::Split { .. });
+                if is_split != was_split {
+                    shared_state = ToggleState::new();
+                    left_state = ToggleState::new();
+                    right_state = ToggleState::new();
+                    Self::apply_click_mode(click_mode, false, &left_executor, &right_executor);
+                    was_split = is_split;
+                }
+
+                let keys: &dyn KeyStateSource = match &raw_input {
+                    Some(listener) => {
+                        if let Some(event) = listener.next_event(Duration::from_millis(250)) {
+                            pressed_keys.record(event.virtual_key, event.pressed);
+                        }
+                        &pressed_keys
+                    },
+                    None => &SystemKeyState,
                 };
 
-                let is_pressed = unsafe { (GetAsyncKeyState(toggle_key) & 0x8000u16 as i16) != 0 };
-
-                match toggle_mode {
-                    ToggleMode::MouseHold => {
-                        if is_pressed && !was_pressed {
-                            is_active = !is_active;
-
-                            match click_mode {
-                                ClickMode::LeftClick => {
-                                    if is_active {
-                                        left_executor.set_active(true);
-                                        left_executor.set_mouse_button(MouseButton::Left);
-                                        right_executor.set_active(false);
-                                    } else {
-                                        left_executor.set_active(false);
-                                        right_executor.set_active(false);
-                                    }
-                                },
-                                ClickMode::RightClick => {
-                                    if is_active {
-                                        right_executor.set_active(true);
-                                        right_executor.set_mouse_button(MouseButton::Right);
-                                        left_executor.set_active(false);
-                                    } else {
-                                        left_executor.set_active(false);
-                                        right_executor.set_active(false);
-                                    }
-                                },
-                                ClickMode::Both => {
-                                    if is_active {
-                                        left_executor.set_active(true);
-                                        left_executor.set_mouse_button(MouseButton::Left);
-                                        right_executor.set_active(true);
-                                        right_executor.set_mouse_button(MouseButton::Right);
-                                    } else {
-                                        left_executor.set_active(false);
-                                        right_executor.set_active(false);
-                                    }
-                                }
-                            }
+                // panic_stop disables the click controllers, so a hold key still down afterwards can't resume clicking.
+                let panic_down = settings.panic_key != 0 && keys.is_down(settings.panic_key);
+                if panic_down && !panic_was_down {
+                    click_service.panic_stop();
+                    shared_state = ToggleState::new();
+                    left_state = ToggleState::new();
+                    right_state = ToggleState::new();
+                }
+                panic_was_down = panic_down;
+
+                match toggle_keys {
+                    ToggleKeys::Shared(toggle_key) => match shared_state.poll(keys, toggle_key, input) {
+                        ToggleTransition::Unchanged => {},
+                        ToggleTransition::Reset => {
+                            log_info("Double-press reset gesture detected", "Menu::start_toggle_monitor");
+                            left_executor.set_active(false);
+                            right_executor.set_active(false);
+                            click_service.reset_state();
+                            Self::apply_click_mode(click_mode, shared_state.is_active(), &left_executor, &right_executor);
+                        },
+                        ToggleTransition::ScheduleEnded => {
+                            log_info("Active schedule ended, disabling clicking", "Menu::start_toggle_monitor");
+                            Self::apply_click_mode(click_mode, false, &left_executor, &right_executor);
+                        },
+                        ToggleTransition::Activated => {
+                            thread::sleep(settings.random_activation_delay());
+                            Self::apply_click_mode(click_mode, shared_state.is_active(), &left_executor, &right_executor);
+                        },
+                        ToggleTransition::Deactivated => {
+                            Self::apply_click_mode(click_mode, shared_state.is_active(), &left_executor, &right_executor);
                         }
                     },
-                    ToggleMode::KeyboardHold => {
-                        if is_pressed != is_active {
-                            is_active = is_pressed;
-
-                            match click_mode {
-                                ClickMode::LeftClick => {
-                                    if is_active {
-                                        left_executor.set_active(true);
-                                        left_executor.set_mouse_button(MouseButton::Left);
-                                        right_executor.set_active(false);
-                                    } else {
-                                        left_executor.set_active(false);
-                                        right_executor.set_active(false);
-                                    }
-                                },
-                                ClickMode::RightClick => {
-                                    if is_active {
-
-                                        right_executor.set_active(true);
-                                        right_executor.set_mouse_button(MouseButton::Right);
-                                        left_executor.set_active(false);
-                                    } else {
-                                        left_executor.set_active(false);
-                                        right_executor.set_active(false);
-                                    }
-                                },
-                                ClickMode::Both => {
-                                    if is_active {
-                                        left_executor.set_active(true);
-                                        left_executor.set_mouse_button(MouseButton::Left);
-                                        right_executor.set_active(true);
-                                        right_executor.set_mouse_button(MouseButton::Right);
-                                    } else {
-                                        left_executor.set_active(false);
-                                        right_executor.set_active(false);
-                                    }
-                                }
-                            }
+                    ToggleKeys::Split { left, right } => {
+                        let left_transition = left_state.poll(keys, left, input);
+                        let right_transition = right_state.poll(keys, right, input);
+                        let transitions = [left_transition, right_transition];
+
+                        // Either key's double press resets the whole service, so both buttons are re-applied.
+                        let reset_fired = transitions.contains(&ToggleTransition::Reset);
+                        if reset_fired {
+                            log_info("Double-press reset gesture detected", "Menu::start_toggle_monitor");
+                            click_service.reset_state();
+                        }
+                        if transitions.contains(&ToggleTransition::ScheduleEnded) {
+                            log_info("Active schedule ended, disabling clicking", "Menu::start_toggle_monitor");
+                        }
+
+                        // Only arming waits, so the first click isn't locked to the key-down instant.
+                        if transitions.contains(&ToggleTransition::Activated) {
+                            thread::sleep(settings.random_activation_delay());
+                        }
+
+                        if reset_fired || left_transition != ToggleTransition::Unchanged {
+                            Self::apply_button(&left_executor, MouseButton::Left, left_state.is_active());
+                        }
+                        if reset_fired || right_transition != ToggleTransition::Unchanged {
+                            Self::apply_button(&right_executor, MouseButton::Right, right_state.is_active());
                         }
                     }
                 }
 
-                was_pressed = is_pressed;
-                thread::sleep(Duration::from_millis(10));
+                // Raw input already blocks on the next event; polling picks its interval from whether anything is armed.
+                if raw_input.is_none() {
+                    let armed = click_service.is_left_armed() || click_service.is_right_armed();
+                    let poll_ms = if armed { settings.toggle_poll_active_ms } else { settings.toggle_poll_idle_ms };
+                    thread::sleep(Duration::from_millis(poll_ms.max(1)));
+                }
             }
         });
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::backend::HWND;
+    use crate::input::click_region::{ClickPoint, ClickRegion};
+    use crate::input::click_service::ClickServiceConfig;
+    use crate::input::click_strategy::{ClickSink, ClickStrategy, ClickStrategyKind};
+    use crate::input::thread_controller::Sleeper;
+
+    // Accepts every click, standing in for a target window.
+    struct AcceptingSink;
+
+    impl ClickStrategy for AcceptingSink {
+        fn name(&self) -> &'static str {
+            "Accepting"
+        }
+
+        fn press(&self, _hwnd: HWND, _button: MouseButton, _point: ClickPoint) -> bool {
+            true
+        }
+
+        fn release(&self, _hwnd: HWND, _button: MouseButton, _point: ClickPoint) -> bool {
+            true
+        }
+    }
+
+    impl ClickSink for AcceptingSink {
+        fn strategy(&self, _kind: ClickStrategyKind) -> &dyn ClickStrategy {
+            self
+        }
+    }
+
+    struct NoSleep;
+
+    impl Sleeper for NoSleep {
+        fn sleep(&self, _duration: Duration) {}
+    }
+
+    // Built directly so the toggle monitor Menu::new starts stays out of the test.
+    fn menu(click_service: &Arc<ClickService>) -> Menu {
+        Menu {
+            click_service: Arc::clone(click_service),
+            click_executor: Arc::clone(&click_service.click_executor),
+            toggle_key: 0,
+            toggle_mode: ToggleMode::from_keyboard_hold(false),
+            click_mode: ClickMode::LeftClick,
+            settings: Settings::default(),
+            anti_cheat_acknowledged: false,
+        }
+    }
+
+    #[test]
+    fn apply_settings_changes_the_live_cps() {
+        let click_service = ClickService::new(ClickServiceConfig::default());
+        let menu = menu(&click_service);
+
+        menu.apply_to_executors(&Settings {
+            left_max_cps: 21,
+            right_max_cps: 22,
+            left_game_mode: "Butterfly".to_string(),
+            ..Settings::default()
+        });
+
+        assert_eq!(menu.click_executor.get_current_max_cps(), 21);
+        assert_eq!(click_service.get_left_click_executor().get_current_max_cps(), 21);
+        assert_eq!(click_service.get_right_click_executor().get_current_max_cps(), 22);
+        assert_eq!(click_service.get_left_click_executor().get_game_mode(), GameMode::Butterfly);
+
+        click_service.shutdown();
+    }
+
+    #[test]
+    fn toggle_transitions_arm_the_executors_for_each_click_mode() {
+        let left_executor = ClickExecutor::with_io(Box::new(AcceptingSink), Box::new(NoSleep));
+        let right_executor = ClickExecutor::with_io(Box::new(AcceptingSink), Box::new(NoSleep));
+        let cases = [
+            (ClickMode::LeftClick, (true, false)),
+            (ClickMode::RightClick, (false, true)),
+            (ClickMode::Both, (true, true)),
+        ];
+
+        for mode in [ToggleMode::MouseHold, ToggleMode::KeyboardHold] {
+            for (click_mode, expected) in cases {
+                let mut state = ToggleState::new();
+                let input = ToggleInput { mode, in_schedule: true, reset_window: Duration::ZERO, now: Instant::now() };
+
+                assert_eq!(state.step(true, input), ToggleTransition::Activated);
+                Menu::apply_click_mode(click_mode, state.is_active(), &left_executor, &right_executor);
+                assert_eq!((left_executor.is_active(), right_executor.is_active()), expected, "{:?} {:?} armed", mode, click_mode);
+
+                // Mouse hold disarms on the next press, keyboard hold on release.
+                let released = state.step(false, input);
+                let later = ToggleInput { now: input.now + Duration::from_secs(1), ..input };
+                let transition = if mode == ToggleMode::MouseHold { state.step(true, later) } else { released };
+                assert_eq!(transition, ToggleTransition::Deactivated);
+                Menu::apply_click_mode(click_mode, state.is_active(), &left_executor, &right_executor);
+                assert_eq!((left_executor.is_active(), right_executor.is_active()), (false, false), "{:?} {:?} disarmed", mode, click_mode);
+            }
+        }
+    }
+
+    #[test]
+    fn the_click_limit_stops_a_session_on_the_limiting_click() {
+        let session_limit = SessionLimit { max_secs: 0, max_clicks: 5 };
+        let executor = ClickExecutor::with_io(Box::new(AcceptingSink), Box::new(NoSleep));
+        executor.set_click_region(ClickRegion::Origin);
+        let hwnd: HWND = std::ptr::NonNull::dangling().as_ptr();
+
+        for _ in 0..4 {
+            assert!(executor.execute_click(hwnd));
+            assert_eq!(session_limit.exceeded(Duration::ZERO, executor.click_count()), None);
+        }
+
+        assert!(executor.execute_click(hwnd));
+        assert!(session_limit.exceeded(Duration::ZERO, executor.click_count()).is_some());
+    }
+
+    #[test]
+    fn the_time_limit_stops_a_session_once_it_has_run_that_long() {
+        let session_limit = SessionLimit { max_secs: 10, max_clicks: 0 };
+        assert_eq!(session_limit.exceeded(Duration::from_millis(9_999), usize::MAX), None);
+        assert!(session_limit.exceeded(Duration::from_secs(10), 0).is_some());
+    }
+
+    #[test]
+    fn zero_limits_never_stop_a_session() {
+        let session_limit = SessionLimit { max_secs: 0, max_clicks: 0 };
+        assert_eq!(session_limit.exceeded(Duration::from_secs(u32::MAX as u64), usize::MAX), None);
+    }
 }
\ No newline at end of file
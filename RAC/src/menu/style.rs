@@ -0,0 +1,49 @@
+use crossterm::style::{Color, Stylize};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static COLOR_ENABLED: AtomicBool = AtomicBool::new(true);
+
+// Color stays off with --no-color, a non-empty NO_COLOR (https://no-color.org) or a Windows
+// console that can't process ANSI sequences.
+pub(crate) fn init(no_color: bool) {
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    #[cfg(windows)]
+    let ansi = crossterm::ansi_support::supports_ansi();
+    #[cfg(not(windows))]
+    let ansi = true;
+
+    COLOR_ENABLED.store(!no_color && !no_color_env && ansi, Ordering::Relaxed);
+}
+
+fn paint(text: &str, color: Color) -> String {
+    if COLOR_ENABLED.load(Ordering::Relaxed) {
+        text.with(color).to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+pub(crate) fn header(title: &str) {
+    let line = format!("=== {} ===", title);
+    if COLOR_ENABLED.load(Ordering::Relaxed) {
+        println!("{}", line.with(Color::Cyan).bold());
+    } else {
+        println!("{}", line);
+    }
+}
+
+pub(crate) fn state(enabled: bool) -> String {
+    if enabled { paint("Enabled", Color::Green) } else { paint("Disabled", Color::DarkGrey) }
+}
+
+pub(crate) fn success(message: &str) {
+    println!("{}", paint(message, Color::Green));
+}
+
+pub(crate) fn warning(message: &str) {
+    println!("{}", paint(message, Color::Yellow));
+}
+
+pub(crate) fn error(message: &str) {
+    println!("{}", paint(message, Color::Red));
+}
@@ -0,0 +1,41 @@
+use crate::logger::logger::{log_info, log_warn};
+use sysinfo::{ProcessesToUpdate, System};
+
+pub struct AntiCheatDetector {
+    process_names: Vec<String>,
+}
+
+impl AntiCheatDetector {
+    pub fn new(process_names: &[String]) -> Self {
+        Self {
+            process_names: process_names.iter().map(|name| name.to_lowercase()).collect(),
+        }
+    }
+
+    pub fn detect(&self) -> Vec<String> {
+        let context = "AntiCheatDetector::detect";
+
+        if self.process_names.is_empty() {
+            return Vec::new();
+        }
+
+        let mut sys = System::new();
+        sys.refresh_processes(ProcessesToUpdate::All, false);
+
+        let mut detected: Vec<String> = Vec::new();
+        for process in sys.processes().values() {
+            let name = process.name().to_string_lossy().to_string();
+            if self.process_names.contains(&name.to_lowercase()) && !detected.contains(&name) {
+                detected.push(name);
+            }
+        }
+
+        if detected.is_empty() {
+            log_info("No known anti-cheat processes detected", context);
+        } else {
+            log_warn(&format!("Detected anti-cheat processes: {}", detected.join(", ")), context);
+        }
+
+        detected
+    }
+}
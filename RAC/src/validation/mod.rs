@@ -1,3 +1,3 @@
+pub(crate) mod anti_cheat_detector;
 pub(crate) mod system_validator;
-mod validation_result;
-mod win32_point;
\ No newline at end of file
+mod validation_result;
\ No newline at end of file
@@ -1,8 +1,9 @@
-use crate::logger::logger::{log_error, log_info};
+use crate::config::data_dir;
+use crate::config::settings::Settings;
+use crate::input::backend::backend;
+use crate::logger::logger::{log_error, log_info, log_warn};
 use crate::validation::validation_result::ValidationResult;
 use std::path::PathBuf;
-use windows::Win32::Foundation::POINT;
-use windows::Win32::UI::WindowsAndMessaging::GetCursorPos;
 
 pub struct SystemRequirements {
     minimum_windows_version: i32,
@@ -12,7 +13,10 @@ pub struct SystemRequirements {
 impl Default for SystemRequirements {
     fn default() -> Self {
         let context = "SystemRequirements::default";
-        let rac_dir = dirs::data_local_dir().unwrap().join("RAC");
+        let rac_dir = data_dir::data_dir().unwrap_or_else(|e| {
+            log_error(&format!("Failed to create data directory: {}", e), context);
+            data_dir::base_dir().join("RAC")
+        });
         let logs_path = rac_dir.join("logs.txt");
 
         if !rac_dir.exists() {
@@ -71,8 +75,8 @@ impl SystemValidator {
 
     fn validate_operating_system(&self) -> ValidationResult {
         let context = "SystemValidator::validate_operating_system";
-        if !cfg!(windows) {
-            let error_msg = format!("Unsupported operating system. Required: Windows, Current: {}", std::env::consts::OS);
+        if !cfg!(any(windows, target_os = "linux")) {
+            let error_msg = format!("Unsupported operating system. Required: Windows or Linux, Current: {}", std::env::consts::OS);
             log_error(&error_msg, context);
             return ValidationResult::with_message(false, error_msg);
         }
@@ -81,6 +85,10 @@ impl SystemValidator {
 
     fn validate_windows_version(&self) -> ValidationResult {
         let context = "SystemValidator::validate_windows_version";
+        if !cfg!(windows) {
+            return ValidationResult::new(true);
+        }
+
         let version = os_info::get();
         let version_str = version.version().to_string();
         let major_version: i32 = match version_str.split('.').next().unwrap().parse() {
@@ -126,14 +134,18 @@ impl SystemValidator {
 
     fn validate_mouse_access(&self) -> ValidationResult {
         let context = "SystemValidator::validate_mouse_access";
-        unsafe {
-            let mut point = POINT { x: 0, y: 0 };
-            if GetCursorPos(&mut point as *mut _).is_err() {
-                let error_msg = "Failed to access mouse controls";
-                log_error(error_msg, context);
-                return ValidationResult::with_message(false, error_msg);
+        if backend().cursor_position().is_none() {
+            let settings = Settings::load().unwrap_or_default();
+            if !settings.requires_cursor_access() {
+                let warning_msg = "Failed to access mouse controls, continuing because PostMessage clicking does not need the cursor";
+                log_warn(warning_msg, context);
+                return ValidationResult::with_message(true, warning_msg);
             }
-            ValidationResult::new(true)
+
+            let error_msg = "Failed to access mouse controls";
+            log_error(error_msg, context);
+            return ValidationResult::with_message(false, error_msg);
         }
+        ValidationResult::new(true)
     }
 }
\ No newline at end of file
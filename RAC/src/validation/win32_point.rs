@@ -1,5 +0,0 @@
-#[repr(C)]
-pub struct Win32Point {
-    pub x: i32,
-    pub y: i32,
-}
\ No newline at end of file
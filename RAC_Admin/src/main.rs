@@ -3,9 +3,10 @@ use serde::{Serialize, Deserialize};
 use sha2::{Sha256, Digest};
 use std::io::{self, Write};
 use std::fs;
+use std::path::{Path, PathBuf};
 use aes_gcm::{Aes256Gcm, Key, KeyInit, Nonce};
 use aes_gcm::aead::Aead;
-use time::{Duration, OffsetDateTime};
+use time::{Date, Duration, Month, OffsetDateTime, Time, UtcOffset};
 use base64::{Engine as _, engine::general_purpose};
 use rand::Rng;
 use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey};
@@ -110,13 +111,36 @@ fn load_private_key() -> Result<RsaPrivateKey, Box<dyn std::error::Error>> {
     Ok(RsaPrivateKey::from_pkcs8_pem(PRIVATE_KEY)?)
 }
 
+fn is_expired(expires_at: i64, now: i64) -> bool {
+    now > expires_at
+}
+
+fn compute_expiry(now: OffsetDateTime, days_valid: u16) -> OffsetDateTime {
+    now + Duration::days(days_valid as i64)
+}
+
 fn create_license(
     private_key: &RsaPrivateKey,
     machine_id: &str,
     days_valid: u16,
 ) -> Result<License, Box<dyn std::error::Error>> {
-    let expires_at = OffsetDateTime::now_utc() + Duration::days(days_valid as i64);
+    create_license_at(private_key, machine_id, days_valid, OffsetDateTime::now_utc())
+}
+
+fn create_license_at(
+    private_key: &RsaPrivateKey,
+    machine_id: &str,
+    days_valid: u16,
+    now: OffsetDateTime,
+) -> Result<License, Box<dyn std::error::Error>> {
+    create_license_expiring(private_key, machine_id, compute_expiry(now, days_valid))
+}
 
+fn create_license_expiring(
+    private_key: &RsaPrivateKey,
+    machine_id: &str,
+    expires_at: OffsetDateTime,
+) -> Result<License, Box<dyn std::error::Error>> {
     let info = LicenseInfo {
         machine_id: machine_id.to_string(),
         expires_at: expires_at.unix_timestamp(),
@@ -140,6 +164,83 @@ fn create_license(
     })
 }
 
+enum Validity {
+    Days(u16),
+    Until(OffsetDateTime),
+}
+
+impl Validity {
+    fn create_license(&self, private_key: &RsaPrivateKey, machine_id: &str) -> Result<License, Box<dyn std::error::Error>> {
+        match self {
+            Validity::Days(days_valid) => create_license(private_key, machine_id, *days_valid),
+            Validity::Until(expires_at) => create_license_expiring(private_key, machine_id, *expires_at),
+        }
+    }
+}
+
+// A plain number is days valid; anything else must be an ISO-8601 date or date-time in the future.
+fn parse_validity(value: &str, now: OffsetDateTime) -> Result<Validity, String> {
+    let value = value.trim();
+    if let Ok(days_valid) = value.parse::<u16>() {
+        return Ok(Validity::Days(days_valid));
+    }
+
+    let expires_at = parse_expiry(value)?;
+    if expires_at <= now {
+        return Err(format!("expiry {} is in the past", value));
+    }
+    Ok(Validity::Until(expires_at))
+}
+
+// Accepts YYYY-MM-DD, which expires at the end of that day UTC, or YYYY-MM-DDTHH:MM[:SS] with an
+// optional Z or +HH:MM offset (UTC when omitted).
+fn parse_expiry(value: &str) -> Result<OffsetDateTime, String> {
+    let invalid = || format!("invalid expiry '{}', expected YYYY-MM-DD or YYYY-MM-DDTHH:MM[:SS][Z|+HH:MM]", value);
+    let number = |part: &str| part.parse::<u32>().map_err(|_| invalid());
+    // Narrowed with try_from, so an out-of-range field is rejected instead of wrapping into a valid one.
+    let field = |part: &str| number(part).and_then(|n| u8::try_from(n).map_err(|_| invalid()));
+    let offset_field = |part: &str| number(part).and_then(|n| i8::try_from(n).map_err(|_| invalid()));
+
+    let (date_part, time_part) = match value.split_once(['T', 't', ' ']) {
+        Some((date_part, time_part)) => (date_part, Some(time_part)),
+        None => (value, None),
+    };
+
+    let date_fields: Vec<&str> = date_part.split('-').collect();
+    let [year, month, day] = date_fields[..] else {
+        return Err(invalid());
+    };
+    let month = Month::try_from(field(month)?).map_err(|_| invalid())?;
+    let year = i32::try_from(number(year)?).map_err(|_| invalid())?;
+    let date = Date::from_calendar_date(year, month, field(day)?).map_err(|_| invalid())?;
+
+    let Some(time_part) = time_part else {
+        return Ok(date.with_time(Time::from_hms(23, 59, 59).map_err(|_| invalid())?).assume_utc());
+    };
+
+    let (clock, offset) = if let Some(clock) = time_part.strip_suffix(['Z', 'z']) {
+        (clock, UtcOffset::UTC)
+    } else if let Some(sign_index) = time_part.rfind(['+', '-']) {
+        let (clock, offset) = time_part.split_at(sign_index);
+        let sign = if offset.starts_with('-') { -1 } else { 1 };
+        let (hours, minutes) = offset[1..].split_once(':').unwrap_or((&offset[1..], "0"));
+        let offset = UtcOffset::from_hms(sign * offset_field(hours)?, sign * offset_field(minutes)?, 0).map_err(|_| invalid())?;
+        (clock, offset)
+    } else {
+        (time_part, UtcOffset::UTC)
+    };
+
+    let clock_fields: Vec<&str> = clock.split(':').collect();
+    let (hour, minute, second) = match clock_fields[..] {
+        [hour, minute] => (field(hour)?, field(minute)?, 0),
+        [hour, minute, second] => (field(hour)?, field(minute)?, field(second)?),
+        _ => return Err(invalid()),
+    };
+    let time = Time::from_hms(hour, minute, second).map_err(|_| invalid())?;
+
+    Ok(date.with_time(time).assume_offset(offset))
+}
+
 fn decrypt_license_data(encrypted_data: &[u8]) -> Result<String, Box<dyn std::error::Error>> {
     if encrypted_data.len() < 12 {
         return Err("Invalid encrypted data length".into());
@@ -160,6 +261,10 @@ fn decrypt_license_data(encrypted_data: &[u8]) -> Result<String, Box<dyn std::er
 }
 
 fn validate_license(license_path: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    validate_license_at(license_path, OffsetDateTime::now_utc().unix_timestamp())
+}
+
+fn validate_license_at(license_path: &str, now: i64) -> Result<bool, Box<dyn std::error::Error>> {
     let encrypted_data = fs::read(license_path)?;
     let license_data = decrypt_license_data(&encrypted_data)?;
     let license: License = serde_json::from_str(&license_data)?;
@@ -179,8 +284,7 @@ fn validate_license(license_path: &str) -> Result<bool, Box<dyn std::error::Erro
         &signature_bytes
     ) {
         Ok(_) => {
-            let now = OffsetDateTime::now_utc().unix_timestamp();
-            if now > license.info.expires_at {
+            if is_expired(license.info.expires_at, now) {
                 println!("License has expired!");
                 Ok(false)
             } else {
@@ -209,22 +313,119 @@ fn generate_license_flow() -> Result<(), Box<dyn std::error::Error>> {
     io::stdin().read_line(&mut machine_id)?;
     let machine_id = machine_id.trim();
 
-    print!("Enter Days Valid: ");
+    print!("Enter Days Valid or Expiry Date (YYYY-MM-DD or YYYY-MM-DDTHH:MM[:SS][Z|+HH:MM]): ");
     io::stdout().flush()?;
-    let mut days_valid = String::new();
-    io::stdin().read_line(&mut days_valid)?;
-    let days_valid: u16 = days_valid.trim().parse()?;
+    let mut validity = String::new();
+    io::stdin().read_line(&mut validity)?;
+    let validity = parse_validity(&validity, OffsetDateTime::now_utc())?;
 
-    let license = create_license(&private_key, machine_id, days_valid)?;
-    let license_json = serde_json::to_string_pretty(&license)?;
+    let license = validity.create_license(&private_key, machine_id)?;
+    let file_name = write_license_file(&license, Path::new("."))?;
+
+    println!("License generated successfully: {} (expires {})", file_name.display(), license.info.expires_at);
+    Ok(())
+}
+
+fn write_license_file(license: &License, out_dir: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let license_json = serde_json::to_string_pretty(license)?;
 
     let encrypted_data = encrypt_license_data(&license_json)?;
 
-    let file_name = format!("{}.license", machine_id);
+    let file_name = out_dir.join(format!("{}.license", license.info.machine_id));
     fs::write(&file_name, encrypted_data)?;
+    Ok(file_name)
+}
 
-    println!("License generated successfully: {}", file_name);
-    Ok(())
+const USAGE: &str = "Usage: rac_admin [generate --input <machines.csv> --out <directory>]
+
+  generate               Write one <machine_id>.license per CSV row
+  --input <file>         CSV of machine_id,days_valid rows (a header row and # comments are skipped);
+                         days_valid may also be an ISO-8601 expiry date such as 2026-11-30
+  --out <directory>      Where the license files are written (created if missing)
+
+With no arguments the interactive menu is shown.";
+
+struct BatchOptions {
+    input: PathBuf,
+    out_dir: PathBuf,
+}
+
+fn parse_batch_args(args: &[String]) -> Result<BatchOptions, String> {
+    let mut iter = args.iter();
+    match iter.next().map(String::as_str) {
+        Some("generate") => {}
+        Some(command) => return Err(format!("Unknown command: {}", command)),
+        None => return Err("Missing command".to_string()),
+    }
+
+    let mut input = None;
+    let mut out_dir = None;
+    while let Some(arg) = iter.next() {
+        let value = iter.next().cloned().ok_or_else(|| format!("Missing value for {}", arg));
+        match arg.as_str() {
+            "--input" => input = Some(PathBuf::from(value?)),
+            "--out" => out_dir = Some(PathBuf::from(value?)),
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+
+    Ok(BatchOptions {
+        input: input.ok_or("--input is required")?,
+        out_dir: out_dir.ok_or("--out is required")?,
+    })
+}
+
+fn parse_batch_row(line: &str, now: OffsetDateTime) -> Result<(String, Validity), String> {
+    let mut fields = line.split(',').map(str::trim);
+    let machine_id = fields.next().unwrap_or_default();
+    let days_valid = fields.next().ok_or("expected machine_id,days_valid")?;
+    if fields.next().is_some() {
+        return Err("expected exactly two columns".to_string());
+    }
+
+    // The machine ID becomes the file name, so anything that could escape the output directory is refused.
+    if machine_id.is_empty() || machine_id.contains(['/', '\\', ':']) || machine_id.starts_with('.') {
+        return Err(format!("invalid machine ID '{}'", machine_id));
+    }
+
+    let validity = parse_validity(days_valid, now)?;
+    Ok((machine_id.to_string(), validity))
+}
+
+// Returns the number of rows that failed.
+fn generate_batch(options: &BatchOptions) -> Result<usize, Box<dyn std::error::Error>> {
+    let csv = fs::read_to_string(&options.input)
+        .map_err(|e| format!("Failed to read {}: {}", options.input.display(), e))?;
+    fs::create_dir_all(&options.out_dir)
+        .map_err(|e| format!("Failed to create {}: {}", options.out_dir.display(), e))?;
+    let private_key = load_private_key()?;
+
+    let mut generated = 0;
+    let mut failed = 0;
+    for (index, line) in csv.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || (index == 0 && line.to_lowercase().starts_with("machine_id")) {
+            continue;
+        }
+
+        let result = parse_batch_row(line, OffsetDateTime::now_utc())
+            .map_err(|e| e.into())
+            .and_then(|(machine_id, validity)| validity.create_license(&private_key, &machine_id))
+            .and_then(|license| write_license_file(&license, &options.out_dir));
+        match result {
+            Ok(file_name) => {
+                generated += 1;
+                println!("Row {}: OK {}", index + 1, file_name.display());
+            }
+            Err(e) => {
+                failed += 1;
+                println!("Row {}: FAILED {}", index + 1, e);
+            }
+        }
+    }
+
+    println!("\nGenerated {} license(s), {} failed", generated, failed);
+    Ok(failed)
 }
 
 fn validate_license_flow() -> Result<(), Box<dyn std::error::Error>> {
@@ -243,6 +444,31 @@ fn validate_license_flow() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    if !args.is_empty() {
+        let options = match parse_batch_args(&args) {
+            Ok(options) => options,
+            Err(e) => {
+                eprintln!("{}\n\n{}", e, USAGE);
+                std::process::exit(2);
+            }
+        };
+
+        match generate_batch(&options) {
+            Ok(0) => return Ok(()),
+            Ok(_) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
     loop {
         print_menu();
 
@@ -261,3 +487,88 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2026-01-01 00:00:00 UTC
+    fn issued_at() -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp(1_767_225_600).unwrap()
+    }
+
+    #[test]
+    fn compute_expiry_adds_whole_days() {
+        assert_eq!(compute_expiry(issued_at(), 0), issued_at());
+        assert_eq!(compute_expiry(issued_at(), 30).unix_timestamp(), issued_at().unix_timestamp() + 30 * 86_400);
+    }
+
+    #[test]
+    fn license_expires_after_its_expiry_second() {
+        let expires_at = compute_expiry(issued_at(), 30).unix_timestamp();
+
+        assert!(!is_expired(expires_at, expires_at - 1));
+        assert!(!is_expired(expires_at, expires_at));
+        assert!(is_expired(expires_at, expires_at + 1));
+    }
+
+    #[test]
+    fn far_future_license_is_valid() {
+        let expires_at = compute_expiry(issued_at(), u16::MAX).unix_timestamp();
+        assert!(!is_expired(expires_at, OffsetDateTime::now_utc().unix_timestamp()));
+    }
+
+    #[test]
+    fn issued_license_validates_up_to_its_expiry() {
+        let out_dir = std::env::temp_dir().join(format!("rac-admin-tests-{}", std::process::id()));
+        fs::create_dir_all(&out_dir).unwrap();
+
+        let private_key = load_private_key().unwrap();
+        let license = create_license_at(&private_key, "expiry-boundary", 1, issued_at()).unwrap();
+        let expires_at = license.info.expires_at;
+        let license_path = write_license_file(&license, &out_dir).unwrap();
+        let license_path = license_path.to_str().unwrap();
+
+        assert!(validate_license_at(license_path, issued_at().unix_timestamp()).unwrap());
+        assert!(validate_license_at(license_path, expires_at - 1).unwrap());
+        assert!(validate_license_at(license_path, expires_at).unwrap());
+        assert!(!validate_license_at(license_path, expires_at + 1).unwrap());
+
+        let _ = fs::remove_dir_all(&out_dir);
+    }
+
+    fn expiry(value: &str) -> i64 {
+        parse_expiry(value).unwrap().unix_timestamp()
+    }
+
+    #[test]
+    fn a_date_only_expiry_ends_with_that_day_utc() {
+        // 2027-03-04 23:59:59 UTC
+        assert_eq!(expiry("2027-03-04"), 1_804_204_799);
+    }
+
+    #[test]
+    fn expiry_times_take_a_z_or_an_offset() {
+        assert_eq!(expiry("2027-03-04T12:30"), 1_804_163_400);
+        assert_eq!(expiry("2027-03-04T12:30:15Z"), 1_804_163_415);
+        assert_eq!(expiry("2027-03-04T12:30+02:00"), 1_804_163_400 - 2 * 3_600);
+        assert_eq!(expiry("2027-03-04T12:30-05:30"), 1_804_163_400 + 5 * 3_600 + 30 * 60);
+    }
+
+    #[test]
+    fn out_of_range_expiry_fields_are_rejected() {
+        for value in ["2027-257-01", "2027-13-01", "2027-03-260", "2027-02-30", "2027-03-04T256:00", "2027-03-04T24:00",
+                      "2027-03-04T12:60", "2027-03-04T12:30:61", "2027-03-04T12:30+300:00", "2027-03-04T12:30+26:00",
+                      "4294967295-01-01", "2027-03", "2027-03-04T12", "not-a-date"] {
+            assert!(parse_expiry(value).is_err(), "{} was accepted", value);
+        }
+    }
+
+    #[test]
+    fn a_past_expiry_is_rejected() {
+        assert!(parse_validity("2025-12-31", issued_at()).is_err());
+        assert!(parse_validity("2026-01-01T00:00Z", issued_at()).is_err());
+        assert!(matches!(parse_validity("2026-01-01T00:00:01Z", issued_at()), Ok(Validity::Until(_))));
+        assert!(matches!(parse_validity("30", issued_at()), Ok(Validity::Days(30))));
+    }
+}